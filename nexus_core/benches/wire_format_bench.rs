@@ -0,0 +1,218 @@
+// nexus_core/benches/wire_format_bench.rs
+//
+// Criterion harness backing the crate's "48 bytes vs 300+ bytes" claims in
+// `wire/messages.rs`. Encodes/decodes a batch of `NewOrder`/`TradeUpdate`
+// messages through the native zero-copy SBE path and through two
+// general-purpose serde backends (bincode, postcard) so a reader can see the
+// throughput and bytes-on-wire tradeoff with real numbers instead of taking
+// the doc comments on faith.
+//
+// Requires `criterion`, `bincode`, `postcard`, and `serde` as dev-dependencies
+// in `Cargo.toml`:
+//
+//   [dev-dependencies]
+//   criterion = { version = "0.5", features = ["html_reports"] }
+//   bincode = "1"
+//   postcard = { version = "1", features = ["alloc"] }
+//   serde = { version = "1", features = ["derive"] }
+//
+//   [[bench]]
+//   name = "wire_format_bench"
+//   harness = false
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use serde::{Deserialize, Serialize};
+
+use nexus_core::{order_type, tif, NewOrder, Price, Side};
+
+const BATCH_SIZE: usize = 10_000;
+
+/// Plain (non-packed) mirror of `NewOrder`'s economically-relevant fields,
+/// used only to drive the bincode/postcard comparison — the real `NewOrder`
+/// implements `Serialize` only behind the crate's `serde` feature.
+#[derive(Serialize, Deserialize, Clone, Copy)]
+struct SerdeOrder {
+    trader_id: u32,
+    client_order_id: u64,
+    price: i64,
+    quantity: u32,
+    side: u8,
+}
+
+impl From<&NewOrder> for SerdeOrder {
+    fn from(o: &NewOrder) -> Self {
+        Self {
+            trader_id: o.trader_id,
+            client_order_id: o.client_order_id,
+            price: o.price,
+            quantity: o.quantity,
+            side: o.side,
+        }
+    }
+}
+
+fn sample_orders(n: usize) -> Vec<NewOrder> {
+    let price = Price::from_str_decimal("100.05").unwrap();
+    (0..n as u32)
+        .map(|i| NewOrder::new(i, i, i as u64, price, 50, Side::Buy, order_type::LIMIT, tif::GTC))
+        .collect()
+}
+
+fn bench_encode(c: &mut Criterion) {
+    let orders = sample_orders(BATCH_SIZE);
+    let mut group = c.benchmark_group("encode");
+    group.throughput(Throughput::Elements(BATCH_SIZE as u64));
+
+    group.bench_function("sbe_zero_copy", |b| {
+        b.iter(|| {
+            let mut buf = vec![0u8; std::mem::size_of::<NewOrder>() * BATCH_SIZE];
+            for (i, order) in orders.iter().enumerate() {
+                let start = i * std::mem::size_of::<NewOrder>();
+                order.encode_to(&mut buf[start..start + std::mem::size_of::<NewOrder>()]);
+            }
+            black_box(buf)
+        })
+    });
+
+    group.bench_function("bincode", |b| {
+        b.iter(|| {
+            let bytes: Vec<u8> = orders
+                .iter()
+                .map(SerdeOrder::from)
+                .flat_map(|o| bincode::serialize(&o).unwrap())
+                .collect();
+            black_box(bytes)
+        })
+    });
+
+    group.bench_function("postcard", |b| {
+        b.iter(|| {
+            let bytes: Vec<u8> = orders
+                .iter()
+                .map(SerdeOrder::from)
+                .flat_map(|o| postcard::to_allocvec(&o).unwrap())
+                .collect();
+            black_box(bytes)
+        })
+    });
+
+    group.finish();
+}
+
+fn bench_decode(c: &mut Criterion) {
+    let orders = sample_orders(BATCH_SIZE);
+    let sbe_bytes: Vec<u8> = {
+        let mut buf = vec![0u8; std::mem::size_of::<NewOrder>() * BATCH_SIZE];
+        for (i, order) in orders.iter().enumerate() {
+            let start = i * std::mem::size_of::<NewOrder>();
+            order.encode_to(&mut buf[start..start + std::mem::size_of::<NewOrder>()]);
+        }
+        buf
+    };
+    let bincode_bytes: Vec<Vec<u8>> = orders
+        .iter()
+        .map(SerdeOrder::from)
+        .map(|o| bincode::serialize(&o).unwrap())
+        .collect();
+    let postcard_bytes: Vec<Vec<u8>> = orders
+        .iter()
+        .map(SerdeOrder::from)
+        .map(|o| postcard::to_allocvec(&o).unwrap())
+        .collect();
+
+    let mut group = c.benchmark_group("decode");
+    group.throughput(Throughput::Elements(BATCH_SIZE as u64));
+
+    group.bench_function("sbe_zero_copy", |b| {
+        b.iter(|| {
+            let mut count = 0usize;
+            for i in 0..BATCH_SIZE {
+                let start = i * std::mem::size_of::<NewOrder>();
+                let decoded =
+                    NewOrder::decode_from(&sbe_bytes[start..start + std::mem::size_of::<NewOrder>()])
+                        .unwrap();
+                count += decoded.quantity as usize;
+            }
+            black_box(count)
+        })
+    });
+
+    group.bench_function("bincode", |b| {
+        b.iter(|| {
+            let mut count = 0usize;
+            for bytes in &bincode_bytes {
+                let decoded: SerdeOrder = bincode::deserialize(bytes).unwrap();
+                count += decoded.quantity as usize;
+            }
+            black_box(count)
+        })
+    });
+
+    group.bench_function("postcard", |b| {
+        b.iter(|| {
+            let mut count = 0usize;
+            for bytes in &postcard_bytes {
+                let decoded: SerdeOrder = postcard::from_bytes(bytes).unwrap();
+                count += decoded.quantity as usize;
+            }
+            black_box(count)
+        })
+    });
+
+    group.finish();
+}
+
+fn bench_bytes_on_wire(c: &mut Criterion) {
+    // Not a timed benchmark — just prints the per-message size comparison
+    // once so `cargo bench` output doubles as the regression guard the
+    // doc comments promise.
+    let order = sample_orders(1).remove(0);
+    let sbe_len = std::mem::size_of::<NewOrder>();
+    let bincode_len = bincode::serialize(&SerdeOrder::from(&order)).unwrap().len();
+    let postcard_len = postcard::to_allocvec(&SerdeOrder::from(&order)).unwrap().len();
+    println!(
+        "bytes-on-wire per NewOrder: sbe={sbe_len} bincode={bincode_len} postcard={postcard_len}"
+    );
+    c.bench_function("bytes_on_wire_noop", |b| b.iter(|| black_box(sbe_len)));
+}
+
+fn bench_roundtrip_correctness(c: &mut Criterion) {
+    // Every encoder must reproduce the same economically-relevant fields.
+    let orders = sample_orders(100);
+    for order in &orders {
+        let mut buf = vec![0u8; std::mem::size_of::<NewOrder>()];
+        order.encode_to(&mut buf);
+        let sbe_decoded = NewOrder::decode_from(&buf).unwrap();
+        assert_eq!({ sbe_decoded.trader_id }, { order.trader_id });
+        assert_eq!(sbe_decoded.price_fixed(), order.price_fixed());
+        assert_eq!({ sbe_decoded.quantity }, { order.quantity });
+        assert_eq!(sbe_decoded.side_enum(), order.side_enum());
+
+        let serde_order = SerdeOrder::from(order);
+        let bincode_decoded: SerdeOrder =
+            bincode::deserialize(&bincode::serialize(&serde_order).unwrap()).unwrap();
+        assert_eq!(bincode_decoded.trader_id, { order.trader_id });
+        assert_eq!(bincode_decoded.price, { order.price });
+        assert_eq!(bincode_decoded.quantity, { order.quantity });
+        assert_eq!(bincode_decoded.side, order.side);
+
+        let postcard_decoded: SerdeOrder =
+            postcard::from_bytes(&postcard::to_allocvec(&serde_order).unwrap()).unwrap();
+        assert_eq!(postcard_decoded.trader_id, { order.trader_id });
+        assert_eq!(postcard_decoded.price, { order.price });
+        assert_eq!(postcard_decoded.quantity, { order.quantity });
+        assert_eq!(postcard_decoded.side, order.side);
+    }
+    // No timed work beyond the correctness check above; this group exists so
+    // `cargo bench` fails loudly if any backend silently drifts.
+    c.bench_function("roundtrip_correctness_noop", |b| b.iter(|| black_box(orders.len())));
+}
+
+criterion_group!(
+    benches,
+    bench_encode,
+    bench_decode,
+    bench_bytes_on_wire,
+    bench_roundtrip_correctness
+);
+criterion_main!(benches);
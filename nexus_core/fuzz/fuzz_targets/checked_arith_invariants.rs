@@ -0,0 +1,62 @@
+// nexus_core/fuzz/fuzz_targets/checked_arith_invariants.rs
+//
+// Fuzzes `Price::checked_add`/`checked_sub`/`checked_notional` against a
+// reference computed in `i128` (which can't itself overflow for these
+// operand sizes): whenever the checked method returns `Some`, it must agree
+// with the `i128` reference exactly, and the result must fall inside
+// `[Price::MIN.raw(), Price::MAX.raw()]`; whenever the reference falls
+// outside `i64`'s range, the checked method must return `None` rather than
+// silently wrapping.
+//
+// Requires the same `fuzz/Cargo.toml` wiring described in
+// `parse_roundtrip.rs`; add to it:
+//
+//   [[bin]]
+//   name = "checked_arith_invariants"
+//   path = "fuzz_targets/checked_arith_invariants.rs"
+//
+// Run with `cargo fuzz run checked_arith_invariants`.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use nexus_core::Price;
+
+#[derive(Debug, arbitrary::Arbitrary)]
+struct Input {
+    a: Price,
+    b: Price,
+    qty: u32,
+}
+
+fn in_range(raw: i64) -> bool {
+    (Price::MIN.raw()..=Price::MAX.raw()).contains(&raw)
+}
+
+fuzz_target!(|input: Input| {
+    let Input { a, b, qty } = input;
+
+    let sum_ref = a.raw() as i128 + b.raw() as i128;
+    match a.checked_add(b) {
+        Some(sum) => {
+            assert_eq!(sum.raw() as i128, sum_ref);
+            assert!(in_range(sum.raw()));
+        }
+        None => assert!(sum_ref < i64::MIN as i128 || sum_ref > i64::MAX as i128),
+    }
+
+    let diff_ref = a.raw() as i128 - b.raw() as i128;
+    match a.checked_sub(b) {
+        Some(diff) => {
+            assert_eq!(diff.raw() as i128, diff_ref);
+            assert!(in_range(diff.raw()));
+        }
+        None => assert!(diff_ref < i64::MIN as i128 || diff_ref > i64::MAX as i128),
+    }
+
+    let notional_ref = a.raw() as i128 * qty as i128;
+    match a.checked_notional(qty) {
+        Some(notional) => assert_eq!(notional as i128, notional_ref),
+        None => assert!(notional_ref < i64::MIN as i128 || notional_ref > i64::MAX as i128),
+    }
+});
@@ -0,0 +1,29 @@
+// nexus_core/fuzz/fuzz_targets/parse_never_panics.rs
+//
+// Fuzzes `Price::from_str_decimal` with arbitrary UTF-8 input: it must
+// always return `Ok`/`Err`, never panic, regardless of how malformed the
+// string is. A seed corpus of known-tricky strings lives alongside this
+// target under `fuzz/corpus/parse_never_panics/` (a leading-dot fraction, a
+// trailing-dot integer, a bare sign, an oversized fraction, ...).
+//
+// Requires the same `fuzz/Cargo.toml` wiring described in
+// `parse_roundtrip.rs`; add to it:
+//
+//   [[bin]]
+//   name = "parse_never_panics"
+//   path = "fuzz_targets/parse_never_panics.rs"
+//
+// Run with `cargo fuzz run parse_never_panics`.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use nexus_core::Price;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(s) = std::str::from_utf8(data) {
+        // The only assertion here is "doesn't panic" — Ok or Err are both
+        // fine outcomes for malformed input.
+        let _ = Price::from_str_decimal(s);
+    }
+});
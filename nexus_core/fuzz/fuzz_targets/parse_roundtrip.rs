@@ -0,0 +1,30 @@
+// nexus_core/fuzz/fuzz_targets/parse_roundtrip.rs
+//
+// Fuzzes the `Display`/`from_str_decimal` round trip: for any `Price` the
+// fuzzer can generate (via `arbitrary`, gated behind the crate's `fuzz`
+// feature — see `Price`/`Quantity`'s `cfg_attr(feature = "fuzz", ...)`
+// derives in `types/fixed_point.rs`), formatting it and parsing the result
+// back must reproduce the exact same raw value.
+//
+// Requires a `fuzz/Cargo.toml` (as `cargo fuzz init` generates) with:
+//
+//   [dependencies]
+//   libfuzzer-sys = "0.4"
+//   nexus_core = { path = "..", features = ["fuzz"] }
+//
+//   [[bin]]
+//   name = "parse_roundtrip"
+//   path = "fuzz_targets/parse_roundtrip.rs"
+//
+// Run with `cargo fuzz run parse_roundtrip`.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use nexus_core::Price;
+
+fuzz_target!(|price: Price| {
+    let formatted = format!("{}", price);
+    let parsed = Price::from_str_decimal(&formatted).expect("Display output must re-parse");
+    assert_eq!(parsed, price, "round trip changed {} into {}", formatted, parsed);
+});
@@ -0,0 +1,178 @@
+// nexus_core/src/ledger/mod.rs
+//
+// The Settlement Ledger.
+//
+// Consumes `Fill`s produced by `MatchingEngine::submit_order` (or replayed
+// from the WAL) and tracks, per trader, a running signed position (base
+// quantity) and cash balance (quote), plus fees charged under the engine's
+// `FeeSchedule`. Unlike the Guardian (pre-trade margin locking), the Ledger
+// never blocks an order — it is a pure post-trade bookkeeping layer, driven
+// entirely by replaying fills, so P&L and fee revenue over a sequence of
+// events stay deterministic.
+
+use std::collections::BTreeMap;
+
+use crate::matching::{Fill, FeeSchedule};
+use crate::types::Side;
+
+/// Per-trader running position and cash.
+#[derive(Debug, Clone, Copy, Default)]
+struct TraderLedger {
+    /// Signed base-asset position. Positive = long, negative = short.
+    position: i64,
+    /// Quote-asset cash balance, fixed-point.
+    cash: i64,
+}
+
+/// Tracks per-trader position, cash, and fees across a stream of `Fill`s.
+#[derive(Debug, Clone)]
+pub struct Ledger {
+    traders: BTreeMap<u32, TraderLedger>,
+    fee_schedule: FeeSchedule,
+    /// Total fees collected across all traders — the exchange's fee revenue.
+    fees_collected: i64,
+}
+
+impl Ledger {
+    pub fn new(fee_schedule: FeeSchedule) -> Self {
+        Self {
+            traders: BTreeMap::new(),
+            fee_schedule,
+            fees_collected: 0,
+        }
+    }
+
+    /// Settle one `Fill`, given which side the taker was on. The maker is
+    /// always the opposite side. Updates both traders' position and cash
+    /// for the trade's notional (`fill.price * fill.qty`), then charges
+    /// each side its configured fee rate (a negative maker rate credits
+    /// cash as a rebate instead).
+    pub fn apply_fill(&mut self, fill: &Fill, taker_side: Side) {
+        let notional = fill.price * fill.qty as i64;
+        let taker_fee = Self::fee_amount(notional, self.fee_schedule.taker_fee_bps);
+        let maker_fee = Self::fee_amount(notional, self.fee_schedule.maker_fee_bps);
+
+        let taker = self.traders.entry(fill.taker_trader_id).or_default();
+        Self::apply_side(taker, taker_side, fill.qty, notional);
+        taker.cash -= taker_fee;
+
+        let maker_side = taker_side.opposite();
+        let maker = self.traders.entry(fill.maker_trader_id).or_default();
+        Self::apply_side(maker, maker_side, fill.qty, notional);
+        maker.cash -= maker_fee;
+
+        self.fees_collected += taker_fee + maker_fee;
+    }
+
+    /// Settle every fill in `fills`, all taken by the same `taker_side` —
+    /// the common case of applying `MatchResult::fills` from a single
+    /// `submit_order` call.
+    pub fn apply_fills(&mut self, fills: &[Fill], taker_side: Side) {
+        for fill in fills {
+            self.apply_fill(fill, taker_side);
+        }
+    }
+
+    /// Apply one side of a trade: position moves by `qty` in `side.sign()`'s
+    /// direction, cash moves by `notional` in the opposite direction (a buy
+    /// spends cash for base; a sell receives cash for base).
+    fn apply_side(ledger: &mut TraderLedger, side: Side, qty: u32, notional: i64) {
+        ledger.position += side.sign() as i64 * qty as i64;
+        ledger.cash -= side.sign() as i64 * notional;
+    }
+
+    /// Basis-point fee on a fixed-point notional: `notional * bps / 10_000`.
+    fn fee_amount(notional: i64, bps: i64) -> i64 {
+        (notional * bps) / 10_000
+    }
+
+    /// Current signed position for a trader (0 if never traded).
+    pub fn position(&self, trader_id: u32) -> i64 {
+        self.traders.get(&trader_id).map(|t| t.position).unwrap_or(0)
+    }
+
+    /// Current cash balance for a trader (0 if never traded).
+    pub fn cash(&self, trader_id: u32) -> i64 {
+        self.traders.get(&trader_id).map(|t| t.cash).unwrap_or(0)
+    }
+
+    /// Total fees collected across all traders so far.
+    pub fn fees_collected(&self) -> i64 {
+        self.fees_collected
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const S: i64 = crate::SCALE;
+
+    fn price(v: i64) -> i64 {
+        v * S
+    }
+
+    fn fill(maker_trader_id: u32, taker_trader_id: u32, price: i64, qty: u32) -> Fill {
+        Fill {
+            maker_order_id: 1, taker_order_id: 2, maker_trader_id, taker_trader_id, price, qty,
+            timestamp_ns: 1, taker_fee: 0, maker_fee: 0,
+        }
+    }
+
+    #[test]
+    fn test_apply_fill_updates_positions_and_cash_with_no_fees() {
+        let mut ledger = Ledger::new(FeeSchedule::default());
+        // Trader 2 buys 10 @ $100 from trader 1 (the resting maker).
+        ledger.apply_fill(&fill(1, 2, price(100), 10), Side::Buy);
+
+        assert_eq!(ledger.position(2), 10);
+        assert_eq!(ledger.cash(2), -price(1_000));
+        assert_eq!(ledger.position(1), -10);
+        assert_eq!(ledger.cash(1), price(1_000));
+        assert_eq!(ledger.fees_collected(), 0);
+    }
+
+    #[test]
+    fn test_apply_fill_charges_taker_and_maker_fees() {
+        let fee_schedule = FeeSchedule { taker_fee_bps: 10, maker_fee_bps: 5 }; // 0.10% / 0.05%
+        let mut ledger = Ledger::new(fee_schedule);
+        ledger.apply_fill(&fill(1, 2, price(100), 10), Side::Buy);
+
+        let notional = price(1_000);
+        let taker_fee = notional * 10 / 10_000;
+        let maker_fee = notional * 5 / 10_000;
+
+        assert_eq!(ledger.cash(2), -notional - taker_fee);
+        assert_eq!(ledger.cash(1), notional - maker_fee);
+        assert_eq!(ledger.fees_collected(), taker_fee + maker_fee);
+    }
+
+    #[test]
+    fn test_negative_maker_fee_is_a_rebate() {
+        let fee_schedule = FeeSchedule { taker_fee_bps: 0, maker_fee_bps: -5 };
+        let mut ledger = Ledger::new(fee_schedule);
+        ledger.apply_fill(&fill(1, 2, price(100), 10), Side::Buy);
+
+        let notional = price(1_000);
+        let rebate = notional * 5 / 10_000;
+        assert_eq!(ledger.cash(1), notional + rebate);
+        assert_eq!(ledger.fees_collected(), -rebate);
+    }
+
+    #[test]
+    fn test_apply_fills_accumulates_across_multiple_trades() {
+        let mut ledger = Ledger::new(FeeSchedule::default());
+        let fills = vec![fill(1, 2, price(100), 5), fill(1, 2, price(100), 5)];
+        ledger.apply_fills(&fills, Side::Buy);
+
+        assert_eq!(ledger.position(2), 10);
+        assert_eq!(ledger.position(1), -10);
+    }
+
+    #[test]
+    fn test_unknown_trader_defaults_to_zero() {
+        let ledger = Ledger::new(FeeSchedule::default());
+        assert_eq!(ledger.position(99), 0);
+        assert_eq!(ledger.cash(99), 0);
+    }
+}
@@ -9,19 +9,25 @@ pub mod types;
 pub mod wire;
 pub mod matching;
 pub mod risk;
+pub mod ledger;
 pub mod persistence;
 
 #[cfg(feature = "python")]
 pub mod python;
 
-pub use types::{Price, Quantity, Side, SCALE};
+pub use types::{Price, Quantity, ScaledPrice, Side, SCALE};
 pub use wire::messages::{
     MessageHeader, NewOrder, OrderCancel, TradeUpdate,
     msg_type, order_type, tif,
 };
-pub use matching::{MatchingEngine, OrderBook, Fill, MatchResult, L2Level, RejectReason, RiskConfig};
-pub use risk::{Guardian, Account, GuardianConfig, GuardianReject, VolatilityBandConfig};
-pub use persistence::{Sentinel, NexusExchange, ExchangeResult, ExchangeError, JournalHeader};
+pub use wire::decode::{decode, WireError, WireMessage};
+pub use matching::{
+    MatchingEngine, OrderBook, Fill, FeeSchedule, MatchResult, L2Level, OrderType, PeggedOrder,
+    RejectReason, RiskConfig, StopOrder, StpMode, TimeInForce,
+};
+pub use risk::{Guardian, Account, GuardianConfig, GuardianReject, LiquidationReport, VolatilityBandConfig};
+pub use ledger::Ledger;
+pub use persistence::{Sentinel, SegmentedSentinel, NexusExchange, ExchangeResult, ExchangeError, JournalHeader};
 
 /// The PyO3 module entry point.
 /// Compiled only with the `python` feature via maturin.
@@ -4,16 +4,19 @@
 //
 // This module implements the core Aggressor-Maker matching loop with:
 // 1. Price-Time Priority (best price first, then FIFO at each price level).
-// 2. Self-Trade Prevention (STP): cancel resting orders instead of self-matching.
+// 2. Self-Trade Prevention (STP): configurable resolution of same-trader matches.
 // 3. Pre-Trade Risk Guardian: Fat-Finger price checks, max quantity limits.
 // 4. Deterministic execution: same input sequence → identical output every time.
 
 pub mod orderbook;
 
 pub use orderbook::{
-    Fill, L2Level, MatchResult, Order, OrderBook, RejectReason, RiskConfig,
+    AmmPool, Fill, FeeSchedule, L2Level, MatchResult, Order, OrderBook, OrderType, PeggedOrder,
+    RejectReason, RiskConfig, StopOrder, StpMode, TimeInForce, AMM_POOL_TRADER_ID,
 };
 
+use std::collections::BTreeMap;
+
 use crate::types::Side;
 
 /// The Matching Engine.
@@ -36,6 +39,23 @@ pub struct MatchingEngine {
     /// In production, this would read from a hardware clock.
     /// In simulation, it increments monotonically per event.
     ts_counter: u64,
+    /// Buy-stops parked by `stop_price`, triggered once
+    /// `last_trade_price >= stop_price`.
+    buy_stops: BTreeMap<i64, Vec<StopOrder>>,
+    /// Sell-stops parked by `stop_price`, triggered once
+    /// `last_trade_price <= stop_price`.
+    sell_stops: BTreeMap<i64, Vec<StopOrder>>,
+    /// Current external reference price pegged orders track. `None` until
+    /// `set_oracle_price` is called at least once.
+    oracle_price: Option<i64>,
+    /// Metadata for every pegged order currently resting on the book,
+    /// consulted (and updated) by `set_oracle_price` to reprice them.
+    pegged_orders: Vec<PeggedOrder>,
+    /// Optional constant-product AMM liquidity pool, consulted by
+    /// `submit_order` once the book is exhausted up to the order's limit
+    /// price. `None` (the default) means no AMM is configured — an order
+    /// only ever matches the book, exactly as before this existed.
+    amm_pool: Option<AmmPool>,
 }
 
 impl MatchingEngine {
@@ -49,6 +69,11 @@ impl MatchingEngine {
             risk_config,
             next_trade_id: 0,
             ts_counter: 0,
+            buy_stops: BTreeMap::new(),
+            sell_stops: BTreeMap::new(),
+            oracle_price: None,
+            pegged_orders: Vec::new(),
+            amm_pool: None,
         }
     }
 
@@ -58,6 +83,19 @@ impl MatchingEngine {
         self.ts_counter
     }
 
+    /// Read the deterministic tick counter without advancing it. Used by the
+    /// checkpoint subsystem to snapshot it.
+    pub(crate) fn ts_counter(&self) -> u64 {
+        self.ts_counter
+    }
+
+    /// Restore the tick counter from a checkpoint, so timestamps assigned
+    /// after recovery continue the original sequence instead of restarting
+    /// from zero.
+    pub(crate) fn restore_ts_counter(&mut self, value: u64) {
+        self.ts_counter = value;
+    }
+
     /// Allocate the next unique trade ID.
     fn next_trade_id(&mut self) -> u64 {
         self.next_trade_id += 1;
@@ -71,18 +109,21 @@ impl MatchingEngine {
     /// Validate an incoming order against the risk configuration.
     ///
     /// Checks performed (in order):
-    /// 1. Price > 0 (for limit orders).
-    /// 2. Quantity > 0.
-    /// 3. Quantity ≤ max_order_qty (hard cap).
-    /// 4. Fat-Finger: price within ±configured% of last trade price.
+    /// 1. Quantity > 0.
+    /// 2. Quantity ≤ max_order_qty (hard cap).
+    /// 3. Quantity ≥ min_order_qty (skipped if `min_order_qty` is `0`).
+    /// 4. Quantity is a multiple of lot_size (skipped if `lot_size` is `0`).
+    /// 5. Price > 0 (for every type except `Market`, which has no real price).
+    /// 6. Price is a multiple of tick_size (skipped for `Market`, and if
+    ///    `tick_size` is `0`).
+    /// 7. Fat-Finger: price within ±configured% of last trade price
+    ///    (skipped for `Market`, since its price is just an implicit limit).
     pub fn validate_risk(
         &self,
+        order_type: OrderType,
         price: i64,
         qty: u32,
     ) -> Result<(), RejectReason> {
-        if price <= 0 {
-            return Err(RejectReason::InvalidPrice);
-        }
         if qty == 0 {
             return Err(RejectReason::InvalidQuantity);
         }
@@ -92,6 +133,26 @@ impl MatchingEngine {
                 max: self.risk_config.max_order_qty,
             });
         }
+        if self.risk_config.min_order_qty > 0 && qty < self.risk_config.min_order_qty {
+            return Err(RejectReason::BelowMinSize {
+                requested: qty,
+                min: self.risk_config.min_order_qty,
+            });
+        }
+        if self.risk_config.lot_size > 0 && qty % self.risk_config.lot_size != 0 {
+            return Err(RejectReason::InvalidLot);
+        }
+
+        if order_type == OrderType::Market {
+            return Ok(());
+        }
+
+        if price <= 0 {
+            return Err(RejectReason::InvalidPrice);
+        }
+        if self.risk_config.tick_size > 0 && price % self.risk_config.tick_size != 0 {
+            return Err(RejectReason::InvalidTick);
+        }
 
         // Fat-Finger check: only if we have a reference price.
         if let Some(ref_price) = self.book.last_trade_price {
@@ -107,28 +168,152 @@ impl MatchingEngine {
         Ok(())
     }
 
+    /// Total quantity available to fill a Buy aggressor against the ask book
+    /// up to `limit_price`, without mutating the book. Orders belonging to
+    /// `taker_trader_id` are excluded since STP would cancel rather than
+    /// fill them. Stops scanning once `target` is reached.
+    fn fillable_qty_against_asks(&self, taker_trader_id: u32, limit_price: i64, target: u32) -> u32 {
+        let mut available: u32 = 0;
+        for (&level_price, level) in self.book.asks.levels.iter() {
+            if level_price > limit_price || available >= target {
+                break;
+            }
+            for maker in level.iter() {
+                if maker.trader_id == taker_trader_id {
+                    continue;
+                }
+                available = available.saturating_add(maker.qty);
+                if available >= target {
+                    break;
+                }
+            }
+        }
+        available
+    }
+
+    /// Sell-side mirror of `fillable_qty_against_asks`, walking the bid book
+    /// from the highest price down.
+    fn fillable_qty_against_bids(&self, taker_trader_id: u32, limit_price: i64, target: u32) -> u32 {
+        let mut available: u32 = 0;
+        for (&level_price, level) in self.book.bids.levels.iter().rev() {
+            if level_price < limit_price || available >= target {
+                break;
+            }
+            for maker in level.iter() {
+                if maker.trader_id == taker_trader_id {
+                    continue;
+                }
+                available = available.saturating_add(maker.qty);
+                if available >= target {
+                    break;
+                }
+            }
+        }
+        available
+    }
+
     // -----------------------------------------------------------------------
     // THE CORE MATCHING LOOP (Aggressor-Maker Algorithm)
     // -----------------------------------------------------------------------
 
-    /// Submit a new limit order to the engine.
+    /// Submit a new order to the engine.
     ///
     /// This is the primary entry point. It performs:
     /// 1. Pre-trade risk validation (Guardian).
-    /// 2. Aggressor phase: cross the opposing book.
-    /// 3. Self-Trade Prevention: cancel resting orders of the same trader.
-    /// 4. Maker phase: post remaining quantity to the book.
+    /// 2. Order-type-specific pre-checks (`PostOnly`/`PostOnlySlide` crossing,
+    ///    `FillOrKill` fillability) that can reject or reprice before any
+    ///    state is mutated.
+    /// 3. Aggressor phase: cross the opposing book (skipped entirely for a
+    ///    repriced `PostOnlySlide`, since it's guaranteed not to cross).
+    /// 4. Self-Trade Prevention: resolve same-trader matches per
+    ///    `RiskConfig::stp_mode`.
+    /// 5. Maker phase: post remaining quantity to the book — skipped for
+    ///    `Market`, `ImmediateOrCancel`, and `FillOrKill`, which never rest.
+    ///
+    /// `tif`/`expiry_ts` only matter for the resting leg: an expired maker
+    /// is reaped lazily the next time it is considered for a match rather
+    /// than proactively, bounded per call by `RiskConfig::max_expired_reap`.
     ///
     /// Returns `Ok(MatchResult)` on success, `Err(RejectReason)` if rejected.
     pub fn submit_order(
         &mut self,
         trader_id: u32,
         side: Side,
+        order_type: OrderType,
         price: i64,
         qty: u32,
+        tif: TimeInForce,
+        expiry_ts: Option<u64>,
     ) -> Result<MatchResult, RejectReason> {
+        // `Market` never blocks on price, so it trades at the most
+        // permissive possible limit instead of the caller's (likely
+        // meaningless) price argument.
+        let limit_price = match order_type {
+            OrderType::Market => match side {
+                Side::Buy => i64::MAX,
+                Side::Sell => 1,
+            },
+            _ => price,
+        };
+
         // Phase 0: Guardian risk checks.
-        self.validate_risk(price, qty)?;
+        self.validate_risk(order_type, limit_price, qty)?;
+
+        // Phase 1: order-type-specific pre-checks. None of these mutate the
+        // book, so a rejection here leaves no trace.
+        let mut resting_price = limit_price;
+        let mut skip_aggressor_phase = false;
+        match order_type {
+            OrderType::PostOnly => {
+                let would_cross = match side {
+                    Side::Buy => match self.book.best_ask() {
+                        Some(best_ask) => limit_price >= best_ask,
+                        None => false,
+                    },
+                    Side::Sell => match self.book.best_bid() {
+                        Some(best_bid) => limit_price <= best_bid,
+                        None => false,
+                    },
+                };
+                if would_cross {
+                    return Err(RejectReason::PostOnlyWouldCross);
+                }
+            }
+            OrderType::PostOnlySlide => match side {
+                Side::Buy => {
+                    if let Some(best_ask) = self.book.best_ask() {
+                        if limit_price >= best_ask {
+                            resting_price = limit_price.min(best_ask - 1);
+                            skip_aggressor_phase = true;
+                        }
+                    }
+                }
+                Side::Sell => {
+                    if let Some(best_bid) = self.book.best_bid() {
+                        if limit_price <= best_bid {
+                            resting_price = limit_price.max(best_bid + 1);
+                            skip_aggressor_phase = true;
+                        }
+                    }
+                }
+            },
+            OrderType::FillOrKill => {
+                let book_available = match side {
+                    Side::Buy => self.fillable_qty_against_asks(trader_id, limit_price, qty),
+                    Side::Sell => self.fillable_qty_against_bids(trader_id, limit_price, qty),
+                };
+                // A thin book shouldn't make an otherwise-fillable FOK
+                // reject just because the AMM pool could have covered the
+                // rest — so AMM capacity counts too.
+                let available = book_available.saturating_add(
+                    self.amm_fillable_qty(side, limit_price, qty.saturating_sub(book_available)),
+                );
+                if available < qty {
+                    return Err(RejectReason::FillOrKillUnfulfillable);
+                }
+            }
+            OrderType::Limit | OrderType::Market | OrderType::ImmediateOrCancel => {}
+        }
 
         let ts = self.tick();
         let order_id = self.book.next_order_id();
@@ -137,49 +322,598 @@ impl MatchingEngine {
         // but realistically << qty). Start with 8 to avoid early reallocs.
         let mut fills: Vec<Fill> = Vec::with_capacity(8);
         let mut stp_cancels: Vec<u64> = Vec::new();
+        let mut expired_cancels: Vec<u64> = Vec::new();
+        let mut taker_stp_cancelled = false;
         let mut remaining_qty = qty;
 
         // Phase A: The Aggressor — cross the opposing book.
+        if !skip_aggressor_phase {
+            match side {
+                Side::Buy => {
+                    // Buy crosses against Asks (lowest price first).
+                    self.match_against_asks(
+                        trader_id, order_id, resting_price, &mut remaining_qty,
+                        &mut fills, &mut stp_cancels, &mut expired_cancels,
+                        &mut taker_stp_cancelled, ts,
+                    );
+                }
+                Side::Sell => {
+                    // Sell crosses against Bids (highest price first).
+                    self.match_against_bids(
+                        trader_id, order_id, resting_price, &mut remaining_qty,
+                        &mut fills, &mut stp_cancels, &mut expired_cancels,
+                        &mut taker_stp_cancelled, ts,
+                    );
+                }
+            }
+
+            // Phase B: route whatever the book couldn't fill against the AMM
+            // pool, if one is configured — the thin-book case this exists
+            // for. No-op if `amm_pool` is `None` or has no capacity at
+            // `resting_price`.
+            if remaining_qty > 0 {
+                if let Some((fill, base_filled)) =
+                    self.amm_fill(side, trader_id, order_id, resting_price, remaining_qty, ts)
+                {
+                    remaining_qty -= base_filled;
+                    fills.push(fill);
+                }
+            }
+        }
+
+        // Phase C: The Maker — post remaining quantity to the book. Market,
+        // IOC, and FOK orders never rest: leftover quantity is simply lost.
+        let never_rests = matches!(
+            order_type,
+            OrderType::Market | OrderType::ImmediateOrCancel | OrderType::FillOrKill
+        );
+        let resting_qty = if never_rests { 0 } else { remaining_qty };
+        let cancelled_qty = if never_rests { remaining_qty } else { 0 };
+
+        if !never_rests && remaining_qty > 0 {
+            let resting = Order {
+                trader_id,
+                order_id,
+                price: resting_price,
+                qty: remaining_qty,
+                ts,
+                tif,
+                expiry_ts,
+            };
+            self.book.insert(side, resting);
+        }
+
+        // Phase D: promote any stops that this order's fill(s) triggered,
+        // cascading until the price stops moving or the depth cap is hit.
+        let mut triggered_fills: Vec<Fill> = Vec::new();
+        self.trigger_stops_cascade(&mut triggered_fills, &mut stp_cancels, &mut expired_cancels);
+
+        Ok(MatchResult {
+            order_id,
+            fills,
+            stp_cancels,
+            stp_mode: self.risk_config.stp_mode,
+            taker_stp_cancelled,
+            resting_qty,
+            cancelled_qty,
+            effective_order_type: order_type,
+            effective_price: resting_price,
+            triggered_fills,
+            expired_cancels,
+        })
+    }
+
+    /// Park a conditional stop order off-book until its trigger condition
+    /// fires.
+    ///
+    /// A buy-stop (`side == Buy`) triggers once `last_trade_price >=
+    /// stop_price`; a sell-stop triggers once `last_trade_price <=
+    /// stop_price`. `limit_price == None` is a stop-market order — once
+    /// triggered it trades through the book like a `Market` order and never
+    /// rests. `limit_price == Some(p)` is a stop-limit order — once
+    /// triggered it behaves like a `Limit` order at `p`.
+    ///
+    /// Only runs the quantity checks from the Guardian: a stop order has no
+    /// real traded price to Fat-Finger-check until it triggers.
+    pub fn submit_stop_order(
+        &mut self,
+        trader_id: u32,
+        side: Side,
+        stop_price: i64,
+        limit_price: Option<i64>,
+        qty: u32,
+    ) -> Result<u64, RejectReason> {
+        if qty == 0 {
+            return Err(RejectReason::InvalidQuantity);
+        }
+        if qty > self.risk_config.max_order_qty {
+            return Err(RejectReason::MaxQuantity {
+                requested: qty,
+                max: self.risk_config.max_order_qty,
+            });
+        }
+        if stop_price <= 0 {
+            return Err(RejectReason::InvalidPrice);
+        }
+
+        let ts = self.tick();
+        let order_id = self.book.next_order_id();
+        let stop = StopOrder { trader_id, order_id, side, stop_price, limit_price, qty, ts };
         match side {
-            Side::Buy => {
-                // Buy crosses against Asks (lowest price first).
-                self.match_against_asks(
-                    trader_id, order_id, price, &mut remaining_qty,
-                    &mut fills, &mut stp_cancels, ts,
-                );
+            Side::Buy => self.buy_stops.entry(stop_price).or_default().push(stop),
+            Side::Sell => self.sell_stops.entry(stop_price).or_default().push(stop),
+        }
+        Ok(order_id)
+    }
+
+    /// Promote every stop whose trigger condition is met by the current
+    /// `last_trade_price`, matching each as a live order and repeating until
+    /// a wave triggers nothing new or `max_stop_cascade_depth` waves have
+    /// run — mirroring the bounded-processing guard other reaping passes in
+    /// this engine use to cap worst-case work.
+    fn trigger_stops_cascade(
+        &mut self,
+        fills_out: &mut Vec<Fill>,
+        stp_cancels_out: &mut Vec<u64>,
+        expired_cancels_out: &mut Vec<u64>,
+    ) {
+        for _ in 0..self.risk_config.max_stop_cascade_depth {
+            let last_trade_price = match self.book.last_trade_price {
+                Some(p) => p,
+                None => break,
+            };
+
+            let mut triggered: Vec<StopOrder> = Vec::new();
+
+            let buy_keys: Vec<i64> =
+                self.buy_stops.range(..=last_trade_price).map(|(k, _)| *k).collect();
+            for key in buy_keys {
+                if let Some(level) = self.buy_stops.remove(&key) {
+                    triggered.extend(level);
+                }
             }
-            Side::Sell => {
-                // Sell crosses against Bids (highest price first).
-                self.match_against_bids(
-                    trader_id, order_id, price, &mut remaining_qty,
-                    &mut fills, &mut stp_cancels, ts,
-                );
+
+            let sell_keys: Vec<i64> =
+                self.sell_stops.range(last_trade_price..).map(|(k, _)| *k).collect();
+            for key in sell_keys {
+                if let Some(level) = self.sell_stops.remove(&key) {
+                    triggered.extend(level);
+                }
+            }
+
+            if triggered.is_empty() {
+                break;
             }
+
+            // Preserve relative time priority among stops triggered in the
+            // same wave, even though they came from two different maps.
+            triggered.sort_by_key(|s| s.ts);
+
+            for stop in triggered {
+                self.trigger_order_and_match(stop, fills_out, stp_cancels_out, expired_cancels_out);
+            }
+        }
+    }
+
+    /// Convert one triggered stop into a live order and run it through the
+    /// matching loop: a stop-market order trades at the implicit `Market`
+    /// limit and never rests; a stop-limit order rests any leftover
+    /// quantity at its limit price, like a regular `Limit` order.
+    fn trigger_order_and_match(
+        &mut self,
+        stop: StopOrder,
+        fills_out: &mut Vec<Fill>,
+        stp_cancels_out: &mut Vec<u64>,
+        expired_cancels_out: &mut Vec<u64>,
+    ) {
+        let ts = self.tick();
+        let limit_price = stop.limit_price.unwrap_or(match stop.side {
+            Side::Buy => i64::MAX,
+            Side::Sell => 1,
+        });
+
+        let mut remaining_qty = stop.qty;
+        // A triggered stop's own STP outcome isn't separately surfaced on
+        // `MatchResult` (which only reports the top-level submitted order's
+        // STP result), so the flag here is a throwaway.
+        let mut taker_cancelled = false;
+        match stop.side {
+            Side::Buy => self.match_against_asks(
+                stop.trader_id, stop.order_id, limit_price, &mut remaining_qty,
+                fills_out, stp_cancels_out, expired_cancels_out, &mut taker_cancelled, ts,
+            ),
+            Side::Sell => self.match_against_bids(
+                stop.trader_id, stop.order_id, limit_price, &mut remaining_qty,
+                fills_out, stp_cancels_out, expired_cancels_out, &mut taker_cancelled, ts,
+            ),
+        }
+
+        if stop.limit_price.is_some() && remaining_qty > 0 {
+            let resting = Order {
+                trader_id: stop.trader_id,
+                order_id: stop.order_id,
+                price: limit_price,
+                qty: remaining_qty,
+                ts,
+                tif: TimeInForce::GTC,
+                expiry_ts: None,
+            };
+            self.book.insert(stop.side, resting);
+        }
+    }
+
+    /// Submit an order whose resting price tracks `oracle_price + peg_offset`
+    /// instead of an absolute price the caller supplies.
+    ///
+    /// Crosses the opposing book just like a `Limit` order at the clamped
+    /// peg price, then rests any leftover quantity and registers it so
+    /// `set_oracle_price` can find and reprice it later.
+    pub fn submit_pegged_order(
+        &mut self,
+        trader_id: u32,
+        side: Side,
+        peg_offset: i64,
+        qty: u32,
+        limit_price: Option<i64>,
+    ) -> Result<MatchResult, RejectReason> {
+        if qty == 0 {
+            return Err(RejectReason::InvalidQuantity);
+        }
+        if qty > self.risk_config.max_order_qty {
+            return Err(RejectReason::MaxQuantity {
+                requested: qty,
+                max: self.risk_config.max_order_qty,
+            });
+        }
+        let oracle_price = self.oracle_price.ok_or(RejectReason::OraclePriceUnset)?;
+
+        let ts = self.tick();
+        let order_id = self.book.next_order_id();
+        let peg_price = self.clamp_peg_price(side, oracle_price + peg_offset, limit_price);
+
+        let mut fills: Vec<Fill> = Vec::new();
+        let mut stp_cancels: Vec<u64> = Vec::new();
+        let mut expired_cancels: Vec<u64> = Vec::new();
+        let mut taker_stp_cancelled = false;
+        let mut remaining_qty = qty;
+        match side {
+            Side::Buy => self.match_against_asks(
+                trader_id, order_id, peg_price, &mut remaining_qty,
+                &mut fills, &mut stp_cancels, &mut expired_cancels,
+                &mut taker_stp_cancelled, ts,
+            ),
+            Side::Sell => self.match_against_bids(
+                trader_id, order_id, peg_price, &mut remaining_qty,
+                &mut fills, &mut stp_cancels, &mut expired_cancels,
+                &mut taker_stp_cancelled, ts,
+            ),
         }
 
-        // Phase C: The Maker — post remaining quantity to the book.
+        let resting_qty = remaining_qty;
         if remaining_qty > 0 {
             let resting = Order {
                 trader_id,
                 order_id,
-                price,
+                price: peg_price,
                 qty: remaining_qty,
                 ts,
+                tif: TimeInForce::GTC,
+                expiry_ts: None,
             };
-            match side {
-                Side::Buy => self.book.bids.insert(resting),
-                Side::Sell => self.book.asks.insert(resting),
-            }
+            self.book.insert(side, resting);
+            self.pegged_orders.push(PeggedOrder {
+                trader_id,
+                order_id,
+                side,
+                peg_offset,
+                limit_price,
+                current_price: peg_price,
+                ts,
+            });
         }
 
+        let mut triggered_fills: Vec<Fill> = Vec::new();
+        self.trigger_stops_cascade(&mut triggered_fills, &mut stp_cancels, &mut expired_cancels);
+
         Ok(MatchResult {
             order_id,
             fills,
             stp_cancels,
-            resting_qty: remaining_qty,
+            stp_mode: self.risk_config.stp_mode,
+            taker_stp_cancelled,
+            resting_qty,
+            cancelled_qty: 0,
+            effective_order_type: OrderType::Limit,
+            effective_price: peg_price,
+            triggered_fills,
+            expired_cancels,
         })
     }
 
+    /// The fee schedule currently applied to fills (see `Fill::taker_fee` /
+    /// `Fill::maker_fee`).
+    pub fn fee_schedule(&self) -> FeeSchedule {
+        self.risk_config.fee_schedule
+    }
+
+    /// Change the fee schedule applied to fills from now on. Already-resting
+    /// orders are unaffected — only fills generated after this call use the
+    /// new rates.
+    pub fn set_fee_schedule(&mut self, fee_schedule: FeeSchedule) {
+        self.risk_config.fee_schedule = fee_schedule;
+    }
+
+    /// Compute `(taker_fee, maker_fee)` for a fill of `qty` at `price` under
+    /// the current fee schedule. A negative maker rate yields a negative
+    /// (rebate) fee.
+    fn compute_fees(&self, price: i64, qty: u32) -> (i64, i64) {
+        let notional = price * qty as i64;
+        let schedule = self.risk_config.fee_schedule;
+        let taker_fee = (notional * schedule.taker_fee_bps) / 10_000;
+        let maker_fee = (notional * schedule.maker_fee_bps) / 10_000;
+        (taker_fee, maker_fee)
+    }
+
+    // -----------------------------------------------------------------------
+    // AMM LIQUIDITY POOL
+    // -----------------------------------------------------------------------
+
+    /// Current AMM pool reserves, if one is configured.
+    pub fn amm_pool(&self) -> Option<AmmPool> {
+        self.amm_pool
+    }
+
+    /// Add liquidity to the AMM pool, creating it (at zero reserves) on the
+    /// first call if none exists yet. There is no per-provider share — only
+    /// the pool's aggregate reserves are tracked, mirroring
+    /// `Guardian::add_funds`'s create-or-top-up semantics.
+    pub fn add_liquidity(&mut self, base: i64, quote: i64) {
+        let pool = self.amm_pool.get_or_insert(AmmPool { base_reserve: 0, quote_reserve: 0 });
+        pool.base_reserve += base;
+        pool.quote_reserve += quote;
+    }
+
+    /// Withdraw liquidity from the AMM pool. Rejects with
+    /// `RejectReason::InsufficientLiquidity` if no pool is configured yet or
+    /// the withdrawal would drive either reserve negative.
+    pub fn remove_liquidity(&mut self, base: i64, quote: i64) -> Result<(), RejectReason> {
+        let pool = self.amm_pool.as_mut().ok_or(RejectReason::InsufficientLiquidity)?;
+        if base > pool.base_reserve || quote > pool.quote_reserve {
+            return Err(RejectReason::InsufficientLiquidity);
+        }
+        pool.base_reserve -= base;
+        pool.quote_reserve -= quote;
+        Ok(())
+    }
+
+    /// Maximum base quantity the AMM pool (if configured) could supply to a
+    /// `side` aggressor at `limit_price`, capped at `target`, without
+    /// mutating anything. Mirrors `fillable_qty_against_asks`/`_bids`'s
+    /// read-only preview, used so `FillOrKill` accounts for AMM capacity
+    /// alongside the book.
+    fn amm_fillable_qty(&self, side: Side, limit_price: i64, target: u32) -> u32 {
+        match self.amm_pool {
+            Some(pool) => Self::amm_quote_for_base(pool, side, limit_price, target).0,
+            None => 0,
+        }
+    }
+
+    /// Find the largest base quantity (capped at `max_qty`) a `side`
+    /// aggressor could trade against `pool` before the pool's post-trade
+    /// price would cross `limit_price`, plus the quote amount that trade
+    /// costs (buy) or pays out (sell). Pure — does not mutate `pool`.
+    ///
+    /// Under the constant-product invariant `k = base_reserve *
+    /// quote_reserve`: buying `x` base moves the pool to `(base_reserve - x,
+    /// quote_reserve + quote_in)` with `quote_in = quote_reserve * x /
+    /// (base_reserve - x)`; selling `x` base moves it to `(base_reserve + x,
+    /// quote_reserve - quote_out)` with `quote_out = quote_reserve * x /
+    /// (base_reserve + x)`. In both directions the post-trade price
+    /// `quote_reserve' / base_reserve'` moves monotonically with `x` (up for
+    /// a buy, down for a sell), so a binary search over integer `x` finds
+    /// the largest value that still respects the order's limit.
+    fn amm_quote_for_base(pool: AmmPool, side: Side, limit_price: i64, max_qty: u32) -> (u32, i64) {
+        let base = pool.base_reserve;
+        let quote = pool.quote_reserve;
+        if base <= 0 || quote <= 0 || max_qty == 0 {
+            return (0, 0);
+        }
+
+        // Whether the post-trade price after moving `x` base stays within
+        // the order's limit, expressed as cross-multiplied i128 comparisons
+        // so no fixed-point division (and its rounding) enters the search.
+        // `submit_order` encodes a marketable Buy as `limit_price ==
+        // i64::MAX` — an actual "no limit" sentinel, not a real price — so
+        // squaring it in the cross-multiplication below would overflow
+        // i128. Treat it as always-feasible instead of as a literal price.
+        let unbounded_buy = side == Side::Buy && limit_price == i64::MAX;
+        let feasible = |x: i64| -> bool {
+            match side {
+                Side::Buy => {
+                    let new_base = base - x;
+                    if new_base <= 0 {
+                        return false;
+                    }
+                    unbounded_buy
+                        || (quote as i128) * (base as i128)
+                            <= (limit_price as i128) * (new_base as i128) * (new_base as i128)
+                }
+                Side::Sell => {
+                    let new_base = base + x;
+                    (quote as i128) * (base as i128)
+                        >= (limit_price as i128) * (new_base as i128) * (new_base as i128)
+                }
+            }
+        };
+
+        let hi = match side {
+            Side::Buy => (base - 1).min(max_qty as i64),
+            Side::Sell => max_qty as i64,
+        };
+        if hi <= 0 || !feasible(0) {
+            return (0, 0);
+        }
+
+        let mut lo: i64 = 0;
+        let mut hi = hi;
+        while lo < hi {
+            let mid = lo + (hi - lo + 1) / 2;
+            if feasible(mid) {
+                lo = mid;
+            } else {
+                hi = mid - 1;
+            }
+        }
+
+        let x = lo;
+        if x == 0 {
+            return (0, 0);
+        }
+
+        let quote_amount = match side {
+            // Round in the pool's favor: the taker pays at least enough to
+            // preserve the invariant.
+            Side::Buy => {
+                let new_base = base - x;
+                ((quote as i128) * (x as i128) + (new_base as i128) - 1) / (new_base as i128)
+            }
+            // Round in the pool's favor: the taker receives no more than
+            // the invariant allows.
+            Side::Sell => {
+                let new_base = base + x;
+                (quote as i128) * (x as i128) / (new_base as i128)
+            }
+        };
+
+        (x as u32, quote_amount as i64)
+    }
+
+    /// Route up to `max_qty` of a `side` aggressor against the AMM pool (if
+    /// configured), mutating its reserves and returning the synthetic `Fill`
+    /// plus the base quantity it consumed. `None` if there is no pool
+    /// configured or it has no capacity left at `limit_price`.
+    fn amm_fill(
+        &mut self,
+        side: Side,
+        taker_trader_id: u32,
+        taker_order_id: u64,
+        limit_price: i64,
+        max_qty: u32,
+        ts: u64,
+    ) -> Option<(Fill, u32)> {
+        let pool = self.amm_pool?;
+        let (base_filled, quote_amount) = Self::amm_quote_for_base(pool, side, limit_price, max_qty);
+        if base_filled == 0 {
+            return None;
+        }
+
+        let pool = self.amm_pool.as_mut().unwrap();
+        match side {
+            Side::Buy => {
+                pool.base_reserve -= base_filled as i64;
+                pool.quote_reserve += quote_amount;
+            }
+            Side::Sell => {
+                pool.base_reserve += base_filled as i64;
+                pool.quote_reserve -= quote_amount;
+            }
+        }
+
+        let fill_price = quote_amount / base_filled as i64;
+        self.book.last_trade_price = Some(fill_price);
+        let (taker_fee, maker_fee) = self.compute_fees(fill_price, base_filled);
+
+        Some((
+            Fill {
+                maker_order_id: 0,
+                taker_order_id,
+                maker_trader_id: AMM_POOL_TRADER_ID,
+                taker_trader_id,
+                price: fill_price,
+                qty: base_filled,
+                timestamp_ns: ts,
+                taker_fee,
+                maker_fee,
+            },
+            base_filled,
+        ))
+    }
+
+    /// Update the oracle/reference price and reprice every resting pegged
+    /// order against it: pull each out of its current book level, recompute
+    /// its clamped peg price, and re-insert at the new level.
+    ///
+    /// Peggeds are repriced in original submission order so that any of
+    /// them landing on the same new price level preserve their relative
+    /// time priority, rather than being reshuffled by processing order.
+    pub fn set_oracle_price(&mut self, oracle_price: i64) {
+        self.oracle_price = Some(oracle_price);
+
+        let mut peggeds = std::mem::take(&mut self.pegged_orders);
+        peggeds.sort_by_key(|p| p.ts);
+
+        let mut still_live = Vec::with_capacity(peggeds.len());
+        for mut peg in peggeds {
+            let side_book = match peg.side {
+                Side::Buy => &mut self.book.bids,
+                Side::Sell => &mut self.book.asks,
+            };
+            let level = match side_book.levels.get_mut(&peg.current_price) {
+                Some(l) => l,
+                // Cancelled or fully filled away since it was last repriced —
+                // drop its registry entry instead of carrying it forever.
+                None => continue,
+            };
+            let pos = match level.iter().position(|o| o.order_id == peg.order_id) {
+                Some(p) => p,
+                None => continue,
+            };
+            let removed = level.remove(pos).unwrap();
+            side_book.total_qty -= removed.qty as u64;
+            if level.is_empty() {
+                side_book.levels.remove(&peg.current_price);
+            }
+
+            let new_price = self.clamp_peg_price(peg.side, oracle_price + peg.peg_offset, peg.limit_price);
+            peg.current_price = new_price;
+            let resting = Order {
+                trader_id: peg.trader_id,
+                order_id: peg.order_id,
+                price: new_price,
+                qty: removed.qty,
+                ts: removed.ts,
+                tif: removed.tif,
+                expiry_ts: removed.expiry_ts,
+            };
+            self.book.insert(peg.side, resting);
+            still_live.push(peg);
+        }
+
+        self.pegged_orders = still_live;
+    }
+
+    /// Clamp a raw pegged price so it never crosses the opposing book
+    /// (peggeds are passive quotes, not aggressors) and never moves past the
+    /// order's own per-order limit cap, if one was set.
+    fn clamp_peg_price(&self, side: Side, raw_price: i64, limit_price: Option<i64>) -> i64 {
+        let tick = if self.risk_config.tick_size > 0 { self.risk_config.tick_size } else { 1 };
+        let non_crossing = match side {
+            Side::Buy => match self.book.best_ask() {
+                Some(ask) if raw_price >= ask => ask - tick,
+                _ => raw_price,
+            },
+            Side::Sell => match self.book.best_bid() {
+                Some(bid) if raw_price <= bid => bid + tick,
+                _ => raw_price,
+            },
+        };
+        match (side, limit_price) {
+            (Side::Buy, Some(cap)) => non_crossing.min(cap),
+            (Side::Sell, Some(cap)) => non_crossing.max(cap),
+            (_, None) => non_crossing,
+        }
+    }
+
     /// Match a Buy aggressor against the Ask book (lowest price first).
     ///
     /// The hot loop is kept intentionally "flat" (no function calls inside the
@@ -193,6 +927,8 @@ impl MatchingEngine {
         remaining_qty: &mut u32,
         fills: &mut Vec<Fill>,
         stp_cancels: &mut Vec<u64>,
+        expired_cancels: &mut Vec<u64>,
+        taker_cancelled: &mut bool,
         ts: u64,
     ) {
         // Drain ask levels starting from the lowest price.
@@ -218,16 +954,69 @@ impl MatchingEngine {
             while *remaining_qty > 0 && !level.is_empty() {
                 let maker = level.front().unwrap();
 
-                // Self-Trade Prevention: if same trader, cancel the resting order.
-                if maker.trader_id == taker_trader_id {
-                    let cancelled = level.pop_front().unwrap();
-                    self.book.asks.total_qty -= cancelled.qty as u64;
-                    stp_cancels.push(cancelled.order_id);
+                // Lazy-expiry-on-match: drop an expired maker instead of
+                // filling against it, bounded by `max_expired_reap` so a
+                // large backlog of stale orders can't make this call pay
+                // unbounded cost (the rest is reaped on later crossings).
+                let is_expired = matches!(maker.expiry_ts, Some(e) if e <= ts)
+                    && (expired_cancels.len() as u32) < self.risk_config.max_expired_reap;
+                if is_expired {
+                    let expired = level.pop_front().unwrap();
+                    self.book.asks.total_qty -= expired.qty as u64;
+                    self.book.order_index.remove(&expired.order_id);
+                    expired_cancels.push(expired.order_id);
                     continue;
                 }
 
+                let maker = level.front().unwrap();
+
+                // Self-Trade Prevention: same trader on both sides of the
+                // match. Which side(s) give way is an operator policy choice
+                // read off `RiskConfig::stp_mode` rather than hardcoded.
+                if maker.trader_id == taker_trader_id {
+                    let maker_order_id = maker.order_id;
+                    let maker_qty = maker.qty;
+                    match self.risk_config.stp_mode {
+                        StpMode::CancelResting => {
+                            level.pop_front();
+                            self.book.asks.total_qty -= maker_qty as u64;
+                            self.book.order_index.remove(&maker_order_id);
+                            stp_cancels.push(maker_order_id);
+                            continue;
+                        }
+                        StpMode::CancelIncoming => {
+                            *remaining_qty = 0;
+                            *taker_cancelled = true;
+                            return;
+                        }
+                        StpMode::CancelBoth => {
+                            level.pop_front();
+                            self.book.asks.total_qty -= maker_qty as u64;
+                            self.book.order_index.remove(&maker_order_id);
+                            stp_cancels.push(maker_order_id);
+                            *remaining_qty = 0;
+                            *taker_cancelled = true;
+                            return;
+                        }
+                        StpMode::DecrementAndCancel => {
+                            let decrement = (*remaining_qty).min(maker_qty);
+                            *remaining_qty -= decrement;
+                            self.book.asks.total_qty -= decrement as u64;
+                            if decrement >= maker_qty {
+                                level.pop_front();
+                                self.book.order_index.remove(&maker_order_id);
+                                stp_cancels.push(maker_order_id);
+                            } else {
+                                level.front_mut().unwrap().qty -= decrement;
+                            }
+                            continue;
+                        }
+                    }
+                }
+
                 let fill_qty = (*remaining_qty).min(maker.qty);
                 let fill_price = maker.price; // Execution at the resting (maker) price.
+                let (taker_fee, maker_fee) = self.compute_fees(fill_price, fill_qty);
 
                 fills.push(Fill {
                     maker_order_id: maker.order_id,
@@ -237,6 +1026,8 @@ impl MatchingEngine {
                     price: fill_price,
                     qty: fill_qty,
                     timestamp_ns: ts,
+                    taker_fee,
+                    maker_fee,
                 });
 
                 *remaining_qty -= fill_qty;
@@ -247,7 +1038,8 @@ impl MatchingEngine {
 
                 if fill_qty >= maker.qty {
                     // Maker fully filled — remove from queue.
-                    level.pop_front();
+                    let filled = level.pop_front().unwrap();
+                    self.book.order_index.remove(&filled.order_id);
                 } else {
                     // Maker partially filled — update remaining quantity.
                     level.front_mut().unwrap().qty -= fill_qty;
@@ -270,6 +1062,8 @@ impl MatchingEngine {
         remaining_qty: &mut u32,
         fills: &mut Vec<Fill>,
         stp_cancels: &mut Vec<u64>,
+        expired_cancels: &mut Vec<u64>,
+        taker_cancelled: &mut bool,
         ts: u64,
     ) {
         while *remaining_qty > 0 {
@@ -290,15 +1084,65 @@ impl MatchingEngine {
             while *remaining_qty > 0 && !level.is_empty() {
                 let maker = level.front().unwrap();
 
-                if maker.trader_id == taker_trader_id {
-                    let cancelled = level.pop_front().unwrap();
-                    self.book.bids.total_qty -= cancelled.qty as u64;
-                    stp_cancels.push(cancelled.order_id);
+                let is_expired = matches!(maker.expiry_ts, Some(e) if e <= ts)
+                    && (expired_cancels.len() as u32) < self.risk_config.max_expired_reap;
+                if is_expired {
+                    let expired = level.pop_front().unwrap();
+                    self.book.bids.total_qty -= expired.qty as u64;
+                    self.book.order_index.remove(&expired.order_id);
+                    expired_cancels.push(expired.order_id);
                     continue;
                 }
 
+                let maker = level.front().unwrap();
+
+                // Self-Trade Prevention: same trader on both sides of the
+                // match. Which side(s) give way is an operator policy choice
+                // read off `RiskConfig::stp_mode` rather than hardcoded.
+                if maker.trader_id == taker_trader_id {
+                    let maker_order_id = maker.order_id;
+                    let maker_qty = maker.qty;
+                    match self.risk_config.stp_mode {
+                        StpMode::CancelResting => {
+                            level.pop_front();
+                            self.book.bids.total_qty -= maker_qty as u64;
+                            self.book.order_index.remove(&maker_order_id);
+                            stp_cancels.push(maker_order_id);
+                            continue;
+                        }
+                        StpMode::CancelIncoming => {
+                            *remaining_qty = 0;
+                            *taker_cancelled = true;
+                            return;
+                        }
+                        StpMode::CancelBoth => {
+                            level.pop_front();
+                            self.book.bids.total_qty -= maker_qty as u64;
+                            self.book.order_index.remove(&maker_order_id);
+                            stp_cancels.push(maker_order_id);
+                            *remaining_qty = 0;
+                            *taker_cancelled = true;
+                            return;
+                        }
+                        StpMode::DecrementAndCancel => {
+                            let decrement = (*remaining_qty).min(maker_qty);
+                            *remaining_qty -= decrement;
+                            self.book.bids.total_qty -= decrement as u64;
+                            if decrement >= maker_qty {
+                                level.pop_front();
+                                self.book.order_index.remove(&maker_order_id);
+                                stp_cancels.push(maker_order_id);
+                            } else {
+                                level.front_mut().unwrap().qty -= decrement;
+                            }
+                            continue;
+                        }
+                    }
+                }
+
                 let fill_qty = (*remaining_qty).min(maker.qty);
                 let fill_price = maker.price;
+                let (taker_fee, maker_fee) = self.compute_fees(fill_price, fill_qty);
 
                 fills.push(Fill {
                     maker_order_id: maker.order_id,
@@ -308,6 +1152,8 @@ impl MatchingEngine {
                     price: fill_price,
                     qty: fill_qty,
                     timestamp_ns: ts,
+                    taker_fee,
+                    maker_fee,
                 });
 
                 *remaining_qty -= fill_qty;
@@ -315,7 +1161,8 @@ impl MatchingEngine {
                 self.book.last_trade_price = Some(fill_price);
 
                 if fill_qty >= maker.qty {
-                    level.pop_front();
+                    let filled = level.pop_front().unwrap();
+                    self.book.order_index.remove(&filled.order_id);
                 } else {
                     level.front_mut().unwrap().qty -= fill_qty;
                 }
@@ -351,15 +1198,112 @@ impl MatchingEngine {
         self.book.clear();
     }
 
-    /// Cancel all orders for a trader (Cancel-on-Disconnect).
-    pub fn cancel_all_for_trader(&mut self, trader_id: u32) -> Vec<u64> {
-        self.book.cancel_all_for_trader(trader_id)
+    /// Eagerly sweep every resting GTD/DAY order expired as of `now`, with
+    /// no per-call cap. For callers that want to reap a stale backlog up
+    /// front instead of waiting for it to be found lazily on later crosses.
+    pub fn reap_expired(&mut self, now: u64) -> Vec<u64> {
+        self.book.reap_expired(now)
     }
-}
 
-impl Default for MatchingEngine {
-    fn default() -> Self {
-        Self::new()
+    /// Cancel all orders for a trader (Cancel-on-Disconnect), including
+    /// stops still parked off-book waiting on their trigger and any pegged
+    /// orders' repricing metadata.
+    pub fn cancel_all_for_trader(&mut self, trader_id: u32) -> Vec<u64> {
+        let mut cancelled = self.book.cancel_all_for_trader(trader_id);
+        for stops in [&mut self.buy_stops, &mut self.sell_stops] {
+            let mut empty_prices = Vec::new();
+            for (price, level) in stops.iter_mut() {
+                level.retain(|s| {
+                    let keep = s.trader_id != trader_id;
+                    if !keep {
+                        cancelled.push(s.order_id);
+                    }
+                    keep
+                });
+                if level.is_empty() {
+                    empty_prices.push(*price);
+                }
+            }
+            for price in empty_prices {
+                stops.remove(&price);
+            }
+        }
+        self.pegged_orders.retain(|p| p.trader_id != trader_id);
+        cancelled
+    }
+
+    /// Cancel a single resting order by id. Returns whether it was found.
+    /// A cancelled pegged order's registry entry is left for the next
+    /// `set_oracle_price` call to drop, the same way a fill or expiry
+    /// already does.
+    pub fn cancel_order(&mut self, order_id: u64) -> bool {
+        self.book.cancel_order(order_id)
+    }
+
+    /// Amend a single resting order's price and/or quantity in place.
+    ///
+    /// Runs the Guardian risk checks against `(new_price, new_qty)` before
+    /// touching anything. A pure quantity decrease at the same price is
+    /// applied in place, keeping the order's spot in its FIFO queue; a
+    /// price change or quantity increase loses time priority — the order is
+    /// cancelled and resubmitted via `submit_order`, landing at the back of
+    /// its (possibly new) level, where it can also immediately cross the
+    /// opposing book like any other submission.
+    pub fn modify_order(
+        &mut self,
+        order_id: u64,
+        new_price: i64,
+        new_qty: u32,
+    ) -> Result<MatchResult, RejectReason> {
+        self.validate_risk(OrderType::Limit, new_price, new_qty)?;
+
+        let (side, existing) = self.book.find_resting(order_id).ok_or(RejectReason::OrderNotFound)?;
+
+        if new_price == existing.price && new_qty <= existing.qty {
+            let side_book = match side {
+                Side::Buy => &mut self.book.bids,
+                Side::Sell => &mut self.book.asks,
+            };
+            let level = side_book.levels.get_mut(&existing.price).expect("order_index is stale");
+            let order = level
+                .iter_mut()
+                .find(|o| o.order_id == order_id)
+                .expect("order_index is stale");
+            let delta = existing.qty - new_qty;
+            order.qty = new_qty;
+            side_book.total_qty -= delta as u64;
+
+            return Ok(MatchResult {
+                order_id,
+                fills: Vec::new(),
+                stp_cancels: Vec::new(),
+                stp_mode: self.risk_config.stp_mode,
+                taker_stp_cancelled: false,
+                resting_qty: new_qty,
+                cancelled_qty: 0,
+                effective_order_type: OrderType::Limit,
+                effective_price: existing.price,
+                triggered_fills: Vec::new(),
+                expired_cancels: Vec::new(),
+            });
+        }
+
+        self.book.cancel_order(order_id);
+        self.submit_order(
+            existing.trader_id,
+            side,
+            OrderType::Limit,
+            new_price,
+            new_qty,
+            existing.tif,
+            existing.expiry_ts,
+        )
+    }
+}
+
+impl Default for MatchingEngine {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -383,12 +1327,12 @@ mod tests {
         let mut engine = MatchingEngine::new();
 
         // Post a Sell at $100.
-        let r1 = engine.submit_order(1, Side::Sell, price(100), 50).unwrap();
+        let r1 = engine.submit_order(1, Side::Sell, OrderType::Limit, price(100), 50, TimeInForce::GTC, None).unwrap();
         assert_eq!(r1.fills.len(), 0);
         assert_eq!(r1.resting_qty, 50);
 
         // Post a Buy at $100 — should match.
-        let r2 = engine.submit_order(2, Side::Buy, price(100), 30).unwrap();
+        let r2 = engine.submit_order(2, Side::Buy, OrderType::Limit, price(100), 30, TimeInForce::GTC, None).unwrap();
         assert_eq!(r2.fills.len(), 1);
         assert_eq!(r2.fills[0].qty, 30);
         assert_eq!(r2.fills[0].price, price(100));
@@ -404,8 +1348,8 @@ mod tests {
     fn test_partial_fill() {
         let mut engine = MatchingEngine::new();
 
-        engine.submit_order(1, Side::Sell, price(100), 10).unwrap();
-        let r = engine.submit_order(2, Side::Buy, price(100), 25).unwrap();
+        engine.submit_order(1, Side::Sell, OrderType::Limit, price(100), 10, TimeInForce::GTC, None).unwrap();
+        let r = engine.submit_order(2, Side::Buy, OrderType::Limit, price(100), 25, TimeInForce::GTC, None).unwrap();
 
         assert_eq!(r.fills.len(), 1);
         assert_eq!(r.fills[0].qty, 10);
@@ -420,11 +1364,11 @@ mod tests {
         let mut engine = MatchingEngine::new();
 
         // Post two Sells: first at $101, then at $100.
-        engine.submit_order(1, Side::Sell, price(101), 10).unwrap();
-        engine.submit_order(2, Side::Sell, price(100), 10).unwrap();
+        engine.submit_order(1, Side::Sell, OrderType::Limit, price(101), 10, TimeInForce::GTC, None).unwrap();
+        engine.submit_order(2, Side::Sell, OrderType::Limit, price(100), 10, TimeInForce::GTC, None).unwrap();
 
         // Buy at $101 — should match the LOWER ask ($100) first (price priority).
-        let r = engine.submit_order(3, Side::Buy, price(101), 15).unwrap();
+        let r = engine.submit_order(3, Side::Buy, OrderType::Limit, price(101), 15, TimeInForce::GTC, None).unwrap();
         assert_eq!(r.fills.len(), 2);
         assert_eq!(r.fills[0].price, price(100)); // Best price first.
         assert_eq!(r.fills[0].qty, 10);
@@ -437,11 +1381,11 @@ mod tests {
         let mut engine = MatchingEngine::new();
 
         // Two Sells at the SAME price. Trader 1 is first.
-        engine.submit_order(1, Side::Sell, price(100), 10).unwrap();
-        engine.submit_order(2, Side::Sell, price(100), 10).unwrap();
+        engine.submit_order(1, Side::Sell, OrderType::Limit, price(100), 10, TimeInForce::GTC, None).unwrap();
+        engine.submit_order(2, Side::Sell, OrderType::Limit, price(100), 10, TimeInForce::GTC, None).unwrap();
 
         // Buy 10 — should match trader 1 first (FIFO).
-        let r = engine.submit_order(3, Side::Buy, price(100), 10).unwrap();
+        let r = engine.submit_order(3, Side::Buy, OrderType::Limit, price(100), 10, TimeInForce::GTC, None).unwrap();
         assert_eq!(r.fills.len(), 1);
         assert_eq!(r.fills[0].maker_trader_id, 1); // FIFO: trader 1 was first.
     }
@@ -451,10 +1395,10 @@ mod tests {
         let mut engine = MatchingEngine::new();
 
         // Trader 1 posts a Sell.
-        engine.submit_order(1, Side::Sell, price(100), 50).unwrap();
+        engine.submit_order(1, Side::Sell, OrderType::Limit, price(100), 50, TimeInForce::GTC, None).unwrap();
 
         // Trader 1 sends a Buy at the same price — STP should cancel the resting Sell.
-        let r = engine.submit_order(1, Side::Buy, price(100), 30).unwrap();
+        let r = engine.submit_order(1, Side::Buy, OrderType::Limit, price(100), 30, TimeInForce::GTC, None).unwrap();
         assert_eq!(r.fills.len(), 0); // NO match.
         assert_eq!(r.stp_cancels.len(), 1); // Resting sell was cancelled.
         assert_eq!(r.resting_qty, 30); // Buy rests on the book.
@@ -470,11 +1414,11 @@ mod tests {
         let mut engine = MatchingEngine::new();
 
         // Establish a reference price via a trade.
-        engine.submit_order(1, Side::Sell, price(100), 10).unwrap();
-        engine.submit_order(2, Side::Buy, price(100), 10).unwrap();
+        engine.submit_order(1, Side::Sell, OrderType::Limit, price(100), 10, TimeInForce::GTC, None).unwrap();
+        engine.submit_order(2, Side::Buy, OrderType::Limit, price(100), 10, TimeInForce::GTC, None).unwrap();
 
         // Now try a Buy at $200 (100% above last trade) — should be rejected.
-        let result = engine.submit_order(3, Side::Buy, price(200), 10);
+        let result = engine.submit_order(3, Side::Buy, OrderType::Limit, price(200), 10, TimeInForce::GTC, None);
         assert!(result.is_err());
         match result.unwrap_err() {
             RejectReason::FatFinger { order_price, reference_price } => {
@@ -488,7 +1432,7 @@ mod tests {
     #[test]
     fn test_max_quantity_rejection() {
         let engine = MatchingEngine::new();
-        let result = engine.validate_risk(price(100), 2_000_000);
+        let result = engine.validate_risk(OrderType::Limit, price(100), 2_000_000);
         assert!(result.is_err());
         match result.unwrap_err() {
             RejectReason::MaxQuantity { requested, max } => {
@@ -499,13 +1443,59 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_invalid_tick_rejection() {
+        let mut config = RiskConfig::default();
+        config.tick_size = price(1) / 100; // Smallest increment is one cent.
+        let engine = MatchingEngine::with_config(config);
+
+        // Off-tick price.
+        let result = engine.validate_risk(OrderType::Limit, price(100) + 1, 10);
+        assert_eq!(result, Err(RejectReason::InvalidTick));
+
+        // On-tick price is fine.
+        assert!(engine.validate_risk(OrderType::Limit, price(100), 10).is_ok());
+    }
+
+    #[test]
+    fn test_invalid_lot_rejection() {
+        let mut config = RiskConfig::default();
+        config.lot_size = 5;
+        let engine = MatchingEngine::with_config(config);
+
+        let result = engine.validate_risk(OrderType::Limit, price(100), 7);
+        assert_eq!(result, Err(RejectReason::InvalidLot));
+
+        assert!(engine.validate_risk(OrderType::Limit, price(100), 10).is_ok());
+    }
+
+    #[test]
+    fn test_below_min_size_rejection() {
+        let mut config = RiskConfig::default();
+        config.min_order_qty = 10;
+        let engine = MatchingEngine::with_config(config);
+
+        let result = engine.validate_risk(OrderType::Limit, price(100), 5);
+        assert_eq!(result, Err(RejectReason::BelowMinSize { requested: 5, min: 10 }));
+
+        assert!(engine.validate_risk(OrderType::Limit, price(100), 10).is_ok());
+    }
+
+    #[test]
+    fn test_tick_lot_min_size_checks_disabled_by_default() {
+        // `RiskConfig::default()` leaves tick_size/lot_size/min_order_qty at
+        // 0, so an odd price/qty combination is still accepted.
+        let engine = MatchingEngine::new();
+        assert!(engine.validate_risk(OrderType::Limit, price(100) + 1, 3).is_ok());
+    }
+
     #[test]
     fn test_no_match_across_spread() {
         let mut engine = MatchingEngine::new();
 
-        engine.submit_order(1, Side::Sell, price(105), 10).unwrap();
+        engine.submit_order(1, Side::Sell, OrderType::Limit, price(105), 10, TimeInForce::GTC, None).unwrap();
         // Buy at $100 — ask is at $105, no cross.
-        let r = engine.submit_order(2, Side::Buy, price(100), 10).unwrap();
+        let r = engine.submit_order(2, Side::Buy, OrderType::Limit, price(100), 10, TimeInForce::GTC, None).unwrap();
         assert_eq!(r.fills.len(), 0);
         assert_eq!(r.resting_qty, 10); // Rests on the bid book.
     }
@@ -515,12 +1505,12 @@ mod tests {
         let mut engine = MatchingEngine::new();
 
         // Stack the ask book: 10@100, 10@101, 10@102.
-        engine.submit_order(1, Side::Sell, price(100), 10).unwrap();
-        engine.submit_order(2, Side::Sell, price(101), 10).unwrap();
-        engine.submit_order(3, Side::Sell, price(102), 10).unwrap();
+        engine.submit_order(1, Side::Sell, OrderType::Limit, price(100), 10, TimeInForce::GTC, None).unwrap();
+        engine.submit_order(2, Side::Sell, OrderType::Limit, price(101), 10, TimeInForce::GTC, None).unwrap();
+        engine.submit_order(3, Side::Sell, OrderType::Limit, price(102), 10, TimeInForce::GTC, None).unwrap();
 
         // Buy 25 at limit $102 — should consume all of 100, all of 101, 5 of 102.
-        let r = engine.submit_order(4, Side::Buy, price(102), 25).unwrap();
+        let r = engine.submit_order(4, Side::Buy, OrderType::Limit, price(102), 25, TimeInForce::GTC, None).unwrap();
         assert_eq!(r.fills.len(), 3);
         assert_eq!(r.fills[0].price, price(100));
         assert_eq!(r.fills[0].qty, 10);
@@ -538,10 +1528,10 @@ mod tests {
     fn test_l2_snapshot_after_trades() {
         let mut engine = MatchingEngine::new();
 
-        engine.submit_order(1, Side::Buy, price(99), 10).unwrap();
-        engine.submit_order(2, Side::Buy, price(100), 20).unwrap();
-        engine.submit_order(3, Side::Sell, price(101), 15).unwrap();
-        engine.submit_order(4, Side::Sell, price(102), 25).unwrap();
+        engine.submit_order(1, Side::Buy, OrderType::Limit, price(99), 10, TimeInForce::GTC, None).unwrap();
+        engine.submit_order(2, Side::Buy, OrderType::Limit, price(100), 20, TimeInForce::GTC, None).unwrap();
+        engine.submit_order(3, Side::Sell, OrderType::Limit, price(101), 15, TimeInForce::GTC, None).unwrap();
+        engine.submit_order(4, Side::Sell, OrderType::Limit, price(102), 25, TimeInForce::GTC, None).unwrap();
 
         let (bids, asks) = engine.l2_snapshot(5);
         assert_eq!(bids.len(), 2);
@@ -558,13 +1548,619 @@ mod tests {
     fn test_cancel_on_disconnect() {
         let mut engine = MatchingEngine::new();
 
-        engine.submit_order(1, Side::Buy, price(100), 10).unwrap();
-        engine.submit_order(1, Side::Sell, price(105), 20).unwrap();
-        engine.submit_order(2, Side::Buy, price(99), 30).unwrap();
+        engine.submit_order(1, Side::Buy, OrderType::Limit, price(100), 10, TimeInForce::GTC, None).unwrap();
+        engine.submit_order(1, Side::Sell, OrderType::Limit, price(105), 20, TimeInForce::GTC, None).unwrap();
+        engine.submit_order(2, Side::Buy, OrderType::Limit, price(99), 30, TimeInForce::GTC, None).unwrap();
 
         let cancelled = engine.cancel_all_for_trader(1);
         assert_eq!(cancelled.len(), 2);
         assert_eq!(engine.best_bid(), Some(price(99))); // Only trader 2's bid remains.
         assert_eq!(engine.best_ask(), None); // Trader 1's ask was cancelled.
     }
+
+    #[test]
+    fn test_market_order_crosses_regardless_of_price() {
+        let mut engine = MatchingEngine::new();
+        engine.submit_order(1, Side::Sell, OrderType::Limit, price(105), 10, TimeInForce::GTC, None).unwrap();
+
+        // A Market buy with price 0 should still fully cross.
+        let r = engine.submit_order(2, Side::Buy, OrderType::Market, 0, 10, TimeInForce::GTC, None).unwrap();
+        assert_eq!(r.fills.len(), 1);
+        assert_eq!(r.fills[0].price, price(105));
+        assert_eq!(r.resting_qty, 0);
+        assert_eq!(r.effective_order_type, OrderType::Market);
+    }
+
+    #[test]
+    fn test_market_order_never_rests() {
+        let mut engine = MatchingEngine::new();
+        // No resting asks — a Market buy has nothing to cross.
+        let r = engine.submit_order(1, Side::Buy, OrderType::Market, 0, 10, TimeInForce::GTC, None).unwrap();
+        assert_eq!(r.fills.len(), 0);
+        assert_eq!(r.resting_qty, 0);
+        assert_eq!(engine.best_bid(), None);
+    }
+
+    #[test]
+    fn test_ioc_cancels_leftover_instead_of_resting() {
+        let mut engine = MatchingEngine::new();
+        engine.submit_order(1, Side::Sell, OrderType::Limit, price(100), 5, TimeInForce::GTC, None).unwrap();
+
+        let r = engine
+            .submit_order(2, Side::Buy, OrderType::ImmediateOrCancel, price(100), 20, TimeInForce::GTC, None)
+            .unwrap();
+        assert_eq!(r.fills.len(), 1);
+        assert_eq!(r.fills[0].qty, 5);
+        assert_eq!(r.resting_qty, 0);
+        assert_eq!(engine.best_bid(), None); // Leftover 15 was cancelled, not rested.
+    }
+
+    #[test]
+    fn test_fill_or_kill_rejects_when_unfulfillable() {
+        let mut engine = MatchingEngine::new();
+        engine.submit_order(1, Side::Sell, OrderType::Limit, price(100), 5, TimeInForce::GTC, None).unwrap();
+
+        let result = engine.submit_order(2, Side::Buy, OrderType::FillOrKill, price(100), 20, TimeInForce::GTC, None);
+        assert_eq!(result.unwrap_err(), RejectReason::FillOrKillUnfulfillable);
+        // The book must be untouched by the rejected FOK.
+        assert_eq!(engine.best_ask(), Some(price(100)));
+    }
+
+    #[test]
+    fn test_fill_or_kill_fills_completely_when_possible() {
+        let mut engine = MatchingEngine::new();
+        engine.submit_order(1, Side::Sell, OrderType::Limit, price(100), 10, TimeInForce::GTC, None).unwrap();
+        engine.submit_order(2, Side::Sell, OrderType::Limit, price(101), 10, TimeInForce::GTC, None).unwrap();
+
+        let r = engine
+            .submit_order(3, Side::Buy, OrderType::FillOrKill, price(101), 15, TimeInForce::GTC, None)
+            .unwrap();
+        assert_eq!(r.fills.len(), 2);
+        assert_eq!(r.resting_qty, 0);
+    }
+
+    #[test]
+    fn test_post_only_rejects_when_it_would_cross() {
+        let mut engine = MatchingEngine::new();
+        engine.submit_order(1, Side::Sell, OrderType::Limit, price(100), 10, TimeInForce::GTC, None).unwrap();
+
+        let result = engine.submit_order(2, Side::Buy, OrderType::PostOnly, price(100), 5, TimeInForce::GTC, None);
+        assert_eq!(result.unwrap_err(), RejectReason::PostOnlyWouldCross);
+        assert_eq!(engine.best_bid(), None);
+    }
+
+    #[test]
+    fn test_post_only_rests_when_it_would_not_cross() {
+        let mut engine = MatchingEngine::new();
+        engine.submit_order(1, Side::Sell, OrderType::Limit, price(100), 10, TimeInForce::GTC, None).unwrap();
+
+        let r = engine
+            .submit_order(2, Side::Buy, OrderType::PostOnly, price(99), 5, TimeInForce::GTC, None)
+            .unwrap();
+        assert_eq!(r.fills.len(), 0);
+        assert_eq!(r.resting_qty, 5);
+    }
+
+    #[test]
+    fn test_post_only_slide_reprices_instead_of_rejecting() {
+        let mut engine = MatchingEngine::new();
+        engine.submit_order(1, Side::Sell, OrderType::Limit, price(100), 10, TimeInForce::GTC, None).unwrap();
+
+        // Buy at $100 would cross — slide to one tick below the best ask.
+        let r = engine
+            .submit_order(2, Side::Buy, OrderType::PostOnlySlide, price(100), 5, TimeInForce::GTC, None)
+            .unwrap();
+        assert_eq!(r.fills.len(), 0);
+        assert_eq!(r.effective_price, price(100) - 1);
+        assert_eq!(engine.best_bid(), Some(price(100) - 1));
+        // The resting ask must be untouched — no cross happened.
+        assert_eq!(engine.best_ask(), Some(price(100)));
+    }
+
+    #[test]
+    fn test_buy_stop_triggers_when_price_rises_to_it() {
+        let mut engine = MatchingEngine::new();
+        engine.submit_order(1, Side::Sell, OrderType::Limit, price(100), 10, TimeInForce::GTC, None).unwrap();
+        engine.submit_order(1, Side::Sell, OrderType::Limit, price(105), 20, TimeInForce::GTC, None).unwrap();
+
+        // A buy-stop market order parked at $105, not yet triggered.
+        engine.submit_stop_order(2, Side::Buy, price(105), None, 10).unwrap();
+        assert_eq!(engine.best_bid(), None);
+
+        // Trade at $100 establishes last_trade_price but doesn't trigger the stop.
+        let r1 = engine.submit_order(3, Side::Buy, OrderType::Limit, price(100), 10, TimeInForce::GTC, None).unwrap();
+        assert!(r1.triggered_fills.is_empty());
+
+        // Trade at $105 triggers the parked stop, which crosses the remaining ask.
+        let r2 = engine.submit_order(4, Side::Buy, OrderType::Limit, price(105), 10, TimeInForce::GTC, None).unwrap();
+        assert_eq!(r2.triggered_fills.len(), 1);
+        assert_eq!(r2.triggered_fills[0].price, price(105));
+        assert_eq!(r2.triggered_fills[0].taker_trader_id, 2);
+    }
+
+    #[test]
+    fn test_sell_stop_triggers_when_price_falls_to_it() {
+        let mut engine = MatchingEngine::new();
+        engine.submit_order(1, Side::Buy, OrderType::Limit, price(100), 10, TimeInForce::GTC, None).unwrap();
+        engine.submit_order(1, Side::Buy, OrderType::Limit, price(95), 20, TimeInForce::GTC, None).unwrap();
+
+        // A sell-stop market order parked at $95.
+        engine.submit_stop_order(2, Side::Sell, price(95), None, 10).unwrap();
+
+        // Trade at $100 doesn't trigger (last_trade_price > stop_price).
+        let r1 = engine.submit_order(3, Side::Sell, OrderType::Limit, price(100), 10, TimeInForce::GTC, None).unwrap();
+        assert!(r1.triggered_fills.is_empty());
+
+        // Trade at $95 triggers it.
+        let r2 = engine.submit_order(4, Side::Sell, OrderType::Limit, price(95), 10, TimeInForce::GTC, None).unwrap();
+        assert_eq!(r2.triggered_fills.len(), 1);
+        assert_eq!(r2.triggered_fills[0].price, price(95));
+        assert_eq!(r2.triggered_fills[0].taker_trader_id, 2);
+    }
+
+    #[test]
+    fn test_stop_limit_rests_leftover_after_triggering() {
+        let mut engine = MatchingEngine::new();
+        engine.submit_order(1, Side::Sell, OrderType::Limit, price(100), 5, TimeInForce::GTC, None).unwrap();
+        engine.submit_order(1, Side::Sell, OrderType::Limit, price(101), 5, TimeInForce::GTC, None).unwrap();
+
+        // Stop-limit buy: triggers at $100, rests any leftover at $101.
+        engine.submit_stop_order(2, Side::Buy, price(100), Some(price(101)), 10).unwrap();
+
+        let r = engine.submit_order(3, Side::Buy, OrderType::Limit, price(100), 5, TimeInForce::GTC, None).unwrap();
+        assert_eq!(r.triggered_fills.len(), 1);
+        assert_eq!(r.triggered_fills[0].qty, 5);
+        // 5 of the triggered 10 filled against the $101 ask; 5 rest at $101.
+        assert_eq!(engine.best_bid(), Some(price(101)));
+        assert_eq!(engine.best_ask(), None);
+    }
+
+    #[test]
+    fn test_stop_cascade_chains_through_multiple_triggers() {
+        let mut engine = MatchingEngine::new();
+        engine.submit_order(1, Side::Sell, OrderType::Limit, price(100), 10, TimeInForce::GTC, None).unwrap();
+        engine.submit_order(1, Side::Sell, OrderType::Limit, price(105), 10, TimeInForce::GTC, None).unwrap();
+
+        // A buy-stop at $100 that, once triggered, trades through $100 and
+        // into $105 should cascade-trigger the $105 buy-stop in the same call.
+        engine.submit_stop_order(2, Side::Buy, price(100), None, 10).unwrap();
+        engine.submit_stop_order(3, Side::Buy, price(105), None, 10).unwrap();
+
+        // A small buy at $100 sets last_trade_price to $100, triggering stop
+        // A (trader 2), whose market buy eats through the rest of $100 and
+        // into $105, which in turn triggers stop B (trader 3).
+        let r = engine.submit_order(4, Side::Buy, OrderType::Limit, price(100), 1, TimeInForce::GTC, None).unwrap();
+        assert_eq!(r.triggered_fills.len(), 3);
+        assert_eq!(r.triggered_fills[0].taker_trader_id, 2);
+        assert_eq!(r.triggered_fills[1].taker_trader_id, 2);
+        assert_eq!(r.triggered_fills[2].taker_trader_id, 3);
+        assert_eq!(engine.best_ask(), None);
+    }
+
+    #[test]
+    fn test_cancel_all_for_trader_sweeps_parked_stops() {
+        let mut engine = MatchingEngine::new();
+        engine.submit_stop_order(1, Side::Buy, price(100), None, 10).unwrap();
+        engine.submit_stop_order(1, Side::Sell, price(90), None, 5).unwrap();
+        engine.submit_stop_order(2, Side::Buy, price(100), None, 3).unwrap();
+
+        let cancelled = engine.cancel_all_for_trader(1);
+        assert_eq!(cancelled.len(), 2);
+
+        // Trader 2's stop must survive cancellation and still be triggerable.
+        engine.submit_order(3, Side::Sell, OrderType::Limit, price(100), 10, TimeInForce::GTC, None).unwrap();
+        let r = engine.submit_order(4, Side::Buy, OrderType::Limit, price(100), 1, TimeInForce::GTC, None).unwrap();
+        assert_eq!(r.triggered_fills.len(), 1);
+        assert_eq!(r.triggered_fills[0].taker_trader_id, 2);
+    }
+
+    #[test]
+    fn test_expired_maker_is_reaped_lazily_on_match_instead_of_filled() {
+        let mut engine = MatchingEngine::new();
+        // Rests at ts=1 with a GTD expiry at tick 2.
+        engine
+            .submit_order(1, Side::Sell, OrderType::Limit, price(100), 10, TimeInForce::GTD, Some(2))
+            .unwrap();
+
+        // This call ticks to ts=2, exactly the maker's expiry — it's reaped
+        // instead of filled.
+        let r = engine
+            .submit_order(2, Side::Buy, OrderType::Limit, price(100), 5, TimeInForce::GTC, None)
+            .unwrap();
+        assert_eq!(r.fills.len(), 0);
+        assert_eq!(r.expired_cancels, vec![1]);
+        assert_eq!(r.resting_qty, 5);
+        assert_eq!(engine.best_ask(), None);
+    }
+
+    #[test]
+    fn test_reap_expired_eager_sweep() {
+        let mut engine = MatchingEngine::new();
+        engine
+            .submit_order(1, Side::Sell, OrderType::Limit, price(100), 10, TimeInForce::DAY, Some(1))
+            .unwrap();
+
+        let reaped = engine.reap_expired(1);
+        assert_eq!(reaped, vec![1]);
+        assert_eq!(engine.best_ask(), None);
+    }
+
+    #[test]
+    fn test_max_expired_reap_bounds_lazy_reaping_per_call() {
+        let mut config = RiskConfig::default();
+        config.max_expired_reap = 1;
+        let mut engine = MatchingEngine::with_config(config);
+
+        // Three resting sells at the same price, all already expired as of
+        // tick 1, posted at ts 1/2/3 respectively.
+        for _ in 0..3 {
+            engine
+                .submit_order(1, Side::Sell, OrderType::Limit, price(100), 5, TimeInForce::DAY, Some(1))
+                .unwrap();
+        }
+
+        // Crosses all three: only the first is reaped (the cap), the other
+        // two are filled normally even though they too are expired.
+        let r = engine
+            .submit_order(2, Side::Buy, OrderType::Limit, price(100), 15, TimeInForce::GTC, None)
+            .unwrap();
+        assert_eq!(r.expired_cancels, vec![1]);
+        assert_eq!(r.fills.len(), 2);
+        assert_eq!(r.fills.iter().map(|f| f.qty).sum::<u32>(), 10);
+        assert_eq!(r.resting_qty, 5);
+    }
+
+    #[test]
+    fn test_stp_cancel_incoming_mode_aborts_taker_without_cancelling_resting() {
+        let mut config = RiskConfig::default();
+        config.stp_mode = StpMode::CancelIncoming;
+        let mut engine = MatchingEngine::with_config(config);
+
+        engine.submit_order(1, Side::Sell, OrderType::Limit, price(100), 50, TimeInForce::GTC, None).unwrap();
+
+        let r = engine.submit_order(1, Side::Buy, OrderType::Limit, price(100), 30, TimeInForce::GTC, None).unwrap();
+        assert_eq!(r.fills.len(), 0);
+        assert!(r.stp_cancels.is_empty()); // Resting order is untouched.
+        assert!(r.taker_stp_cancelled);
+        assert_eq!(r.stp_mode, StpMode::CancelIncoming);
+        assert_eq!(r.resting_qty, 0); // Incoming quantity was aborted, not posted.
+
+        // The resting sell is still there, unaffected.
+        assert_eq!(engine.best_ask(), Some(price(100)));
+        assert_eq!(engine.best_bid(), None);
+    }
+
+    #[test]
+    fn test_stp_cancel_both_mode_cancels_both_sides() {
+        let mut config = RiskConfig::default();
+        config.stp_mode = StpMode::CancelBoth;
+        let mut engine = MatchingEngine::with_config(config);
+
+        engine.submit_order(1, Side::Sell, OrderType::Limit, price(100), 50, TimeInForce::GTC, None).unwrap();
+
+        let r = engine.submit_order(1, Side::Buy, OrderType::Limit, price(100), 30, TimeInForce::GTC, None).unwrap();
+        assert_eq!(r.fills.len(), 0);
+        assert_eq!(r.stp_cancels, vec![1]); // Resting sell cancelled.
+        assert!(r.taker_stp_cancelled); // Incoming buy aborted too.
+        assert_eq!(r.resting_qty, 0);
+
+        assert_eq!(engine.best_ask(), None);
+        assert_eq!(engine.best_bid(), None);
+    }
+
+    #[test]
+    fn test_stp_decrement_and_cancel_reduces_both_sides() {
+        let mut config = RiskConfig::default();
+        config.stp_mode = StpMode::DecrementAndCancel;
+        let mut engine = MatchingEngine::with_config(config);
+
+        // Resting sell for 50, trader 1.
+        engine.submit_order(1, Side::Sell, OrderType::Limit, price(100), 50, TimeInForce::GTC, None).unwrap();
+        // Another resting sell from trader 2, behind it at the same price.
+        engine.submit_order(2, Side::Sell, OrderType::Limit, price(100), 20, TimeInForce::GTC, None).unwrap();
+
+        // Trader 1 buys 30 — self-trade against the first 50: both decrement
+        // by min(30, 50) = 30, leaving the resting sell at 20 (not cancelled)
+        // and the incoming buy fully exhausted (not posted).
+        let r = engine.submit_order(1, Side::Buy, OrderType::Limit, price(100), 30, TimeInForce::GTC, None).unwrap();
+        assert_eq!(r.fills.len(), 0);
+        assert!(r.stp_cancels.is_empty()); // Neither side reached zero.
+        assert!(!r.taker_stp_cancelled); // Not the CancelIncoming/CancelBoth flavor.
+        assert_eq!(r.resting_qty, 0);
+
+        // Trader 1's sell shrank from 50 to 20; trader 2's 20 behind it is untouched.
+        let (_, asks) = engine.l2_snapshot(10);
+        assert_eq!(asks.len(), 1);
+        assert_eq!(asks[0].qty, 40); // 20 (trader 1 remainder) + 20 (trader 2).
+
+        // A matching buy from a different trader now fills trader 1's
+        // leftover 20 first (price-time priority preserved).
+        let r2 = engine.submit_order(3, Side::Buy, OrderType::Limit, price(100), 20, TimeInForce::GTC, None).unwrap();
+        assert_eq!(r2.fills.len(), 1);
+        assert_eq!(r2.fills[0].maker_trader_id, 1);
+        assert_eq!(r2.fills[0].qty, 20);
+    }
+
+    #[test]
+    fn test_pegged_order_rejected_without_oracle_price() {
+        let mut engine = MatchingEngine::new();
+        let result = engine.submit_pegged_order(1, Side::Buy, -price(1), 10, None);
+        assert_eq!(result.unwrap_err(), RejectReason::OraclePriceUnset);
+    }
+
+    #[test]
+    fn test_pegged_buy_rests_below_oracle_by_offset() {
+        let mut engine = MatchingEngine::new();
+        engine.set_oracle_price(price(100));
+
+        let r = engine.submit_pegged_order(1, Side::Buy, -price(1), 10, None).unwrap();
+        assert_eq!(r.fills.len(), 0);
+        assert_eq!(r.resting_qty, 10);
+        assert_eq!(r.effective_price, price(99));
+        assert_eq!(engine.best_bid(), Some(price(99)));
+    }
+
+    #[test]
+    fn test_set_oracle_price_reprices_resting_pegged_order() {
+        let mut engine = MatchingEngine::new();
+        engine.set_oracle_price(price(100));
+        engine.submit_pegged_order(1, Side::Buy, -price(1), 10, None).unwrap();
+        assert_eq!(engine.best_bid(), Some(price(99)));
+
+        // Oracle moves up $5 — the peg should follow it to $104.
+        engine.set_oracle_price(price(105));
+        assert_eq!(engine.best_bid(), Some(price(104)));
+
+        // Old level is gone, no stray order left behind.
+        let (bids, _) = engine.l2_snapshot(10);
+        assert_eq!(bids.len(), 1);
+        assert_eq!(bids[0].price, price(104));
+        assert_eq!(bids[0].qty, 10);
+    }
+
+    #[test]
+    fn test_pegged_price_clamped_non_crossing() {
+        let mut engine = MatchingEngine::new();
+        // Resting ask at $100.
+        engine.submit_order(1, Side::Sell, OrderType::Limit, price(100), 10, TimeInForce::GTC, None).unwrap();
+
+        engine.set_oracle_price(price(100));
+        // Raw peg price would be oracle + offset = $101, which crosses the
+        // $100 ask — clamp it to just inside the spread instead of crossing.
+        let r = engine.submit_pegged_order(2, Side::Buy, price(1), 5, None).unwrap();
+        assert_eq!(r.fills.len(), 0);
+        assert!(r.effective_price < price(100));
+        assert_eq!(engine.best_ask(), Some(price(100))); // Untouched — no cross happened.
+    }
+
+    #[test]
+    fn test_pegged_price_clamped_to_limit_cap() {
+        let mut engine = MatchingEngine::new();
+        engine.set_oracle_price(price(100));
+
+        // Offset would peg at $102, but the cap says never pay more than $101.
+        let r = engine.submit_pegged_order(1, Side::Buy, price(2), 10, Some(price(101))).unwrap();
+        assert_eq!(r.effective_price, price(101));
+    }
+
+    #[test]
+    fn test_pegged_orders_preserve_relative_time_priority_on_repeg() {
+        let mut engine = MatchingEngine::new();
+        engine.set_oracle_price(price(100));
+
+        // Two pegged buys land at the same price ($99) in submission order.
+        engine.submit_pegged_order(1, Side::Buy, -price(1), 10, None).unwrap();
+        engine.submit_pegged_order(2, Side::Buy, -price(1), 10, None).unwrap();
+
+        // Reprice to a new common level — relative order (1 then 2) must hold.
+        engine.set_oracle_price(price(105));
+        assert_eq!(engine.best_bid(), Some(price(104)));
+
+        let r = engine.submit_order(3, Side::Sell, OrderType::Limit, price(104), 10, TimeInForce::GTC, None).unwrap();
+        assert_eq!(r.fills.len(), 1);
+        assert_eq!(r.fills[0].maker_trader_id, 1); // Trader 1 was still first.
+    }
+
+    #[test]
+    fn test_cancel_all_for_trader_drops_pegged_registry_entry() {
+        let mut engine = MatchingEngine::new();
+        engine.set_oracle_price(price(100));
+        engine.submit_pegged_order(1, Side::Buy, -price(1), 10, None).unwrap();
+
+        engine.cancel_all_for_trader(1);
+        assert_eq!(engine.best_bid(), None);
+
+        // Repricing afterward must not resurrect the cancelled order.
+        engine.set_oracle_price(price(105));
+        assert_eq!(engine.best_bid(), None);
+    }
+
+    #[test]
+    fn test_cancel_order_removes_single_resting_order() {
+        let mut engine = MatchingEngine::new();
+        let r1 = engine.submit_order(1, Side::Buy, OrderType::Limit, price(100), 10, TimeInForce::GTC, None).unwrap();
+        engine.submit_order(1, Side::Buy, OrderType::Limit, price(99), 5, TimeInForce::GTC, None).unwrap();
+
+        assert!(engine.cancel_order(r1.order_id));
+        assert_eq!(engine.best_bid(), Some(price(99)));
+        assert!(!engine.cancel_order(r1.order_id)); // Already gone.
+    }
+
+    #[test]
+    fn test_modify_order_rejects_unknown_id() {
+        let mut engine = MatchingEngine::new();
+        let result = engine.modify_order(999, price(100), 10);
+        assert_eq!(result.unwrap_err(), RejectReason::OrderNotFound);
+    }
+
+    #[test]
+    fn test_modify_order_runs_guardian_checks_before_touching_the_book() {
+        let mut engine = MatchingEngine::new();
+        let r = engine.submit_order(1, Side::Buy, OrderType::Limit, price(100), 10, TimeInForce::GTC, None).unwrap();
+
+        let result = engine.modify_order(r.order_id, price(100), 2_000_000);
+        assert!(matches!(result.unwrap_err(), RejectReason::MaxQuantity { .. }));
+        // The original order must be untouched.
+        assert_eq!(engine.best_bid(), Some(price(100)));
+    }
+
+    #[test]
+    fn test_modify_order_quantity_decrease_retains_time_priority() {
+        let mut engine = MatchingEngine::new();
+        let r1 = engine.submit_order(1, Side::Buy, OrderType::Limit, price(100), 10, TimeInForce::GTC, None).unwrap();
+        engine.submit_order(2, Side::Buy, OrderType::Limit, price(100), 10, TimeInForce::GTC, None).unwrap();
+
+        // Shrink trader 1's order from 10 to 5 — same price, lower qty.
+        let modified = engine.modify_order(r1.order_id, price(100), 5).unwrap();
+        assert_eq!(modified.fills.len(), 0);
+        assert_eq!(modified.resting_qty, 5);
+
+        // A matching sell for 5 should still hit trader 1 first — it kept
+        // its place at the front of the queue instead of moving behind
+        // trader 2's order.
+        let r = engine.submit_order(3, Side::Sell, OrderType::Limit, price(100), 5, TimeInForce::GTC, None).unwrap();
+        assert_eq!(r.fills.len(), 1);
+        assert_eq!(r.fills[0].maker_trader_id, 1);
+        assert_eq!(r.fills[0].maker_order_id, r1.order_id);
+        assert_eq!(engine.best_bid(), Some(price(100))); // Trader 2's order still rests.
+    }
+
+    #[test]
+    fn test_modify_order_price_change_loses_time_priority() {
+        let mut engine = MatchingEngine::new();
+        let r1 = engine.submit_order(1, Side::Buy, OrderType::Limit, price(99), 10, TimeInForce::GTC, None).unwrap();
+        engine.submit_order(2, Side::Buy, OrderType::Limit, price(100), 10, TimeInForce::GTC, None).unwrap();
+
+        // Move trader 1's bid up to $100 — it now lands behind trader 2's
+        // resting order at the same price, not in front of it.
+        let modified = engine.modify_order(r1.order_id, price(100), 10).unwrap();
+        assert_eq!(modified.resting_qty, 10);
+
+        let r = engine.submit_order(3, Side::Sell, OrderType::Limit, price(100), 10, TimeInForce::GTC, None).unwrap();
+        assert_eq!(r.fills.len(), 1);
+        assert_eq!(r.fills[0].maker_trader_id, 2); // Trader 2 still goes first.
+    }
+
+    #[test]
+    fn test_modify_order_quantity_increase_loses_time_priority() {
+        let mut engine = MatchingEngine::new();
+        let r1 = engine.submit_order(1, Side::Buy, OrderType::Limit, price(100), 5, TimeInForce::GTC, None).unwrap();
+        engine.submit_order(2, Side::Buy, OrderType::Limit, price(100), 5, TimeInForce::GTC, None).unwrap();
+
+        // Growing the quantity, even at the same price, loses priority.
+        engine.modify_order(r1.order_id, price(100), 10).unwrap();
+
+        let r = engine.submit_order(3, Side::Sell, OrderType::Limit, price(100), 5, TimeInForce::GTC, None).unwrap();
+        assert_eq!(r.fills.len(), 1);
+        assert_eq!(r.fills[0].maker_trader_id, 2); // Trader 2 now goes first.
+    }
+
+    #[test]
+    fn test_modify_order_can_immediately_cross_at_its_new_price() {
+        let mut engine = MatchingEngine::new();
+        engine.submit_order(1, Side::Sell, OrderType::Limit, price(101), 10, TimeInForce::GTC, None).unwrap();
+        let r1 = engine.submit_order(2, Side::Buy, OrderType::Limit, price(99), 10, TimeInForce::GTC, None).unwrap();
+
+        // Repricing the bid up to $101 should now cross the resting ask.
+        let modified = engine.modify_order(r1.order_id, price(101), 10).unwrap();
+        assert_eq!(modified.fills.len(), 1);
+        assert_eq!(modified.fills[0].qty, 10);
+        assert_eq!(modified.resting_qty, 0);
+        assert_eq!(engine.best_ask(), None);
+    }
+
+    // -------------------------------------------------------------------
+    // AMM Tests
+    // -------------------------------------------------------------------
+
+    #[test]
+    fn test_amm_pool_is_none_until_liquidity_added() {
+        let engine = MatchingEngine::new();
+        assert_eq!(engine.amm_pool(), None);
+    }
+
+    #[test]
+    fn test_order_routes_to_amm_when_book_is_empty() {
+        let mut engine = MatchingEngine::new();
+        engine.add_liquidity(1_000_000, price(100) * 1_000_000);
+
+        let r = engine
+            .submit_order(1, Side::Buy, OrderType::Limit, price(200), 1_000, TimeInForce::GTC, None)
+            .unwrap();
+        assert_eq!(r.fills.len(), 1);
+        assert_eq!(r.fills[0].maker_trader_id, AMM_POOL_TRADER_ID);
+        assert_eq!(r.fills[0].qty, 1_000);
+        assert_eq!(r.resting_qty, 0);
+
+        let pool = engine.amm_pool().unwrap();
+        assert_eq!(pool.base_reserve, 1_000_000 - 1_000);
+        assert!(pool.quote_reserve > price(100) * 1_000_000);
+    }
+
+    #[test]
+    fn test_amm_only_covers_what_the_book_could_not() {
+        let mut engine = MatchingEngine::new();
+        engine.add_liquidity(1_000_000, price(100) * 1_000_000);
+        engine.submit_order(1, Side::Sell, OrderType::Limit, price(100), 300, TimeInForce::GTC, None).unwrap();
+
+        let r = engine
+            .submit_order(2, Side::Buy, OrderType::Limit, price(200), 1_000, TimeInForce::GTC, None)
+            .unwrap();
+        assert_eq!(r.fills.len(), 2);
+        assert_eq!(r.fills[0].maker_trader_id, 1);
+        assert_eq!(r.fills[0].qty, 300);
+        assert_eq!(r.fills[1].maker_trader_id, AMM_POOL_TRADER_ID);
+        assert_eq!(r.fills[1].qty, 700);
+        assert_eq!(r.resting_qty, 0);
+    }
+
+    #[test]
+    fn test_amm_does_not_trade_past_its_marginal_price_crossing_the_limit() {
+        let mut engine = MatchingEngine::new();
+        // A small pool: its marginal price rises fast as base_reserve is drained.
+        engine.add_liquidity(100, price(100) * 100);
+
+        let r = engine
+            .submit_order(1, Side::Buy, OrderType::Limit, price(110), 1_000, TimeInForce::GTC, None)
+            .unwrap();
+        // Only a sliver of the pool (4 units) can trade before the price crosses $110.
+        assert!(r.fills[0].qty < 1_000);
+        assert!(r.resting_qty > 0); // The rest rests on the book instead.
+    }
+
+    #[test]
+    fn test_fill_or_kill_counts_amm_capacity_toward_fulfillability() {
+        let mut engine = MatchingEngine::new();
+        engine.add_liquidity(1_000_000, price(100) * 1_000_000);
+
+        // No book liquidity at all, but the AMM alone can cover this FOK.
+        let r = engine
+            .submit_order(1, Side::Buy, OrderType::FillOrKill, price(200), 1_000, TimeInForce::GTC, None)
+            .unwrap();
+        assert_eq!(r.resting_qty, 0);
+        assert_eq!(r.fills[0].maker_trader_id, AMM_POOL_TRADER_ID);
+    }
+
+    #[test]
+    fn test_remove_liquidity_rejects_without_a_pool() {
+        let mut engine = MatchingEngine::new();
+        assert_eq!(engine.remove_liquidity(1, 1), Err(RejectReason::InsufficientLiquidity));
+    }
+
+    #[test]
+    fn test_remove_liquidity_rejects_when_exceeding_reserves() {
+        let mut engine = MatchingEngine::new();
+        engine.add_liquidity(100, 100);
+        assert_eq!(engine.remove_liquidity(101, 0), Err(RejectReason::InsufficientLiquidity));
+    }
+
+    #[test]
+    fn test_add_then_remove_liquidity_round_trips_reserves() {
+        let mut engine = MatchingEngine::new();
+        engine.add_liquidity(1_000, 2_000);
+        engine.remove_liquidity(400, 800).unwrap();
+        let pool = engine.amm_pool().unwrap();
+        assert_eq!(pool.base_reserve, 600);
+        assert_eq!(pool.quote_reserve, 1_200);
+    }
 }
@@ -0,0 +1,692 @@
+// nexus_core/src/matching/orderbook.rs
+//
+// The limit order book: price-sorted levels of FIFO-queued resting orders,
+// plus the small plain-data types (`Order`, `Fill`, `MatchResult`,
+// `RejectReason`, `RiskConfig`, `L2Level`, `StopOrder`, `PeggedOrder`) the
+// matching engine in `mod.rs` is built around.
+//
+// Each side (`bids`/`asks`) is a `BTreeMap<i64, VecDeque<Order>>`: the
+// BTreeMap gives O(log N) access to the best price, and the VecDeque at each
+// price level gives O(1) FIFO time priority within that price.
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+use crate::types::Side;
+
+/// A resting (or about-to-rest) order on one side of the book.
+#[derive(Debug, Clone, Copy)]
+pub struct Order {
+    pub trader_id: u32,
+    pub order_id: u64,
+    pub price: i64,
+    pub qty: u32,
+    pub ts: u64,
+    pub tif: TimeInForce,
+    /// Tick at which this order expires, compared against the aggressor's
+    /// `ts` the next time it is considered for a match (lazy expiry). `None`
+    /// for `TimeInForce::GTC`, which never expires.
+    pub expiry_ts: Option<u64>,
+}
+
+/// How long a resting order stays eligible to rest on the book.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeInForce {
+    /// Good-Til-Cancelled: never expires on its own.
+    GTC,
+    /// Good-Til-Date: expires at the `Order`'s `expiry_ts`.
+    GTD,
+    /// Day order: expires at the `Order`'s `expiry_ts`, the end of the
+    /// current trading day.
+    DAY,
+}
+
+/// One executed trade between a resting maker and an incoming taker.
+#[derive(Debug, Clone, Copy)]
+pub struct Fill {
+    pub maker_order_id: u64,
+    pub taker_order_id: u64,
+    pub maker_trader_id: u32,
+    pub taker_trader_id: u32,
+    pub price: i64,
+    pub qty: u32,
+    pub timestamp_ns: u64,
+    /// Fee charged to the taker (`taker_trader_id`) under the engine's
+    /// `FeeSchedule` at the time of this fill, in fixed-point quote units.
+    /// Always 0 under the default (zero-rate) schedule.
+    pub taker_fee: i64,
+    /// Fee charged to the maker (`maker_trader_id`) — negative if
+    /// `FeeSchedule::maker_fee_bps` is a rebate.
+    pub maker_fee: i64,
+}
+
+/// Outcome of `MatchingEngine::submit_order`.
+#[derive(Debug, Clone)]
+pub struct MatchResult {
+    pub order_id: u64,
+    pub fills: Vec<Fill>,
+    /// Resting order ids cancelled by Self-Trade Prevention. Populated under
+    /// `CancelResting`, `CancelBoth`, and whichever side `DecrementAndCancel`
+    /// zeroes out; empty under `CancelIncoming`, where only the taker is hit.
+    pub stp_cancels: Vec<u64>,
+    /// The `StpMode` this call ran under.
+    pub stp_mode: StpMode,
+    /// Whether STP aborted the incoming order's remaining quantity
+    /// (`CancelIncoming` or `CancelBoth` triggering).
+    pub taker_stp_cancelled: bool,
+    /// Quantity left over after matching (what got posted to the book).
+    pub resting_qty: u32,
+    /// Quantity left over after matching that was discarded instead of
+    /// resting — always 0 for `Limit`/`PostOnly`/`PostOnlySlide` (which only
+    /// ever rest or reject), and the unmatched remainder for `Market`/
+    /// `ImmediateOrCancel` (and, in practice, always 0 for `FillOrKill`,
+    /// which rejects up front rather than leaving anything unfilled).
+    /// Distinct from `resting_qty` so a caller can tell "fully filled" apart
+    /// from "partially filled, remainder cancelled" when both report 0 qty
+    /// left on the book.
+    pub cancelled_qty: u32,
+    /// The order type actually applied — equal to the one submitted, except
+    /// for `PostOnlySlide`, which this still reports even after repricing.
+    pub effective_order_type: OrderType,
+    /// The limit price actually used to match/rest the order: the implicit
+    /// `Market` limit, or the slid price for `PostOnlySlide`, or simply the
+    /// submitted price for every other order type.
+    pub effective_price: i64,
+    /// Fills generated by stop orders that this order's trade(s) triggered,
+    /// in trigger order. Empty unless this order moved `last_trade_price`
+    /// across a parked stop's trigger condition.
+    pub triggered_fills: Vec<Fill>,
+    /// Resting order ids dropped because they had expired (lazy-expiry-on-
+    /// match), bounded per call by `RiskConfig::max_expired_reap`.
+    pub expired_cancels: Vec<u64>,
+}
+
+/// How an order interacts with the opposing book when submitted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderType {
+    /// Rests on the book if not fully filled. The default order type.
+    Limit,
+    /// Crosses at any price and never rests. Implemented as a limit order
+    /// at the most permissive possible price (`i64::MAX` for buys, `1` for
+    /// sells) so price never blocks the cross.
+    Market,
+    /// Fills what it can immediately; any remainder is cancelled instead of
+    /// resting on the book.
+    ImmediateOrCancel,
+    /// Fills completely immediately or is rejected with
+    /// `RejectReason::FillOrKillUnfulfillable` without touching the book.
+    FillOrKill,
+    /// Rejected with `RejectReason::PostOnlyWouldCross` if it would
+    /// immediately match the opposing best price.
+    PostOnly,
+    /// Like `PostOnly`, but instead of rejecting, reprices one tick better
+    /// than the opposing best so it posts without crossing.
+    PostOnlySlide,
+}
+
+/// Why the Pre-Trade Risk Guardian rejected an order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RejectReason {
+    /// Price must be positive.
+    InvalidPrice,
+    /// Quantity must be positive.
+    InvalidQuantity,
+    /// Quantity exceeds the hard per-order cap.
+    MaxQuantity { requested: u32, max: u32 },
+    /// Price deviates from the last trade price by more than the configured
+    /// band.
+    FatFinger { order_price: i64, reference_price: i64 },
+    /// A `FillOrKill` order could not be completely filled within its limit.
+    FillOrKillUnfulfillable,
+    /// A `PostOnly` order would have immediately crossed the opposing book.
+    PostOnlyWouldCross,
+    /// Price is not an exact multiple of `RiskConfig::tick_size`.
+    InvalidTick,
+    /// Quantity is not an exact multiple of `RiskConfig::lot_size`.
+    InvalidLot,
+    /// Quantity is below `RiskConfig::min_order_qty`.
+    BelowMinSize { requested: u32, min: u32 },
+    /// A pegged order was submitted before `MatchingEngine::set_oracle_price`
+    /// ever supplied a reference price to peg against.
+    OraclePriceUnset,
+    /// `MatchingEngine::modify_order` was given an order id that isn't
+    /// currently resting on the book (already filled, cancelled, or never
+    /// existed).
+    OrderNotFound,
+    /// `MatchingEngine::remove_liquidity` was asked to withdraw more than
+    /// the AMM pool currently holds, or no pool is configured at all.
+    InsufficientLiquidity,
+}
+
+/// How Self-Trade Prevention resolves a maker/taker match belonging to the
+/// same trader.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StpMode {
+    /// Cancel the resting maker; the incoming order keeps matching. The
+    /// default, and the only behavior this engine had before `StpMode`.
+    CancelResting,
+    /// Abort the incoming order's remaining quantity without posting it;
+    /// the resting maker is left untouched.
+    CancelIncoming,
+    /// Cancel the resting maker AND abort the incoming order's remaining
+    /// quantity — neither side survives the self-match.
+    CancelBoth,
+    /// Decrement both sides by `min(resting_qty, incoming_qty)`; whichever
+    /// side reaches zero is cancelled (both, if the quantities were equal),
+    /// and matching continues with whatever's left.
+    DecrementAndCancel,
+}
+
+/// Maker/taker fee rates, in basis points (1 bp = 0.01%) of the fill's
+/// notional. The engine stamps the computed amounts onto each `Fill` as
+/// `taker_fee`/`maker_fee`; a caller (`NexusExchange`, or
+/// `crate::ledger::Ledger`) actually debits/credits the trader balances.
+#[derive(Debug, Clone, Copy)]
+pub struct FeeSchedule {
+    /// Rate charged to the aggressor (`Fill::taker_trader_id`).
+    pub taker_fee_bps: i64,
+    /// Rate charged to the resting side (`Fill::maker_trader_id`). A
+    /// negative rate is a maker rebate.
+    pub maker_fee_bps: i64,
+}
+
+impl Default for FeeSchedule {
+    fn default() -> Self {
+        Self { taker_fee_bps: 0, maker_fee_bps: 0 }
+    }
+}
+
+/// Reserved synthetic trader id standing in for the AMM pool as the maker
+/// side of a `Fill` generated by `MatchingEngine::submit_order` routing
+/// against it (see `AmmPool`). No caller is expected to ever register a real
+/// trader under this id, so `Guardian::charge_fee`/`settle_fill_v2` calls
+/// made against it are harmless no-ops, the same defensive behavior they
+/// already have for any other unknown trader id.
+pub const AMM_POOL_TRADER_ID: u32 = u32::MAX;
+
+/// An optional constant-product (`x * y = k`) liquidity pool an order routes
+/// against once `MatchingEngine::submit_order` has exhausted what the book
+/// can offer at its limit price. `base_reserve` is in the same raw-count
+/// units as `Order::qty`; `quote_reserve` is fixed-point quote notional
+/// (the same scale as `price * qty`). There is no per-provider accounting —
+/// `MatchingEngine::add_liquidity`/`remove_liquidity` only track the pool's
+/// aggregate reserves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AmmPool {
+    pub base_reserve: i64,
+    pub quote_reserve: i64,
+}
+
+/// Pre-trade risk limits enforced by `MatchingEngine::validate_risk`.
+#[derive(Debug, Clone)]
+pub struct RiskConfig {
+    /// Hard maximum quantity per order.
+    pub max_order_qty: u32,
+    /// Maximum allowed deviation from the last trade price, as a fixed-point
+    /// fraction scaled by 10^8 (e.g. `20_000_000` = 20%).
+    pub max_price_deviation_pct: i64,
+    /// Maximum number of stop-trigger waves processed per `submit_order`
+    /// call, bounding worst-case work when triggered stops move the price
+    /// far enough to cascade into further stops.
+    pub max_stop_cascade_depth: u32,
+    /// Maximum number of expired resting orders reaped per `submit_order`
+    /// call, bounding worst-case work when a crossing aggressor walks
+    /// through a backlog of stale GTD/DAY orders. Leftover expired orders
+    /// are reaped lazily on later crossings.
+    pub max_expired_reap: u32,
+    /// How Self-Trade Prevention resolves a same-trader maker/taker match.
+    pub stp_mode: StpMode,
+    /// Minimum price increment: a limit order's price must be an exact
+    /// multiple of this. `0` disables the check.
+    pub tick_size: i64,
+    /// Minimum quantity increment: an order's quantity must be an exact
+    /// multiple of this. `0` disables the check.
+    pub lot_size: u32,
+    /// Smallest quantity an order may be submitted with. `0` disables the
+    /// check.
+    pub min_order_qty: u32,
+    /// Maker/taker fee rates applied to this engine's `Fill`s (see
+    /// `Fill::taker_fee`/`maker_fee`). Zero rates (the default) collect no
+    /// fees. Change at runtime via `MatchingEngine::set_fee_schedule`.
+    pub fee_schedule: FeeSchedule,
+}
+
+impl Default for RiskConfig {
+    fn default() -> Self {
+        Self {
+            max_order_qty: 1_000_000,
+            max_price_deviation_pct: 20_000_000, // 20%
+            max_stop_cascade_depth: 10,
+            max_expired_reap: 100,
+            stp_mode: StpMode::CancelResting,
+            tick_size: 0,
+            lot_size: 0,
+            min_order_qty: 0,
+            fee_schedule: FeeSchedule { taker_fee_bps: 0, maker_fee_bps: 0 },
+        }
+    }
+}
+
+/// A conditional order parked off-book until its trigger condition fires.
+///
+/// A buy-stop triggers once `last_trade_price >= stop_price`; a sell-stop
+/// triggers once `last_trade_price <= stop_price`. `limit_price` distinguishes
+/// stop-market (`None`, triggers into a `Market` order) from stop-limit
+/// (`Some`, triggers into a `Limit` order at that price).
+#[derive(Debug, Clone, Copy)]
+pub struct StopOrder {
+    pub trader_id: u32,
+    pub order_id: u64,
+    pub side: Side,
+    pub stop_price: i64,
+    pub limit_price: Option<i64>,
+    pub qty: u32,
+    pub ts: u64,
+}
+
+/// A resting order whose price tracks an external oracle/reference price
+/// instead of sitting at a fixed absolute price.
+///
+/// The live quantity and book position are the resting `Order` itself;
+/// this is just the metadata `MatchingEngine::set_oracle_price` needs to
+/// find and reprice it — `current_price` records which level it's
+/// currently resting at, so a repeg knows where to pull it from.
+#[derive(Debug, Clone, Copy)]
+pub struct PeggedOrder {
+    pub trader_id: u32,
+    pub order_id: u64,
+    pub side: Side,
+    /// Offset from the oracle price: negative rests a buy below it,
+    /// positive rests a sell above it.
+    pub peg_offset: i64,
+    /// Per-order worst price the peg will never move past, even if the
+    /// oracle moves far enough to imply one. `None` means uncapped.
+    pub limit_price: Option<i64>,
+    pub current_price: i64,
+    pub ts: u64,
+}
+
+/// One price level in an L2 market data snapshot.
+#[derive(Debug, Clone, Copy)]
+pub struct L2Level {
+    pub price: i64,
+    pub qty: u64,
+    pub order_count: u32,
+}
+
+/// One side of the book: price levels, each a FIFO queue of resting orders.
+#[derive(Debug, Clone, Default)]
+pub struct BookSide {
+    pub levels: BTreeMap<i64, VecDeque<Order>>,
+    pub total_qty: u64,
+}
+
+impl BookSide {
+    fn new() -> Self {
+        Self::default()
+    }
+
+    /// Rest an order at the back of its price level's queue.
+    pub fn insert(&mut self, order: Order) {
+        self.total_qty += order.qty as u64;
+        self.levels.entry(order.price).or_default().push_back(order);
+    }
+}
+
+/// The limit order book for a single instrument.
+#[derive(Debug, Clone)]
+pub struct OrderBook {
+    pub bids: BookSide,
+    pub asks: BookSide,
+    /// Price of the most recent trade, used as the Fat-Finger reference.
+    pub last_trade_price: Option<i64>,
+    next_order_id: u64,
+    /// Secondary index from order id to its resting side and price level, so
+    /// `cancel_order`/`find_resting` can go straight to the one level an
+    /// order rests on instead of scanning every level. `pub(crate)` so the
+    /// matching loop in `mod.rs` can drop an entry directly as it pops an
+    /// order off a level, without re-borrowing the whole `OrderBook`
+    /// through a method while that level is already borrowed.
+    pub(crate) order_index: HashMap<u64, (Side, i64)>,
+}
+
+impl OrderBook {
+    pub fn new() -> Self {
+        Self {
+            bids: BookSide::new(),
+            asks: BookSide::new(),
+            last_trade_price: None,
+            next_order_id: 0,
+            order_index: HashMap::new(),
+        }
+    }
+
+    /// Allocate the next unique order id.
+    pub fn next_order_id(&mut self) -> u64 {
+        self.next_order_id += 1;
+        self.next_order_id
+    }
+
+    /// Read the next-order-id counter without allocating one. Used by the
+    /// checkpoint subsystem to snapshot it.
+    pub(crate) fn peek_next_order_id(&self) -> u64 {
+        self.next_order_id
+    }
+
+    /// Restore the next-order-id counter from a checkpoint, so ids allocated
+    /// after recovery continue from where the snapshot left off instead of
+    /// colliding with orders it already restored.
+    pub(crate) fn restore_next_order_id(&mut self, value: u64) {
+        self.next_order_id = value;
+    }
+
+    /// Rest `order` on `side`, recording it in the secondary index so it can
+    /// later be found by `cancel_order`/`find_resting` in O(1) instead of a
+    /// full book scan.
+    pub fn insert(&mut self, side: Side, order: Order) {
+        self.order_index.insert(order.order_id, (side, order.price));
+        match side {
+            Side::Buy => self.bids.insert(order),
+            Side::Sell => self.asks.insert(order),
+        }
+    }
+
+    /// Look up a resting order by id via the secondary index, then find it
+    /// within that one level. O(level size), not O(N) over the whole book.
+    pub(crate) fn find_resting(&self, order_id: u64) -> Option<(Side, Order)> {
+        let (side, price) = *self.order_index.get(&order_id)?;
+        let level = match side {
+            Side::Buy => self.bids.levels.get(&price),
+            Side::Sell => self.asks.levels.get(&price),
+        }?;
+        level.iter().find(|o| o.order_id == order_id).copied().map(|o| (side, o))
+    }
+
+    /// Cancel a single resting order by id. Returns whether it was found.
+    pub fn cancel_order(&mut self, order_id: u64) -> bool {
+        let (side, price) = match self.order_index.get(&order_id).copied() {
+            Some(v) => v,
+            None => return false,
+        };
+        let side_book = match side {
+            Side::Buy => &mut self.bids,
+            Side::Sell => &mut self.asks,
+        };
+        let level = match side_book.levels.get_mut(&price) {
+            Some(l) => l,
+            None => return false,
+        };
+        let pos = match level.iter().position(|o| o.order_id == order_id) {
+            Some(p) => p,
+            None => return false,
+        };
+        let removed = level.remove(pos).unwrap();
+        side_book.total_qty -= removed.qty as u64;
+        if level.is_empty() {
+            side_book.levels.remove(&price);
+        }
+        self.order_index.remove(&order_id);
+        true
+    }
+
+    /// Best (highest) bid price. O(log N).
+    pub fn best_bid(&self) -> Option<i64> {
+        self.bids.levels.keys().next_back().copied()
+    }
+
+    /// Best (lowest) ask price. O(log N).
+    pub fn best_ask(&self) -> Option<i64> {
+        self.asks.levels.keys().next().copied()
+    }
+
+    /// Top `depth` price levels on each side, best price first.
+    pub fn l2_snapshot(&self, depth: usize) -> (Vec<L2Level>, Vec<L2Level>) {
+        let bids = self
+            .bids
+            .levels
+            .iter()
+            .rev()
+            .take(depth)
+            .map(|(price, orders)| L2Level {
+                price: *price,
+                qty: orders.iter().map(|o| o.qty as u64).sum(),
+                order_count: orders.len() as u32,
+            })
+            .collect();
+
+        let asks = self
+            .asks
+            .levels
+            .iter()
+            .take(depth)
+            .map(|(price, orders)| L2Level {
+                price: *price,
+                qty: orders.iter().map(|o| o.qty as u64).sum(),
+                order_count: orders.len() as u32,
+            })
+            .collect();
+
+        (bids, asks)
+    }
+
+    /// Reset the book to empty (session reset). Preserves the order id
+    /// counter so ids stay unique across a reset.
+    pub fn clear(&mut self) {
+        self.bids = BookSide::new();
+        self.asks = BookSide::new();
+        self.last_trade_price = None;
+    }
+
+    /// Eagerly sweep every resting order whose `expiry_ts` has passed as of
+    /// `now`, on both sides, with no per-call cap. Returns the reaped order
+    /// ids. Unlike the lazy-expiry-on-match path in `MatchingEngine`, this is
+    /// unbounded — callers who want eager cleanup (e.g. an end-of-day job)
+    /// accept the O(N) cost explicitly by calling this directly.
+    pub fn reap_expired(&mut self, now: u64) -> Vec<u64> {
+        let mut reaped = Vec::new();
+        for side in [&mut self.bids, &mut self.asks] {
+            let BookSide { levels, total_qty } = side;
+            let mut empty_levels = Vec::new();
+            for (price, level) in levels.iter_mut() {
+                let before = level.len();
+                level.retain(|o| {
+                    let expired = matches!(o.expiry_ts, Some(e) if e <= now);
+                    if expired {
+                        reaped.push(o.order_id);
+                        *total_qty -= o.qty as u64;
+                    }
+                    !expired
+                });
+                if level.len() != before && level.is_empty() {
+                    empty_levels.push(*price);
+                }
+            }
+            for price in empty_levels {
+                levels.remove(&price);
+            }
+        }
+        for order_id in &reaped {
+            self.order_index.remove(order_id);
+        }
+        reaped
+    }
+
+    /// Every resting order belonging to `trader_id`, on both sides, without
+    /// removing them. Used to snapshot each order's side/price/qty before a
+    /// bulk cancel, so the caller can unlock the exact margin amount and log
+    /// the cancellation per order. O(N) over all resting orders.
+    pub fn resting_orders_for_trader(&self, trader_id: u32) -> Vec<(Side, Order)> {
+        self.bids
+            .levels
+            .values()
+            .map(|level| (Side::Buy, level))
+            .chain(self.asks.levels.values().map(|level| (Side::Sell, level)))
+            .flat_map(|(side, level)| level.iter().map(move |o| (side, *o)))
+            .filter(|(_, o)| o.trader_id == trader_id)
+            .collect()
+    }
+
+    /// Cancel every resting order belonging to `trader_id`, on both sides.
+    /// Returns the cancelled order ids. O(N) over all resting orders.
+    pub fn cancel_all_for_trader(&mut self, trader_id: u32) -> Vec<u64> {
+        let mut cancelled = Vec::new();
+        for side in [&mut self.bids, &mut self.asks] {
+            let BookSide { levels, total_qty } = side;
+            let mut empty_levels = Vec::new();
+            for (price, level) in levels.iter_mut() {
+                let before = level.len();
+                level.retain(|o| {
+                    let keep = o.trader_id != trader_id;
+                    if !keep {
+                        cancelled.push(o.order_id);
+                        *total_qty -= o.qty as u64;
+                    }
+                    keep
+                });
+                if level.len() != before && level.is_empty() {
+                    empty_levels.push(*price);
+                }
+            }
+            for price in empty_levels {
+                levels.remove(&price);
+            }
+        }
+        for order_id in &cancelled {
+            self.order_index.remove(order_id);
+        }
+        cancelled
+    }
+}
+
+impl Default for OrderBook {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn order(trader_id: u32, order_id: u64, price: i64, qty: u32, ts: u64) -> Order {
+        Order { trader_id, order_id, price, qty, ts, tif: TimeInForce::GTC, expiry_ts: None }
+    }
+
+    #[test]
+    fn test_next_order_id_increments() {
+        let mut book = OrderBook::new();
+        assert_eq!(book.next_order_id(), 1);
+        assert_eq!(book.next_order_id(), 2);
+    }
+
+    #[test]
+    fn test_insert_tracks_best_price_and_total_qty() {
+        let mut book = OrderBook::new();
+        book.bids.insert(order(1, 1, 100, 10, 1));
+        book.bids.insert(order(2, 2, 105, 10, 2));
+        assert_eq!(book.best_bid(), Some(105));
+        assert_eq!(book.bids.total_qty, 20);
+    }
+
+    #[test]
+    fn test_l2_snapshot_orders_by_best_price_first() {
+        let mut book = OrderBook::new();
+        book.bids.insert(order(1, 1, 99, 10, 1));
+        book.bids.insert(order(2, 2, 100, 20, 2));
+        book.asks.insert(order(3, 3, 102, 25, 3));
+        book.asks.insert(order(4, 4, 101, 15, 4));
+
+        let (bids, asks) = book.l2_snapshot(5);
+        assert_eq!(bids[0].price, 100);
+        assert_eq!(bids[0].qty, 20);
+        assert_eq!(asks[0].price, 101);
+        assert_eq!(asks[0].qty, 15);
+    }
+
+    #[test]
+    fn test_cancel_all_for_trader_removes_empty_levels() {
+        let mut book = OrderBook::new();
+        book.bids.insert(order(1, 1, 100, 10, 1));
+        book.asks.insert(order(1, 2, 105, 20, 2));
+        book.bids.insert(order(2, 3, 99, 30, 3));
+
+        let cancelled = book.cancel_all_for_trader(1);
+        assert_eq!(cancelled.len(), 2);
+        assert_eq!(book.best_bid(), Some(99));
+        assert_eq!(book.best_ask(), None);
+        assert_eq!(book.bids.total_qty, 30);
+    }
+
+    #[test]
+    fn test_clear_resets_book_but_keeps_order_id_counter() {
+        let mut book = OrderBook::new();
+        book.next_order_id();
+        book.bids.insert(order(1, 1, 100, 10, 1));
+        book.clear();
+        assert_eq!(book.best_bid(), None);
+        assert_eq!(book.bids.total_qty, 0);
+        assert_eq!(book.next_order_id(), 2);
+    }
+
+    #[test]
+    fn test_reap_expired_removes_only_past_expiry_orders() {
+        let mut book = OrderBook::new();
+        let mut fresh = order(1, 1, 100, 10, 1);
+        fresh.tif = TimeInForce::GTD;
+        fresh.expiry_ts = Some(100);
+        let mut stale = order(2, 2, 99, 20, 2);
+        stale.tif = TimeInForce::DAY;
+        stale.expiry_ts = Some(10);
+        book.bids.insert(fresh);
+        book.bids.insert(stale);
+
+        let reaped = book.reap_expired(50);
+        assert_eq!(reaped, vec![2]);
+        assert_eq!(book.best_bid(), Some(100));
+        assert_eq!(book.bids.total_qty, 10);
+    }
+
+    #[test]
+    fn test_cancel_order_removes_resting_order_and_empty_level() {
+        let mut book = OrderBook::new();
+        book.insert(Side::Buy, order(1, 1, 100, 10, 1));
+
+        assert!(book.cancel_order(1));
+        assert_eq!(book.best_bid(), None);
+        assert_eq!(book.bids.total_qty, 0);
+    }
+
+    #[test]
+    fn test_cancel_order_unknown_id_returns_false() {
+        let mut book = OrderBook::new();
+        book.insert(Side::Buy, order(1, 1, 100, 10, 1));
+        assert!(!book.cancel_order(999));
+        assert_eq!(book.best_bid(), Some(100)); // Untouched.
+    }
+
+    #[test]
+    fn test_cancel_order_leaves_other_orders_at_the_same_level() {
+        let mut book = OrderBook::new();
+        book.insert(Side::Sell, order(1, 1, 100, 10, 1));
+        book.insert(Side::Sell, order(2, 2, 100, 20, 2));
+
+        assert!(book.cancel_order(1));
+        assert_eq!(book.best_ask(), Some(100));
+        assert_eq!(book.asks.total_qty, 20);
+
+        let (_, asks) = book.l2_snapshot(5);
+        assert_eq!(asks[0].qty, 20);
+        assert_eq!(asks[0].order_count, 1);
+    }
+
+    #[test]
+    fn test_find_resting_locates_order_by_id() {
+        let mut book = OrderBook::new();
+        book.insert(Side::Buy, order(7, 1, 100, 10, 1));
+
+        let (side, found) = book.find_resting(1).unwrap();
+        assert_eq!(side, Side::Buy);
+        assert_eq!(found.trader_id, 7);
+        assert_eq!(found.qty, 10);
+        assert!(book.find_resting(999).is_none());
+    }
+}
@@ -0,0 +1,215 @@
+// nexus_core/src/persistence/segmented.rs
+//
+// SegmentedSentinel — rolls a WAL over to a new backing file instead of
+// hard-failing once the active segment's fixed-capacity mmap fills up.
+//
+// Segments live in one directory, named `<stem>.NNNN.wal` (zero-padded, so a
+// directory listing already sorts in write order). Sequence numbers stay
+// globally monotonic across segments: a segment's base sequence number is
+// simply its first entry's own `JournalHeader.sequence_number` — `Sentinel`
+// already generalizes its recovery check to compare against a running
+// counter rather than assuming every file starts at zero, so no extra
+// on-disk field is needed to make a segment self-describing. That means
+// recovery (or a deleted-prefix directory after compaction) can open any
+// segment on its own and know where it sits in the global sequence, without
+// having to replay the segments before it.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use super::{JournalEntry, Sentinel};
+
+/// Metadata kept for a segment that's no longer being appended to, so
+/// `SegmentedSentinel` doesn't have to keep every past segment's mmap open.
+struct ClosedSegment {
+    path: PathBuf,
+    base_seq: u64,
+    entry_count: u64,
+}
+
+impl ClosedSegment {
+    /// Global sequence number one past this segment's last entry — i.e. the
+    /// base sequence number the next segment must start at.
+    fn next_seq(&self) -> u64 {
+        self.base_seq + self.entry_count
+    }
+}
+
+/// A WAL split across multiple fixed-capacity segment files, so a
+/// long-running exchange isn't bounded by a single `Sentinel`'s mmap size.
+///
+/// Only the active (newest) segment is kept mapped; closed segments are
+/// reopened on demand by `read_all_entries` and deleted outright once a
+/// checkpoint has made them redundant.
+pub struct SegmentedSentinel {
+    dir: PathBuf,
+    stem: String,
+    capacity: usize,
+    closed: Vec<ClosedSegment>,
+    active: Sentinel,
+    active_index: u32,
+}
+
+impl SegmentedSentinel {
+    /// Open (or create) a segmented WAL under `dir`, with segment files
+    /// named `<stem>.NNNN.wal`. Existing segments are discovered from the
+    /// directory listing and reopened in order so the global sequence
+    /// counter picks up where the last run left off.
+    pub fn open<P: AsRef<Path>>(dir: P, stem: &str, capacity: usize) -> io::Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+
+        let indices = Self::list_segment_indices(&dir, stem)?;
+
+        let mut closed = Vec::new();
+        let mut next_seq = 0u64;
+        let mut active_index = 0u32;
+
+        if let Some((&last, rest)) = indices.split_last() {
+            for &index in rest {
+                let path = Self::segment_path(&dir, stem, index);
+                // Reopened only long enough to read off its base sequence
+                // number and entry count — this happens once per segment,
+                // at startup.
+                let segment = Sentinel::open_segment(&path, capacity, next_seq, &[])?;
+                let entry_count = segment.entry_count() - segment.base_seq();
+                closed.push(ClosedSegment { path, base_seq: segment.base_seq(), entry_count });
+                next_seq = segment.entry_count();
+            }
+            active_index = last;
+        }
+
+        let active_path = Self::segment_path(&dir, stem, active_index);
+        let active = Sentinel::open_segment(&active_path, capacity, next_seq, &[])?;
+
+        Ok(Self { dir, stem: stem.to_string(), capacity, closed, active, active_index })
+    }
+
+    fn segment_path(dir: &Path, stem: &str, index: u32) -> PathBuf {
+        dir.join(format!("{stem}.{index:04}.wal"))
+    }
+
+    /// Every `<stem>.NNNN.wal` index found in `dir`, ascending.
+    fn list_segment_indices(dir: &Path, stem: &str) -> io::Result<Vec<u32>> {
+        let entries = match fs::read_dir(dir) {
+            Ok(e) => e,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+
+        let prefix = format!("{stem}.");
+        let mut indices = Vec::new();
+        for entry in entries {
+            let entry = entry?;
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            if let Some(index) = name
+                .strip_prefix(prefix.as_str())
+                .and_then(|s| s.strip_suffix(".wal"))
+                .and_then(|s| s.parse::<u32>().ok())
+            {
+                indices.push(index);
+            }
+        }
+        indices.sort_unstable();
+        Ok(indices)
+    }
+
+    /// Append a message, rolling over to a new segment file first if it
+    /// wouldn't fit in the active one.
+    pub fn append(&mut self, msg_type: u8, payload: &[u8], timestamp_ns: u64) -> io::Result<u64> {
+        match self.active.append(msg_type, payload, timestamp_ns) {
+            Ok(seq) => Ok(seq),
+            Err(e) if e.kind() == io::ErrorKind::OutOfMemory => {
+                self.roll_segment()?;
+                self.active.append(msg_type, payload, timestamp_ns)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Close the active segment and open a fresh one, continuing the global
+    /// sequence counter where the closed one left off.
+    fn roll_segment(&mut self) -> io::Result<()> {
+        self.active.flush()?;
+
+        let next_seq = self.active.entry_count();
+        self.closed.push(ClosedSegment {
+            path: self.active.path().to_path_buf(),
+            base_seq: self.active.base_seq(),
+            entry_count: next_seq - self.active.base_seq(),
+        });
+
+        self.active_index += 1;
+        let path = Self::segment_path(&self.dir, &self.stem, self.active_index);
+        self.active = Sentinel::open_segment(path, self.capacity, next_seq, &[])?;
+        Ok(())
+    }
+
+    /// Force-flush the active segment to disk.
+    pub fn flush(&self) -> io::Result<()> {
+        self.active.flush()
+    }
+
+    /// Read every entry across every segment, oldest segment first, in the
+    /// same global sequence order they were appended in.
+    pub fn read_all_entries(&self) -> io::Result<Vec<JournalEntry>> {
+        let mut entries = Vec::new();
+        for segment in &self.closed {
+            let sentinel = Sentinel::open_segment(&segment.path, self.capacity, segment.base_seq, &[])?;
+            entries.extend(sentinel.read_all_entries());
+        }
+        entries.extend(self.active.read_all_entries());
+        Ok(entries)
+    }
+
+    /// Total entries written across every segment (the next global sequence
+    /// number to be assigned).
+    pub fn entry_count(&self) -> u64 {
+        self.active.entry_count()
+    }
+
+    /// A synthetic path — `<dir>/<stem>`, never created on disk — whose
+    /// parent directory and file name checkpoint helpers (`checkpoint_path`,
+    /// `list_checkpoints`) can derive sibling `.snap` file names from, the
+    /// same way they do for a single `Sentinel`'s real WAL path.
+    pub fn base_path(&self) -> PathBuf {
+        self.dir.join(&self.stem)
+    }
+
+    /// Reset the WAL: delete every closed segment file and truncate the
+    /// active one back to empty. Use for test cleanup or session reset,
+    /// mirroring `Sentinel::reset`.
+    pub fn reset(&mut self) -> io::Result<()> {
+        for segment in self.closed.drain(..) {
+            fs::remove_file(&segment.path)?;
+        }
+        self.active.reset();
+        self.active_index = 0;
+        let path = Self::segment_path(&self.dir, &self.stem, self.active_index);
+        if self.active.path() != path {
+            fs::remove_file(self.active.path()).ok();
+            self.active = Sentinel::open_segment(&path, self.capacity, 0, &[])?;
+        }
+        Ok(())
+    }
+
+    /// Delete every closed segment whose entries are all `<=
+    /// covered_through_seq` — i.e. fully reflected by a checkpoint tagged
+    /// with that sequence number, so recovery would never need to replay
+    /// them. The active segment is never deleted, since appends keep
+    /// landing in it.
+    pub fn compact_through(&mut self, covered_through_seq: u64) -> io::Result<()> {
+        let mut keep = Vec::with_capacity(self.closed.len());
+        for segment in self.closed.drain(..) {
+            if segment.entry_count > 0 && segment.next_seq() - 1 <= covered_through_seq {
+                fs::remove_file(&segment.path)?;
+            } else {
+                keep.push(segment);
+            }
+        }
+        self.closed = keep;
+        Ok(())
+    }
+}
@@ -20,6 +20,7 @@ use pyo3::exceptions::{PyValueError, PyRuntimeError};
 
 use crate::persistence::NexusExchange;
 use crate::types::Side;
+use crate::matching::{FeeSchedule, OrderType};
 use crate::SCALE;
 
 use std::time::Instant;
@@ -28,12 +29,46 @@ use std::time::Instant;
 // Performance Tracker
 // ---------------------------------------------------------------------------
 
+/// Number of bins in `PerfTracker`'s latency histogram — one per sub-bucket
+/// of every power-of-two latency range a `u64` nanosecond count can fall in
+/// (64 possible exponents * 8 sub-buckets each).
+const NUM_LATENCY_BINS: usize = 512;
+
+/// Map a latency in nanoseconds to its histogram bin: a power-of-two bucket
+/// (`ilog2`) subdivided into 8 linear sub-buckets, so a bin's midpoint is
+/// within 1/16 (~6%) of any latency in it at any scale (the same layout HDR
+/// histograms use) without the O(n) insertion cost of keeping every sample
+/// sorted. Below 8 ns the sub-bucket is the latency itself.
+fn latency_bin(latency_ns: u64) -> usize {
+    let v = latency_ns.max(1);
+    let exp = v.ilog2();
+    let shift = exp.saturating_sub(3);
+    let sub = (v >> shift) & 7;
+    (exp as u64 * 8 + sub) as usize
+}
+
+/// Inverse of `latency_bin`: the `[lower, lower + width)` nanosecond range a
+/// bin covers.
+fn latency_bin_range(bin: usize) -> (u64, u64) {
+    let exp = (bin / 8) as u32;
+    let sub = (bin % 8) as u64;
+    if exp < 3 {
+        // Below 8 ns `latency_bin` keeps the whole latency as the sub-bucket.
+        return (sub, 1);
+    }
+    let shift = exp.saturating_sub(3);
+    let width = 1u64 << shift;
+    let lower = (1u64 << exp) + sub * width;
+    (lower, width)
+}
+
 /// Tracks hot-path latency for the dashboard.
 struct PerfTracker {
     total_orders: u64,
     total_fills: u64,
     total_volume: u64,
-    cumulative_latency_ns: u64,
+    latency_histogram: [u64; NUM_LATENCY_BINS],
+    max_latency_ns: u64,
     last_match_latency_ns: u64,
 }
 
@@ -43,7 +78,8 @@ impl PerfTracker {
             total_orders: 0,
             total_fills: 0,
             total_volume: 0,
-            cumulative_latency_ns: 0,
+            latency_histogram: [0; NUM_LATENCY_BINS],
+            max_latency_ns: 0,
             last_match_latency_ns: 0,
         }
     }
@@ -52,15 +88,100 @@ impl PerfTracker {
         self.total_orders += 1;
         self.total_fills += fills as u64;
         self.total_volume += volume;
-        self.cumulative_latency_ns += latency_ns;
+        self.latency_histogram[latency_bin(latency_ns)] += 1;
+        self.max_latency_ns = self.max_latency_ns.max(latency_ns);
         self.last_match_latency_ns = latency_ns;
     }
 
-    fn avg_latency_ns(&self) -> u64 {
-        if self.total_orders == 0 { 0 } else {
-            self.cumulative_latency_ns / self.total_orders
+    /// The latency at or below which `p` of recorded orders fall (e.g.
+    /// `p = 0.99` for p99), read off the histogram in O(bins) time. Returns
+    /// the representative latency (bin midpoint) of the bin the running
+    /// count first reaches `p * total_orders` in, not the exact sample.
+    fn percentile_latency_ns(&self, p: f64) -> u64 {
+        if self.total_orders == 0 {
+            return 0;
+        }
+        let target = (self.total_orders as f64 * p).ceil() as u64;
+        let mut running = 0u64;
+        for (bin, &count) in self.latency_histogram.iter().enumerate() {
+            running += count;
+            if running >= target {
+                let (lower, width) = latency_bin_range(bin);
+                return lower + width / 2;
+            }
         }
+        self.max_latency_ns
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Wire-protocol framing — self-describing magic/version/kind header
+// ---------------------------------------------------------------------------
+//
+// Every buffer `submit_order`, `get_l2_snapshot` and friends hand back to
+// Python as plain bytes opens with a 4-byte header:
+//   [2: magic ("NX")][1: format_version][1: record_kind]
+// so a field layout change bumps `PROTOCOL_VERSION` and a stale caller gets
+// a precise `PyValueError` instead of silently misreading shifted bytes.
+// `describe_layout` is the authoritative source for what comes after the
+// header, so callers generate their `struct`/`numpy` decoding from Rust
+// instead of hand-maintaining stride constants.
+//
+// The buffer-protocol views (`RecordBuffer`, `submit_order_array`,
+// `get_l2_array`) deliberately sit outside this framing: `numpy.frombuffer`
+// already requires the raw buffer length to be an exact multiple of
+// `itemsize`, and the format string `RecordBuffer` hands the buffer
+// protocol is itself a self-describing contract, so a magic header would
+// just be four bytes numpy has to be told to skip. Same reasoning for
+// `submit_orders_batch`'s fixed-stride input/output: it predates framing
+// and stays a raw, header-free contract for throughput.
+const PROTOCOL_MAGIC: [u8; 2] = *b"NX";
+
+/// Bumped whenever a framed record's field layout changes non-additively.
+pub const PROTOCOL_VERSION: u8 = 1;
+
+/// `describe_layout`/header `record_kind` for the packed order input.
+pub const RECORD_KIND_ORDER: u8 = 1;
+/// `describe_layout`/header `record_kind` for `serialize_fills`' output.
+pub const RECORD_KIND_FILL: u8 = 2;
+/// `describe_layout`/header `record_kind` for `serialize_l2_levels`' output.
+pub const RECORD_KIND_L2: u8 = 3;
+
+/// Prefix `payload` with the 4-byte framing header for `record_kind`.
+fn frame(record_kind: u8, payload: Vec<u8>) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(4 + payload.len());
+    buf.extend_from_slice(&PROTOCOL_MAGIC);
+    buf.push(PROTOCOL_VERSION);
+    buf.push(record_kind);
+    buf.extend_from_slice(&payload);
+    buf
+}
+
+/// Validate a framed buffer's 4-byte header against `expected_kind`.
+/// Returns the byte offset the payload starts at (always 4) on success, so
+/// callers can slice past the header in one step.
+fn validate_header(bytes: &[u8], expected_kind: u8) -> PyResult<usize> {
+    if bytes.len() < 4 {
+        return Err(PyValueError::new_err(format!(
+            "Record too short for a framing header: need >= 4 bytes, got {}", bytes.len()
+        )));
+    }
+    if bytes[0..2] != PROTOCOL_MAGIC {
+        return Err(PyValueError::new_err(format!(
+            "Bad magic bytes {:?}, expected {:?}", &bytes[0..2], PROTOCOL_MAGIC
+        )));
+    }
+    if bytes[2] != PROTOCOL_VERSION {
+        return Err(PyValueError::new_err(format!(
+            "Unsupported format_version {}; this build speaks version {}", bytes[2], PROTOCOL_VERSION
+        )));
     }
+    if bytes[3] != expected_kind {
+        return Err(PyValueError::new_err(format!(
+            "record_kind mismatch: expected {}, got {}", expected_kind, bytes[3]
+        )));
+    }
+    Ok(4)
 }
 
 // ---------------------------------------------------------------------------
@@ -74,7 +195,10 @@ impl PerfTracker {
 //
 pub const FILL_RECORD_SIZE: usize = 40;
 
-fn serialize_fills(fills: &[crate::matching::Fill]) -> Vec<u8> {
+/// The raw, header-free fill records — used wherever multiple fixed-stride
+/// records get concatenated into one buffer (batch/array/simulation
+/// release paths) and a mid-buffer header would break the stride.
+fn serialize_fill_records(fills: &[crate::matching::Fill]) -> Vec<u8> {
     let mut buf = Vec::with_capacity(fills.len() * FILL_RECORD_SIZE);
     for fill in fills {
         buf.extend_from_slice(&fill.maker_order_id.to_le_bytes());
@@ -88,6 +212,12 @@ fn serialize_fills(fills: &[crate::matching::Fill]) -> Vec<u8> {
     buf
 }
 
+/// `serialize_fill_records`, framed with the protocol header. This is what
+/// single-buffer callers (`submit_order`) hand back to Python.
+fn serialize_fills(fills: &[crate::matching::Fill]) -> Vec<u8> {
+    frame(RECORD_KIND_FILL, serialize_fill_records(fills))
+}
+
 // ---------------------------------------------------------------------------
 // L2 Level serialization (16 bytes per level)
 // ---------------------------------------------------------------------------
@@ -96,7 +226,9 @@ fn serialize_fills(fills: &[crate::matching::Fill]) -> Vec<u8> {
 //
 pub const L2_LEVEL_SIZE: usize = 16;
 
-fn serialize_l2_levels(levels: &[crate::matching::L2Level]) -> Vec<u8> {
+/// The raw, header-free L2 level records — used by the buffer-protocol view
+/// (`get_l2_array`), which needs an exact multiple of `L2_LEVEL_SIZE`.
+fn serialize_l2_level_records(levels: &[crate::matching::L2Level]) -> Vec<u8> {
     let mut buf = Vec::with_capacity(levels.len() * L2_LEVEL_SIZE);
     for level in levels {
         buf.extend_from_slice(&level.price.to_le_bytes());
@@ -106,6 +238,293 @@ fn serialize_l2_levels(levels: &[crate::matching::L2Level]) -> Vec<u8> {
     buf
 }
 
+/// `serialize_l2_level_records`, framed with the protocol header. This is
+/// what single-buffer callers (`get_l2_snapshot`) hand back to Python.
+fn serialize_l2_levels(levels: &[crate::matching::L2Level]) -> Vec<u8> {
+    frame(RECORD_KIND_L2, serialize_l2_level_records(levels))
+}
+
+/// Decode the packed order format's trailing `[1: order_type]` byte (0=Limit
+/// GTC, 1=Market, 2=IOC, 3=FOK, 4=Post-Only). Missing — i.e. the original
+/// 17-byte form with no trailing byte — is treated as Limit GTC, for
+/// backward compatibility with callers built before this byte existed.
+fn order_type_from_wire(byte: Option<u8>) -> PyResult<OrderType> {
+    match byte {
+        None | Some(0) => Ok(OrderType::Limit),
+        Some(1) => Ok(OrderType::Market),
+        Some(2) => Ok(OrderType::ImmediateOrCancel),
+        Some(3) => Ok(OrderType::FillOrKill),
+        Some(4) => Ok(OrderType::PostOnly),
+        Some(v) => Err(PyValueError::new_err(format!(
+            "Invalid order_type byte: {}. Must be 0-4 (Limit/Market/IOC/FOK/PostOnly)", v
+        ))),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Deterministic simulation mode (virtual clock + injected latency/slippage)
+// ---------------------------------------------------------------------------
+//
+// Live, `self.sim` is `None` and every order submits and matches immediately
+// against wall time, exactly as before this existed. With `simulated=True`,
+// a virtual clock (`SimState::now_ns`) stands in for `Instant::now()`, and
+// orders can be held in `SimState::pending` — a latency-sorted queue — until
+// `advance_clock`/`set_now` moves the virtual clock past their sampled
+// arrival time, at which point they're submitted through the ordinary
+// pipeline and their fills are handed back from the clock call instead of
+// from `submit_order`. This makes a replay driven by a fixed seed and a
+// fixed sequence of `(order, advance_clock)` calls produce bit-for-bit the
+// same fills every run, regardless of how fast the host machine is.
+
+/// An order whose effect is deferred until the virtual clock reaches
+/// `release_ns` — the result of sampling `SimState`'s latency model at
+/// submission time. `seq` breaks ties between orders that land on the same
+/// nanosecond, preserving submission order.
+struct PendingOrder {
+    release_ns: u64,
+    seq: u64,
+    trader_id: u32,
+    side: Side,
+    order_type: OrderType,
+    price: i64,
+    qty: u32,
+}
+
+/// Virtual-clock state for a `simulated=True` exchange: the clock itself,
+/// the injected latency and slippage models, and the queue of orders
+/// awaiting release.
+struct SimState {
+    now_ns: u64,
+    latency_model: Option<(u64, u64)>,
+    slippage_bps: i64,
+    pending: Vec<PendingOrder>,
+    rng_state: u64,
+    seq: u64,
+}
+
+impl SimState {
+    /// A fixed seed, not a wall-clock one — "deterministic" means the same
+    /// seed every run, not a seed that merely looks random.
+    const INITIAL_SEED: u64 = 0x2545_F491_4F6C_DD1D;
+
+    fn new() -> Self {
+        Self {
+            now_ns: 0,
+            latency_model: None,
+            slippage_bps: 0,
+            pending: Vec::new(),
+            rng_state: Self::INITIAL_SEED,
+            seq: 0,
+        }
+    }
+
+    /// splitmix64 — small, dependency-free, and stable across platforms, so
+    /// a latency draw only depends on the seed and call order, never on the
+    /// host's OS RNG.
+    fn next_rng(&mut self) -> u64 {
+        self.rng_state = self.rng_state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.rng_state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Sample one latency draw: `base_ns + uniform(0, jitter_ns)`, or `0` if
+    /// no latency model is configured.
+    fn sample_latency_ns(&mut self) -> u64 {
+        match self.latency_model {
+            None => 0,
+            Some((base_ns, 0)) => base_ns,
+            Some((base_ns, jitter_ns)) => base_ns + self.next_rng() % (jitter_ns + 1),
+        }
+    }
+
+    /// Worsen `price` by `slippage_bps` for the aggressor — buys pay more,
+    /// sells receive less — emulating the price impact a live venue would
+    /// add. Market orders already submit with a sentinel price the engine
+    /// ignores in favor of the resting book, so slippage has nothing to
+    /// adjust there, and a Post-Only order never takes liquidity; it only
+    /// affects the price of a Limit, IOC, or FOK order.
+    fn apply_slippage(&self, side: Side, order_type: OrderType, price: i64) -> i64 {
+        if self.slippage_bps == 0 || matches!(order_type, OrderType::Market | OrderType::PostOnly) {
+            return price;
+        }
+        let adj = ((price as i128 * self.slippage_bps as i128) / 10_000).unsigned_abs() as i64;
+        match side {
+            Side::Buy => price.saturating_add(adj),
+            Side::Sell => price.saturating_sub(adj),
+        }
+    }
+
+    fn queue(&mut self, trader_id: u32, side: Side, order_type: OrderType, price: i64, qty: u32) {
+        let release_ns = self.now_ns.saturating_add(self.sample_latency_ns());
+        self.seq += 1;
+        self.pending.push(PendingOrder { release_ns, seq: self.seq, trader_id, side, order_type, price, qty });
+    }
+
+    /// Pop every pending order whose `release_ns` is now `<= self.now_ns`,
+    /// in release order (ties broken by submission order).
+    fn drain_due(&mut self) -> Vec<PendingOrder> {
+        self.pending.sort_by_key(|o| (o.release_ns, o.seq));
+        let split = self.pending.partition_point(|o| o.release_ns <= self.now_ns);
+        self.pending.drain(..split).collect()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Zero-copy buffer-protocol views (for numpy.frombuffer / structured dtypes)
+// ---------------------------------------------------------------------------
+//
+// The `serialize_*` functions above already produce fixed-stride byte
+// buffers; `RecordBuffer` is just a thin wrapper exposing the same bytes
+// through Python's buffer protocol (`__getbuffer__`/`__releasebuffer__`)
+// with a `struct`-module format string, so `numpy.frombuffer(buf,
+// dtype=...)` views them with no extra copy instead of Python having to
+// reinterpret raw `bytes` by hand.
+
+/// Null-terminated `struct`-module format string for a view over
+/// `serialize_l2_level_records`'s 16-byte records: `price:i64, qty:u32, orders:u32`.
+const L2_BUFFER_FORMAT: &[u8] = b"=qII\0";
+
+/// Null-terminated format string for a view over `serialize_fill_records`'s
+/// 40-byte records: `maker_order_id:i64, taker_order_id:i64, maker_trader_id:u32,
+/// taker_trader_id:u32, price:i64, qty:u32`, plus the 4 padding bytes
+/// `serialize_fill_records` writes after `qty`.
+const FILL_BUFFER_FORMAT: &[u8] = b"=qqIIqI4x\0";
+
+/// An owned, fixed-stride byte buffer exposed through Python's buffer
+/// protocol. Read-only — the bytes were already computed on the Rust side
+/// and handed over once, so there's nothing for Python to write back.
+#[pyclass]
+struct RecordBuffer {
+    data: Vec<u8>,
+    format: &'static [u8],
+    itemsize: usize,
+}
+
+impl RecordBuffer {
+    fn new(data: Vec<u8>, format: &'static [u8], itemsize: usize) -> Self {
+        Self { data, format, itemsize }
+    }
+}
+
+#[pymethods]
+impl RecordBuffer {
+    /// Number of fixed-stride records in this buffer.
+    fn __len__(&self) -> usize {
+        if self.itemsize == 0 { 0 } else { self.data.len() / self.itemsize }
+    }
+
+    unsafe fn __getbuffer__(
+        slf: PyRefMut<'_, Self>,
+        view: *mut pyo3::ffi::Py_buffer,
+        flags: std::os::raw::c_int,
+    ) -> PyResult<()> {
+        let py = slf.py();
+        let len = slf.data.len() as isize;
+        let ptr = slf.data.as_ptr() as *mut std::os::raw::c_void;
+        let result = pyo3::ffi::PyBuffer_FillInfo(view, slf.as_ptr(), ptr, len, 1, flags);
+        if result != 0 {
+            return Err(PyErr::fetch(py));
+        }
+        if !view.is_null() {
+            (*view).format = slf.format.as_ptr() as *mut std::os::raw::c_char;
+            (*view).itemsize = slf.itemsize as isize;
+        }
+        Ok(())
+    }
+
+    unsafe fn __releasebuffer__(&self, _view: *mut pyo3::ffi::Py_buffer) {}
+}
+
+// ---------------------------------------------------------------------------
+// PyPrice — Python-facing wrapper around `Price` (`ScaledPrice<8>`)
+// ---------------------------------------------------------------------------
+//
+// `crate::types::fixed_point::ScaledPrice` is generic over its decimal
+// precision (`ScaledPrice<const DECIMALS: u32>`, with `Price` an alias for
+// `ScaledPrice<8>`), and pyo3's `#[pyclass]` can't bind a generic type.
+// `PyPrice` is the concrete, non-generic stand-in registered with Python as
+// `Price`; every method just forwards to the wrapped `crate::Price`.
+
+/// Python-facing fixed-point price, scaled to 8 decimal places. Forwards to
+/// `crate::Price` (`ScaledPrice<8>`) for every operation.
+#[pyclass(name = "Price")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PyPrice(crate::Price);
+
+#[pymethods]
+impl PyPrice {
+    #[new]
+    fn new(raw: i64) -> Self {
+        Self(crate::Price::new(raw))
+    }
+
+    #[staticmethod]
+    fn from_str_decimal(s: &str) -> Result<Self, String> {
+        crate::Price::from_str_decimal(s).map(Self)
+    }
+
+    #[staticmethod]
+    fn from_float(value: f64) -> Self {
+        Self(crate::Price::from_float(value))
+    }
+
+    fn raw(&self) -> i64 {
+        self.0.raw()
+    }
+
+    fn to_float(&self) -> f64 {
+        self.0.to_float()
+    }
+
+    fn notional(&self, qty: u32) -> i128 {
+        self.0.notional(qty)
+    }
+
+    fn checked_notional(&self, qty: u32) -> Option<i64> {
+        self.0.checked_notional(qty)
+    }
+
+    fn checked_add(&self, other: PyPrice) -> Option<PyPrice> {
+        self.0.checked_add(other.0).map(Self)
+    }
+
+    fn checked_sub(&self, other: PyPrice) -> Option<PyPrice> {
+        self.0.checked_sub(other.0).map(Self)
+    }
+
+    fn saturating_add(&self, other: PyPrice) -> PyPrice {
+        Self(self.0.saturating_add(other.0))
+    }
+
+    fn saturating_sub(&self, other: PyPrice) -> PyPrice {
+        Self(self.0.saturating_sub(other.0))
+    }
+
+    #[staticmethod]
+    fn weighted_avg(old_avg: &PyPrice, old_qty: u32, new_price: &PyPrice, new_qty: u32) -> PyPrice {
+        Self(crate::Price::weighted_avg(&old_avg.0, old_qty, &new_price.0, new_qty))
+    }
+
+    fn midpoint(&self, other: &PyPrice) -> PyPrice {
+        Self(self.0.midpoint(&other.0))
+    }
+
+    #[staticmethod]
+    fn div_round(num: i64, den: i64) -> i64 {
+        crate::Price::div_round(num, den)
+    }
+
+    fn checked_mul(&self, other: &PyPrice) -> Option<PyPrice> {
+        self.0.checked_mul(&other.0).map(Self)
+    }
+
+    fn checked_div(&self, other: &PyPrice) -> Option<PyPrice> {
+        self.0.checked_div(&other.0).map(Self)
+    }
+}
+
 // ---------------------------------------------------------------------------
 // PyNexusExchange — The Python-facing exchange wrapper
 // ---------------------------------------------------------------------------
@@ -120,22 +539,32 @@ fn serialize_l2_levels(levels: &[crate::matching::L2Level]) -> Vec<u8> {
 /// exchange.add_funds(trader_id=1, amount=10000.0)
 /// exchange.add_funds(trader_id=2, amount=10000.0)
 ///
-/// # Submit order as raw bytes: [4:trader_id][1:side][8:price_raw][4:qty]
+/// # Submit a framed order: [header][4:trader_id][1:side][8:price_raw][4:qty]
+/// # See PyNexusExchange.describe_layout(RECORD_KIND_ORDER) for field offsets.
 /// result = exchange.submit_order(order_bytes)
-/// fills_bytes = result  # Raw bytes, 40 bytes per fill
+/// fills_bytes = result  # Framed bytes: [header][40-byte fill records...]
 /// ```
 #[pyclass(name = "PyNexusExchange")]
 pub struct PyNexusExchange {
     inner: NexusExchange,
     perf: PerfTracker,
+    sim: Option<SimState>,
 }
 
 #[pymethods]
 impl PyNexusExchange {
     /// Create a new exchange without WAL persistence.
+    ///
+    /// Args:
+    ///     wal_path (str, optional): Enable WAL persistence at this path.
+    ///     simulated (bool): If `True`, run against a virtual clock instead
+    ///         of wall time — see `set_now`/`advance_clock`/
+    ///         `set_latency_model`/`set_slippage_bps` — so a strategy can
+    ///         drive deterministic, bit-for-bit-reproducible backtests
+    ///         through the same engine binary used live.
     #[new]
-    #[pyo3(signature = (wal_path=None))]
-    fn new(wal_path: Option<String>) -> PyResult<Self> {
+    #[pyo3(signature = (wal_path=None, simulated=false))]
+    fn new(wal_path: Option<String>, simulated: bool) -> PyResult<Self> {
         let inner = match wal_path {
             Some(path) => NexusExchange::with_persistence(&path)
                 .map_err(|e| PyRuntimeError::new_err(format!("WAL init failed: {}", e)))?,
@@ -144,6 +573,7 @@ impl PyNexusExchange {
         Ok(Self {
             inner,
             perf: PerfTracker::new(),
+            sim: if simulated { Some(SimState::new()) } else { None },
         })
     }
 
@@ -156,13 +586,60 @@ impl PyNexusExchange {
     /// Args:
     ///     trader_id (int): The trader's unique ID.
     ///     amount (float): Dollar amount to add (e.g., 10000.0).
-    fn add_funds(&mut self, trader_id: u32, amount: f64) {
-        self.inner.add_funds_float(trader_id, amount);
+    fn add_funds(&mut self, trader_id: u32, amount: f64) -> PyResult<()> {
+        self.inner.add_funds_float(trader_id, amount)
+            .map_err(|e| PyRuntimeError::new_err(format!("{:?}", e)))
     }
 
     /// Add funds using raw fixed-point amount.
-    fn add_funds_raw(&mut self, trader_id: u32, amount_raw: i64) {
-        self.inner.add_funds(trader_id, amount_raw);
+    fn add_funds_raw(&mut self, trader_id: u32, amount_raw: i64) -> PyResult<()> {
+        self.inner.add_funds(trader_id, amount_raw)
+            .map_err(|e| PyRuntimeError::new_err(format!("{:?}", e)))
+    }
+
+    /// Set the maker/taker fee schedule applied to fills from now on.
+    ///
+    /// Args:
+    ///     taker_fee_bps (int): Rate charged to the aggressor, in basis points.
+    ///     maker_fee_bps (int): Rate charged to the resting side; negative is a rebate.
+    fn set_fee_schedule(&mut self, taker_fee_bps: i64, maker_fee_bps: i64) -> PyResult<()> {
+        self.inner.set_fee_schedule(FeeSchedule { taker_fee_bps, maker_fee_bps })
+            .map_err(|e| PyRuntimeError::new_err(format!("{:?}", e)))
+    }
+
+    /// Total maker + taker fees collected across every fill so far.
+    fn collected_fees(&self) -> i64 {
+        self.inner.collected_fees()
+    }
+
+    // -------------------------------------------------------------------
+    // AMM LIQUIDITY POOL
+    // -------------------------------------------------------------------
+
+    /// Deposit liquidity into the AMM pool, creating it on the first call.
+    ///
+    /// Args:
+    ///     base (int): Base-asset quantity to deposit, in raw order-qty units.
+    ///     quote (int): Quote-asset amount to deposit, in raw fixed-point units.
+    fn add_liquidity(&mut self, base: i64, quote: i64) -> PyResult<()> {
+        self.inner.add_liquidity(base, quote)
+            .map_err(|e| PyRuntimeError::new_err(format!("{:?}", e)))
+    }
+
+    /// Withdraw liquidity from the AMM pool.
+    ///
+    /// Raises:
+    ///     RuntimeError: If no pool is configured, or the withdrawal would
+    ///         exceed its current reserves.
+    fn remove_liquidity(&mut self, base: i64, quote: i64) -> PyResult<()> {
+        self.inner.remove_liquidity(base, quote)
+            .map_err(|e| PyRuntimeError::new_err(format!("{:?}", e)))
+    }
+
+    /// Current AMM pool reserves as `(base_reserve, quote_reserve)`, or
+    /// `None` if no pool is configured.
+    fn amm_pool(&self) -> Option<(i64, i64)> {
+        self.inner.amm_pool().map(|p| (p.base_reserve, p.quote_reserve))
     }
 
     // -------------------------------------------------------------------
@@ -172,23 +649,34 @@ impl PyNexusExchange {
     /// Submit an order using raw bytes. Zero-copy hot path.
     ///
     /// Args:
-    ///     order_bytes (bytes): 17-byte packed order:
+    ///     order_bytes (bytes): a framed, 21 or 22-byte packed order:
+    ///         [2: magic "NX"][1: format_version][1: record_kind=RECORD_KIND_ORDER]
     ///         [4: trader_id (u32 LE)]
     ///         [1: side (1=Buy, 2=Sell)]
-    ///         [8: price (i64 LE, fixed-point)]  
+    ///         [8: price (i64 LE, fixed-point)]
     ///         [4: qty (u32 LE)]
+    ///         [1: order_type (optional; 0=Limit GTC, 1=Market, 2=IOC,
+    ///             3=FOK, 4=Post-Only — defaults to Limit GTC if omitted)]
+    ///         Use `describe_layout(RECORD_KIND_ORDER)` for the authoritative
+    ///         field offsets instead of hand-maintaining these.
     ///
     /// Returns:
-    ///     bytes: Packed fill records, 40 bytes each.
-    ///            Empty bytes if no fills (resting order).
+    ///     bytes: framed `[header][40-byte fill records...]`, via
+    ///         `describe_layout(RECORD_KIND_FILL)`. Header-only (no fill
+    ///         records) if no fills (resting order).
     ///
     /// Raises:
-    ///     ValueError: If order bytes are malformed.
-    ///     RuntimeError: If rejected by risk gate (insufficient margin, fat-finger, etc).
+    ///     ValueError: If order bytes are malformed, or the header's magic/
+    ///         version/record_kind doesn't match this build.
+    ///     RuntimeError: If rejected by risk gate (insufficient margin,
+    ///         fat-finger, etc), or by the engine (e.g. an unfillable FOK,
+    ///         or a Post-Only that would cross the spread).
     fn submit_order<'py>(&mut self, py: Python<'py>, order_bytes: &[u8]) -> PyResult<Bound<'py, PyBytes>> {
+        let header_len = validate_header(order_bytes, RECORD_KIND_ORDER)?;
+        let order_bytes = &order_bytes[header_len..];
         if order_bytes.len() < 17 {
             return Err(PyValueError::new_err(
-                format!("Order bytes must be >= 17 bytes, got {}", order_bytes.len())
+                format!("Order payload must be >= 17 bytes, got {}", order_bytes.len())
             ));
         }
 
@@ -200,14 +688,31 @@ impl PyNexusExchange {
         };
         let price = i64::from_le_bytes(order_bytes[5..13].try_into().unwrap());
         let qty = u32::from_le_bytes(order_bytes[13..17].try_into().unwrap());
+        let order_type = order_type_from_wire(order_bytes.get(17).copied())?;
+
+        // Simulated with a latency model configured: defer the order's
+        // effect instead of matching it now. Fills arrive later, from
+        // `advance_clock`/`set_now` once the sampled latency has elapsed.
+        if let Some(sim) = &mut self.sim {
+            if sim.latency_model.is_some() {
+                sim.queue(trader_id, side, order_type, price, qty);
+                return Ok(PyBytes::new(py, &serialize_fills(&[])));
+            }
+        }
+
+        let price = match &self.sim {
+            Some(sim) => sim.apply_slippage(side, order_type, price),
+            None => price,
+        };
 
-        // Time the hot path.
+        // Time the hot path (wall clock; unused for latency stats in
+        // simulated mode, where elapsed wall time isn't reproducible).
         let start = Instant::now();
 
-        let result = self.inner.submit_order(trader_id, side, price, qty)
+        let result = self.inner.submit_order(trader_id, side, order_type, price, qty)
             .map_err(|e| PyRuntimeError::new_err(format!("{:?}", e)))?;
 
-        let elapsed_ns = start.elapsed().as_nanos() as u64;
+        let elapsed_ns = if self.sim.is_some() { 0 } else { start.elapsed().as_nanos() as u64 };
 
         // Track performance.
         let volume: u64 = result.match_result.fills.iter().map(|f| f.qty as u64).sum();
@@ -218,6 +723,192 @@ impl PyNexusExchange {
         Ok(PyBytes::new(py, &fill_bytes))
     }
 
+    // -------------------------------------------------------------------
+    // SIMULATION MODE — virtual clock + injected latency/slippage
+    // -------------------------------------------------------------------
+
+    /// Set the virtual clock to an absolute nanosecond value and release
+    /// any latency-delayed orders whose sampled arrival time has now
+    /// passed, in release order. Simulated mode only.
+    ///
+    /// Returns:
+    ///     bytes: same `[4: order_count][order_count * (8:order_id,
+    ///         4:fill_count, 1:status)][fills...]` layout as
+    ///         `submit_orders_batch`, one entry per order released by this
+    ///         call (may be empty if nothing was due).
+    ///
+    /// Raises:
+    ///     RuntimeError: If the exchange wasn't constructed with `simulated=True`.
+    fn set_now<'py>(&mut self, py: Python<'py>, ns: u64) -> PyResult<Bound<'py, PyBytes>> {
+        let sim = self.sim.as_mut()
+            .ok_or_else(|| PyRuntimeError::new_err("set_now requires simulated=True"))?;
+        sim.now_ns = ns;
+        self.release_due(py)
+    }
+
+    /// Advance the virtual clock by `ns` nanoseconds and release any
+    /// latency-delayed orders whose sampled arrival time has now passed, in
+    /// release order. Simulated mode only. See `set_now` for the return layout.
+    ///
+    /// Raises:
+    ///     RuntimeError: If the exchange wasn't constructed with `simulated=True`.
+    fn advance_clock<'py>(&mut self, py: Python<'py>, ns: u64) -> PyResult<Bound<'py, PyBytes>> {
+        let sim = self.sim.as_mut()
+            .ok_or_else(|| PyRuntimeError::new_err("advance_clock requires simulated=True"))?;
+        sim.now_ns = sim.now_ns.saturating_add(ns);
+        self.release_due(py)
+    }
+
+    /// Configure the injected latency model: from now on, each order
+    /// submitted via `submit_order` is held until `virtual_now + base_ns +
+    /// uniform(0, jitter_ns)` instead of matching immediately, and its
+    /// fills are returned from whichever `advance_clock`/`set_now` call
+    /// first moves the virtual clock past that time. The jitter draw comes
+    /// from a fixed-seed PRNG, so replays stay bit-for-bit deterministic
+    /// regardless of host speed. Pass `(0, 0)` to disable (the default) and
+    /// go back to immediate execution.
+    ///
+    /// `submit_orders_batch` and `submit_order_array` are unaffected by this
+    /// model — they always execute immediately, simulated or not.
+    ///
+    /// Raises:
+    ///     RuntimeError: If the exchange wasn't constructed with `simulated=True`.
+    fn set_latency_model(&mut self, base_ns: u64, jitter_ns: u64) -> PyResult<()> {
+        let sim = self.sim.as_mut()
+            .ok_or_else(|| PyRuntimeError::new_err("set_latency_model requires simulated=True"))?;
+        sim.latency_model = if base_ns == 0 && jitter_ns == 0 { None } else { Some((base_ns, jitter_ns)) };
+        Ok(())
+    }
+
+    /// Configure the slippage model: the price of a Limit, IOC, or FOK
+    /// order is worsened by `bps` basis points before it reaches the
+    /// matching engine — buys pay more, sells receive less — emulating the
+    /// price impact a live venue would add. Market orders are not adjusted
+    /// (the engine ignores their price), and neither are Post-Only orders.
+    /// Pass `0` to disable (the default).
+    ///
+    /// Raises:
+    ///     RuntimeError: If the exchange wasn't constructed with `simulated=True`.
+    fn set_slippage_bps(&mut self, bps: i64) -> PyResult<()> {
+        let sim = self.sim.as_mut()
+            .ok_or_else(|| PyRuntimeError::new_err("set_slippage_bps requires simulated=True"))?;
+        sim.slippage_bps = bps;
+        Ok(())
+    }
+
+    /// Submit many orders in one call, amortizing the PyO3 boundary
+    /// crossing `submit_order` pays once per order — for a Python strategy
+    /// firing thousands of orders, that crossing dominates cost.
+    ///
+    /// Args:
+    ///     orders_bytes (bytes): concatenated 17-byte packed orders, same
+    ///         fixed fields as `submit_order`'s payload but with no framing
+    ///         header per order (or around the batch) — this predates the
+    ///         versioned protocol and stays a raw, fixed-stride contract for
+    ///         throughput; every order in a batch is submitted as Limit GTC,
+    ///         use `submit_order` directly for the other order types.
+    ///
+    /// Returns:
+    ///     bytes: `[4: order_count (u32 LE)]` followed by `order_count`
+    ///         index entries of `[8: order_id][4: fill_count][1: status]`
+    ///         (status: 0=filled, 1=resting, 2=rejected by the risk gate —
+    ///         a rejected entry's order_id and fill_count are both 0), then
+    ///         the concatenated 40-byte fill records for every order, in
+    ///         the same order the orders were submitted in. A rejection
+    ///         only skips that one order; the rest of the batch still runs.
+    ///         No framing header, for the same reason as the input.
+    ///
+    /// Raises:
+    ///     ValueError: If `orders_bytes` isn't a whole number of 17-byte records.
+    fn submit_orders_batch<'py>(&mut self, py: Python<'py>, orders_bytes: &[u8]) -> PyResult<Bound<'py, PyBytes>> {
+        if orders_bytes.len() % 17 != 0 {
+            return Err(PyValueError::new_err(
+                format!("orders_bytes must be a multiple of 17 bytes, got {}", orders_bytes.len())
+            ));
+        }
+        let order_count = orders_bytes.len() / 17;
+
+        let mut index = Vec::with_capacity(4 + order_count * 13);
+        index.extend_from_slice(&(order_count as u32).to_le_bytes());
+        let mut fills_buf = Vec::new();
+
+        for chunk in orders_bytes.chunks_exact(17) {
+            let trader_id = u32::from_le_bytes(chunk[0..4].try_into().unwrap());
+            let side = match chunk[4] {
+                1 => Side::Buy,
+                2 => Side::Sell,
+                v => return Err(PyValueError::new_err(format!("Invalid side byte: {}. Must be 1 (Buy) or 2 (Sell)", v))),
+            };
+            let price = i64::from_le_bytes(chunk[5..13].try_into().unwrap());
+            let qty = u32::from_le_bytes(chunk[13..17].try_into().unwrap());
+
+            let start = Instant::now();
+            match self.inner.submit_order(trader_id, side, OrderType::Limit, price, qty) {
+                Ok(result) => {
+                    let elapsed_ns = start.elapsed().as_nanos() as u64;
+                    let volume: u64 = result.match_result.fills.iter().map(|f| f.qty as u64).sum();
+                    self.perf.record_order(result.match_result.fills.len(), volume, elapsed_ns);
+
+                    let status: u8 = if result.match_result.resting_qty > 0 { 1 } else { 0 };
+                    index.extend_from_slice(&result.match_result.order_id.to_le_bytes());
+                    index.extend_from_slice(&(result.match_result.fills.len() as u32).to_le_bytes());
+                    index.push(status);
+                    fills_buf.extend_from_slice(&serialize_fill_records(&result.match_result.fills));
+                }
+                Err(_) => {
+                    index.extend_from_slice(&0u64.to_le_bytes());
+                    index.extend_from_slice(&0u32.to_le_bytes());
+                    index.push(2);
+                }
+            }
+        }
+
+        index.extend_from_slice(&fills_buf);
+        Ok(PyBytes::new(py, &index))
+    }
+
+    /// Like `submit_order`, but returns fills as a `RecordBuffer` instead of
+    /// raw `PyBytes` — `numpy.frombuffer(buf, dtype=...)` views the fill
+    /// records with no copy. See `get_l2_array` for the matching book-side
+    /// view and `RecordBuffer` for the buffer-protocol machinery.
+    ///
+    /// Args / Raises: same as `submit_order` for the input (a framed order).
+    /// The returned `RecordBuffer` is itself self-describing via the buffer
+    /// protocol, so unlike `submit_order`'s return it carries no magic
+    /// header — `numpy.frombuffer` needs the raw bytes to be an exact
+    /// multiple of `FILL_RECORD_SIZE`.
+    fn submit_order_array(&mut self, py: Python<'_>, order_bytes: &[u8]) -> PyResult<Py<RecordBuffer>> {
+        let header_len = validate_header(order_bytes, RECORD_KIND_ORDER)?;
+        let order_bytes = &order_bytes[header_len..];
+        if order_bytes.len() < 17 {
+            return Err(PyValueError::new_err(
+                format!("Order payload must be >= 17 bytes, got {}", order_bytes.len())
+            ));
+        }
+
+        let trader_id = u32::from_le_bytes(order_bytes[0..4].try_into().unwrap());
+        let side = match order_bytes[4] {
+            1 => Side::Buy,
+            2 => Side::Sell,
+            v => return Err(PyValueError::new_err(format!("Invalid side byte: {}. Must be 1 (Buy) or 2 (Sell)", v))),
+        };
+        let price = i64::from_le_bytes(order_bytes[5..13].try_into().unwrap());
+        let qty = u32::from_le_bytes(order_bytes[13..17].try_into().unwrap());
+        let order_type = order_type_from_wire(order_bytes.get(17).copied())?;
+
+        let start = Instant::now();
+
+        let result = self.inner.submit_order(trader_id, side, order_type, price, qty)
+            .map_err(|e| PyRuntimeError::new_err(format!("{:?}", e)))?;
+
+        let elapsed_ns = start.elapsed().as_nanos() as u64;
+        let volume: u64 = result.match_result.fills.iter().map(|f| f.qty as u64).sum();
+        self.perf.record_order(result.match_result.fills.len(), volume, elapsed_ns);
+
+        let fill_bytes = serialize_fill_records(&result.match_result.fills);
+        Py::new(py, RecordBuffer::new(fill_bytes, FILL_BUFFER_FORMAT, FILL_RECORD_SIZE))
+    }
+
     /// Submit an order using human-readable parameters.
     ///
     /// Convenience method for interactive use. NOT the hot path.
@@ -229,7 +920,8 @@ impl PyNexusExchange {
     ///     qty (int): Order quantity.
     ///
     /// Returns:
-    ///     dict: { "order_id": int, "fills": list[dict], "resting_qty": int }
+    ///     dict: { "order_id": int, "fills": list[dict] (each with "taker_fee"/"maker_fee"),
+    ///             "resting_qty": int, "cancelled_qty": int }
     fn submit_order_human<'py>(&mut self, py: Python<'py>, trader_id: u32, side: &str, price: f64, qty: u32) -> PyResult<Bound<'py, PyDict>> {
         let side = Side::from_str(side)
             .map_err(|e| PyValueError::new_err(e))?;
@@ -237,7 +929,7 @@ impl PyNexusExchange {
 
         let start = Instant::now();
 
-        let result = self.inner.submit_order(trader_id, side, price_raw, qty)
+        let result = self.inner.submit_order(trader_id, side, OrderType::Limit, price_raw, qty)
             .map_err(|e| PyRuntimeError::new_err(format!("{:?}", e)))?;
 
         let elapsed_ns = start.elapsed().as_nanos() as u64;
@@ -248,6 +940,7 @@ impl PyNexusExchange {
         let dict = PyDict::new(py);
         dict.set_item("order_id", result.match_result.order_id)?;
         dict.set_item("resting_qty", result.match_result.resting_qty)?;
+        dict.set_item("cancelled_qty", result.match_result.cancelled_qty)?;
         dict.set_item("latency_ns", elapsed_ns)?;
 
         let fills_list = PyList::empty(py);
@@ -260,6 +953,8 @@ impl PyNexusExchange {
             fill_dict.set_item("price", fill.price as f64 / SCALE as f64)?;
             fill_dict.set_item("price_raw", fill.price)?;
             fill_dict.set_item("qty", fill.qty)?;
+            fill_dict.set_item("taker_fee", fill.taker_fee)?;
+            fill_dict.set_item("maker_fee", fill.maker_fee)?;
             fills_list.append(fill_dict)?;
         }
         dict.set_item("fills", fills_list)?;
@@ -278,7 +973,8 @@ impl PyNexusExchange {
     ///
     /// Returns:
     ///     dict: { "bids": bytes, "asks": bytes, "bid_count": int, "ask_count": int }
-    ///     Each side is packed as 16-byte records: [8:price][4:qty][4:orders].
+    ///     `bids`/`asks` are each a framed `[header][16-byte records...]`
+    ///     buffer (one header per side) — see `describe_layout(RECORD_KIND_L2)`.
     fn get_l2_snapshot<'py>(&self, py: Python<'py>, depth: usize) -> PyResult<Bound<'py, PyDict>> {
         let (bids, asks) = self.inner.l2_snapshot(depth);
 
@@ -298,6 +994,27 @@ impl PyNexusExchange {
         Ok(dict)
     }
 
+    /// Like `get_l2_snapshot`, but returns each side as a `RecordBuffer`
+    /// instead of raw `PyBytes` — `numpy.frombuffer(buf, dtype=np.dtype(
+    /// [("price", "i8"), ("qty", "u4"), ("orders", "u4")]))` views it with
+    /// no copy. See `submit_order_array` for the matching fills view.
+    ///
+    /// Args:
+    ///     depth (int): Number of price levels per side.
+    ///
+    /// Returns:
+    ///     dict: { "bids": RecordBuffer, "asks": RecordBuffer }
+    fn get_l2_array<'py>(&self, py: Python<'py>, depth: usize) -> PyResult<Bound<'py, PyDict>> {
+        let (bids, asks) = self.inner.l2_snapshot(depth);
+        let bids_buf = RecordBuffer::new(serialize_l2_level_records(&bids), L2_BUFFER_FORMAT, L2_LEVEL_SIZE);
+        let asks_buf = RecordBuffer::new(serialize_l2_level_records(&asks), L2_BUFFER_FORMAT, L2_LEVEL_SIZE);
+
+        let dict = PyDict::new(py);
+        dict.set_item("bids", Py::new(py, bids_buf)?)?;
+        dict.set_item("asks", Py::new(py, asks_buf)?)?;
+        Ok(dict)
+    }
+
     /// Get a human-readable L2 snapshot (for dashboards).
     fn get_l2_human<'py>(&self, py: Python<'py>, depth: usize) -> PyResult<Bound<'py, PyDict>> {
         let (bids, asks) = self.inner.l2_snapshot(depth);
@@ -371,9 +1088,12 @@ impl PyNexusExchange {
     /// Returns:
     ///     dict: {
     ///         "total_orders": int,
-    ///         "total_fills": int, 
+    ///         "total_fills": int,
     ///         "total_volume": int,
-    ///         "avg_match_latency_ns": int,
+    ///         "p50_latency_ns": int,
+    ///         "p99_latency_ns": int,
+    ///         "p999_latency_ns": int,
+    ///         "max_latency_ns": int,
     ///         "last_match_latency_ns": int,
     ///         "wal_entries": int,
     ///         "wal_bytes_used": int,
@@ -387,7 +1107,10 @@ impl PyNexusExchange {
         dict.set_item("total_orders", self.perf.total_orders)?;
         dict.set_item("total_fills", self.perf.total_fills)?;
         dict.set_item("total_volume", self.perf.total_volume)?;
-        dict.set_item("avg_match_latency_ns", self.perf.avg_latency_ns())?;
+        dict.set_item("p50_latency_ns", self.perf.percentile_latency_ns(0.50))?;
+        dict.set_item("p99_latency_ns", self.perf.percentile_latency_ns(0.99))?;
+        dict.set_item("p999_latency_ns", self.perf.percentile_latency_ns(0.999))?;
+        dict.set_item("max_latency_ns", self.perf.max_latency_ns)?;
         dict.set_item("last_match_latency_ns", self.perf.last_match_latency_ns)?;
 
         // WAL metrics.
@@ -464,6 +1187,110 @@ impl PyNexusExchange {
     fn raw_to_price(raw: i64) -> f64 {
         raw as f64 / SCALE as f64
     }
+
+    /// Describe a framed record's field layout — name, byte offset (after
+    /// the 4-byte header), width, and endianness — so Python builds its
+    /// `struct`/`numpy` dtype from this authoritative definition instead of
+    /// hand-maintained stride constants that silently drift from Rust.
+    ///
+    /// Args:
+    ///     record_kind (int): `RECORD_KIND_ORDER`, `RECORD_KIND_FILL`, or
+    ///         `RECORD_KIND_L2` (exported module constants).
+    ///
+    /// Returns:
+    ///     dict: { "record_kind": int, "size": int,
+    ///             "fields": list[dict] (each with "name"/"offset"/"width"/"endian") }
+    ///
+    /// Raises:
+    ///     ValueError: If `record_kind` isn't one of the known constants.
+    #[staticmethod]
+    fn describe_layout<'py>(py: Python<'py>, record_kind: u8) -> PyResult<Bound<'py, PyDict>> {
+        let fields: &[(&str, usize, usize)] = match record_kind {
+            RECORD_KIND_ORDER => &[
+                ("trader_id", 0, 4),
+                ("side", 4, 1),
+                ("price", 5, 8),
+                ("qty", 13, 4),
+                ("order_type", 17, 1), // Optional trailing byte; see order_type_from_wire.
+            ],
+            RECORD_KIND_FILL => &[
+                ("maker_order_id", 0, 8),
+                ("taker_order_id", 8, 8),
+                ("maker_trader_id", 16, 4),
+                ("taker_trader_id", 20, 4),
+                ("price", 24, 8),
+                ("qty", 32, 4),
+                ("_padding", 36, 4),
+            ],
+            RECORD_KIND_L2 => &[
+                ("price", 0, 8),
+                ("qty", 8, 4),
+                ("order_count", 12, 4),
+            ],
+            v => return Err(PyValueError::new_err(format!(
+                "Unknown record_kind: {}. Must be {} (ORDER), {} (FILL), or {} (L2)",
+                v, RECORD_KIND_ORDER, RECORD_KIND_FILL, RECORD_KIND_L2
+            ))),
+        };
+
+        let size = fields.iter().map(|(_, offset, width)| offset + width).max().unwrap_or(0);
+
+        let field_list = PyList::empty(py);
+        for (name, offset, width) in fields {
+            let field = PyDict::new(py);
+            field.set_item("name", *name)?;
+            field.set_item("offset", *offset)?;
+            field.set_item("width", *width)?;
+            field.set_item("endian", "little")?;
+            field_list.append(field)?;
+        }
+
+        let dict = PyDict::new(py);
+        dict.set_item("record_kind", record_kind)?;
+        dict.set_item("size", size)?;
+        dict.set_item("fields", field_list)?;
+        Ok(dict)
+    }
+}
+
+impl PyNexusExchange {
+    /// Submit every pending order whose release time is now due (per
+    /// `SimState::drain_due`), in release order, through the ordinary
+    /// pipeline — applying the slippage model the same way `submit_order`
+    /// would. Builds the same index-then-fills buffer `submit_orders_batch`
+    /// returns, so callers already parsing that layout can reuse it here.
+    fn release_due<'py>(&mut self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        let sim = self.sim.as_mut().expect("release_due only called in simulated mode");
+        let due = sim.drain_due();
+
+        let mut index = Vec::with_capacity(4 + due.len() * 13);
+        index.extend_from_slice(&(due.len() as u32).to_le_bytes());
+        let mut fills_buf = Vec::new();
+
+        for order in due {
+            let price = self.sim.as_ref().unwrap().apply_slippage(order.side, order.order_type, order.price);
+            match self.inner.submit_order(order.trader_id, order.side, order.order_type, price, order.qty) {
+                Ok(result) => {
+                    let volume: u64 = result.match_result.fills.iter().map(|f| f.qty as u64).sum();
+                    self.perf.record_order(result.match_result.fills.len(), volume, 0);
+
+                    let status: u8 = if result.match_result.resting_qty > 0 { 1 } else { 0 };
+                    index.extend_from_slice(&result.match_result.order_id.to_le_bytes());
+                    index.extend_from_slice(&(result.match_result.fills.len() as u32).to_le_bytes());
+                    index.push(status);
+                    fills_buf.extend_from_slice(&serialize_fill_records(&result.match_result.fills));
+                }
+                Err(_) => {
+                    index.extend_from_slice(&0u64.to_le_bytes());
+                    index.extend_from_slice(&0u32.to_le_bytes());
+                    index.push(2);
+                }
+            }
+        }
+
+        index.extend_from_slice(&fills_buf);
+        Ok(PyBytes::new(py, &index))
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -473,11 +1300,16 @@ impl PyNexusExchange {
 /// Register all Python-exposed types and the PyNexusExchange class.
 pub fn register_module(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<PyNexusExchange>()?;
-    m.add_class::<crate::Price>()?;
+    m.add_class::<RecordBuffer>()?;
+    m.add_class::<PyPrice>()?;
     m.add_class::<crate::Quantity>()?;
     m.add_class::<crate::Side>()?;
     m.add("SCALE", SCALE)?;
     m.add("FILL_RECORD_SIZE", FILL_RECORD_SIZE)?;
     m.add("L2_LEVEL_SIZE", L2_LEVEL_SIZE)?;
+    m.add("PROTOCOL_VERSION", PROTOCOL_VERSION)?;
+    m.add("RECORD_KIND_ORDER", RECORD_KIND_ORDER)?;
+    m.add("RECORD_KIND_FILL", RECORD_KIND_FILL)?;
+    m.add("RECORD_KIND_L2", RECORD_KIND_L2)?;
     Ok(())
 }
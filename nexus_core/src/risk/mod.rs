@@ -52,6 +52,36 @@ const SCALE: i64 = crate::SCALE;
 // Account & Position Types
 // ---------------------------------------------------------------------------
 
+/// How a trader's margin is pooled across symbols.
+///
+/// `Cross` (the default) is today's behavior: one shared
+/// `available_balance`/`locked_margin` pair covers every symbol, and a
+/// loss on one symbol eats into the margin backing another. `Isolated`
+/// walls off a dedicated collateral pool per symbol (see
+/// `Account::isolated_allocations`) so a blow-up on one symbol can't
+/// drain another's margin — at the cost of that collateral being
+/// unavailable to cover other symbols.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MarginMode {
+    #[default]
+    Cross,
+    Isolated,
+}
+
+/// Which collateral pool a margin operation reads from and writes to:
+/// the account-wide balance under `MarginMode::Cross`, or a single
+/// symbol's `isolated_allocations` entry under `MarginMode::Isolated`.
+///
+/// Resolved once per call by `Guardian::margin_context` from the
+/// trader's current `MarginMode`, then consulted by `validate_and_lock`,
+/// `settle_fill_v2`, `unlock_margin`, and `check_liquidation` so every
+/// margin touch-point agrees on which pool applies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MarginContext {
+    pub mode: MarginMode,
+    pub symbol_id: u32,
+}
+
 /// A trader's account. All values are in fixed-point (i64 × 10^8).
 #[derive(Debug, Clone)]
 pub struct Account {
@@ -62,8 +92,39 @@ pub struct Account {
     /// Net position per symbol. Positive = long, negative = short.
     /// Key is a symbol ID (u32) for cache efficiency.
     pub positions: BTreeMap<u32, i64>,
+    /// Weighted-average entry price per symbol (fixed-point, same scale as
+    /// `positions` quantities are priced in). Only meaningful while
+    /// `positions[symbol_id] != 0`; a fill that grows a position in the
+    /// same direction folds into this average, a fill that only reduces it
+    /// leaves the average untouched.
+    pub entry_prices: BTreeMap<u32, i64>,
     /// Realized PnL (accumulated from closed positions).
     pub realized_pnl: i64,
+    /// Aggregate notional (limit price × qty) of this trader's resting BUY
+    /// orders, per symbol. Used alongside `open_sell_notional` to net the
+    /// two directions when computing margin — see `Guardian::net_margin_requirement`.
+    pub open_buy_notional: BTreeMap<u32, i64>,
+    /// Aggregate notional of this trader's resting SELL orders, per symbol.
+    pub open_sell_notional: BTreeMap<u32, i64>,
+    /// Whether this account pools margin across all symbols (`Cross`) or
+    /// walls it off per symbol (`Isolated`). See `MarginMode`.
+    pub margin_mode: MarginMode,
+    /// Collateral walled off to a symbol under `MarginMode::Isolated`,
+    /// moved out of `available_balance` via `Guardian::allocate_isolated`.
+    /// Unused while `margin_mode` is `Cross`.
+    pub isolated_allocations: BTreeMap<u32, i64>,
+    /// Highest `total_equity()` this account has ever reached. Tracked by
+    /// `Guardian::evaluate_risk_triggers` to enforce `max_drawdown_pct` —
+    /// equity is compared against this peak, not the starting balance, so
+    /// a trader who has booked gains can give some of them back before the
+    /// drawdown trigger fires.
+    pub high_water_mark: i64,
+    /// Cumulative maker/taker fees charged against this account by
+    /// `settle_fill_v2` — see `GuardianConfig::maker_fee_bps`/`taker_fee_bps`.
+    pub fees_paid: i64,
+    /// Cumulative funding transferred by `Guardian::apply_funding`. Positive
+    /// = net paid out over this account's lifetime, negative = net received.
+    pub cumulative_funding: i64,
 }
 
 impl Account {
@@ -73,7 +134,15 @@ impl Account {
             available_balance: starting_balance,
             locked_margin: 0,
             positions: BTreeMap::new(),
+            entry_prices: BTreeMap::new(),
             realized_pnl: 0,
+            open_buy_notional: BTreeMap::new(),
+            open_sell_notional: BTreeMap::new(),
+            margin_mode: MarginMode::Cross,
+            high_water_mark: starting_balance,
+            isolated_allocations: BTreeMap::new(),
+            fees_paid: 0,
+            cumulative_funding: 0,
         }
     }
 
@@ -86,6 +155,12 @@ impl Account {
     pub fn position(&self, symbol_id: u32) -> i64 {
         *self.positions.get(&symbol_id).unwrap_or(&0)
     }
+
+    /// Get the weighted-average entry price for a symbol, if a position is
+    /// currently open.
+    pub fn entry_price(&self, symbol_id: u32) -> Option<i64> {
+        self.entry_prices.get(&symbol_id).copied()
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -119,6 +194,55 @@ pub struct GuardianConfig {
     pub max_order_qty: u32,
     /// Whether to allow short selling (positions going negative).
     pub allow_short_selling: bool,
+    /// Default leverage multiplier applied when computing initial margin: a
+    /// buy or sell locks `notional / leverage` rather than the full
+    /// notional. `1` (the default) reproduces today's fully cash-covered
+    /// behavior. A symbol with its own leverage set via `set_leverage`
+    /// uses that instead — see `Guardian::leverage_scaled`.
+    pub leverage: u32,
+    /// Maintenance margin requirement, as a fixed-point fraction of
+    /// notional (e.g. `0.05 × SCALE` = 5%). Used by `check_liquidation` and
+    /// `liquidation_price` to decide when a position is underwater.
+    pub maintenance_margin_pct: i64,
+    /// Automatic Kill Switch trigger: maximum fraction an account's equity
+    /// may drop from its high-water mark (e.g. `0.20 × SCALE` = 20%) before
+    /// `evaluate_risk_triggers` bans the trader. `None` disables the check.
+    pub max_drawdown_pct: Option<i64>,
+    /// Automatic Kill Switch trigger: an absolute floor on `realized_pnl`
+    /// (fixed-point). A trader whose `realized_pnl` falls to or below
+    /// `-max_realized_loss` is auto-banned by `evaluate_risk_triggers`.
+    /// `None` disables the check.
+    pub max_realized_loss: Option<i64>,
+    /// Automatic Kill Switch trigger: the maximum notional (at cost basis)
+    /// a trader may hold on any single symbol before `evaluate_risk_triggers`
+    /// bans them. `None` disables the check.
+    pub max_position_notional: Option<i64>,
+    /// Circuit breaker: maximum fractional move of the reference price
+    /// between consecutive fills (e.g. `0.15 × SCALE` = 15%) before
+    /// `check_price_circuit_breaker` halts the whole market. `None`
+    /// disables the check.
+    pub circuit_breaker_price_move_pct: Option<i64>,
+    /// Circuit breaker: the maximum aggregate net exposure (sum of every
+    /// trader's signed position notional on a symbol) before
+    /// `check_exposure_circuit_breaker` halts the whole market. `None`
+    /// disables the check.
+    pub circuit_breaker_max_net_exposure: Option<i64>,
+    /// Cap on leverage a single `Guardian::set_leverage` call may
+    /// configure for a symbol, as a fixed-point factor (e.g. `20 × SCALE`
+    /// = 20x). `None` leaves leverage unbounded.
+    pub max_leverage: Option<i64>,
+    /// Fee charged on a fill that rests passively on the book (the
+    /// opposite side crossed to meet it), in basis points of fill notional
+    /// (same units as `matching::FeeSchedule::maker_fee_bps` — this is a
+    /// separate rate the Guardian charges against margin/balance, not the
+    /// matching engine's own post-trade fee collection). `0` (the default)
+    /// charges no maker fee; negative is a rebate.
+    pub maker_fee_bps: i64,
+    /// Fee charged on a fill that crosses the book to take liquidity, same
+    /// units as `maker_fee_bps`. `settle_fill_v2` picks maker vs. taker by
+    /// comparing `fill_price` against the order's own limit price — see its
+    /// doc comment. `0` (the default) charges no taker fee.
+    pub taker_fee_bps: i64,
 }
 
 impl Default for GuardianConfig {
@@ -127,6 +251,16 @@ impl Default for GuardianConfig {
             volatility_band: VolatilityBandConfig::default(),
             max_order_qty: 1_000_000,
             allow_short_selling: true,
+            leverage: 1,
+            maintenance_margin_pct: 5_000_000, // 5% (0.05 × 10^8)
+            max_drawdown_pct: None,
+            max_realized_loss: None,
+            max_position_notional: None,
+            circuit_breaker_price_move_pct: None,
+            circuit_breaker_max_net_exposure: None,
+            max_leverage: None,
+            maker_fee_bps: 0,
+            taker_fee_bps: 0,
         }
     }
 }
@@ -171,6 +305,114 @@ pub enum GuardianReject {
         required: i64,
         current: i64,
     },
+    /// `price × qty`, computed in `i128`, doesn't fit back in `i64`. Caught
+    /// before the overflow can wrap and corrupt `locked_margin`.
+    NotionalOverflow {
+        price: i64,
+        qty: u32,
+    },
+    /// `Guardian::set_leverage` was asked for more leverage than
+    /// `GuardianConfig::max_leverage` allows. Both values are fixed-point
+    /// leverage factors (e.g. `20 × SCALE` = 20x).
+    LeverageExceedsMax {
+        requested: i64,
+        max: i64,
+    },
+    /// `Guardian::set_leverage` was asked for zero or negative leverage.
+    /// `requested` is the fixed-point factor it scaled to (e.g. `-5 × SCALE`
+    /// = -5x) — every margin calculation divides by this, so zero panics
+    /// and negative flips the sign of the margin requirement, bypassing
+    /// risk checks entirely.
+    InvalidLeverage {
+        requested: i64,
+    },
+}
+
+// ---------------------------------------------------------------------------
+// Liquidation
+// ---------------------------------------------------------------------------
+
+/// The outcome of a `Guardian::check_liquidation` call that found an
+/// account underwater: its mark-to-market equity, the maintenance
+/// requirement it failed to meet, and every position the caller must
+/// submit offsetting orders to unwind.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LiquidationReport {
+    pub trader_id: u32,
+    pub equity: i64,
+    pub maintenance_requirement: i64,
+    pub positions_to_unwind: Vec<(u32, i64)>,
+}
+
+// ---------------------------------------------------------------------------
+// Automatic Risk Triggers
+// ---------------------------------------------------------------------------
+
+/// An automatic Kill Switch trigger firing, returned by
+/// `Guardian::evaluate_risk_triggers`, `Guardian::check_price_circuit_breaker`,
+/// and `Guardian::check_exposure_circuit_breaker`. The Guardian has already
+/// banned the affected trader(s) by the time this is returned — the engine's
+/// job is to react (e.g. cancel the trader's resting orders).
+#[derive(Debug, Clone, PartialEq)]
+pub enum RiskEvent {
+    /// A trader's equity fell more than `max_drawdown_pct` below its
+    /// high-water mark.
+    DrawdownBreached {
+        trader_id: u32,
+        equity: i64,
+        high_water_mark: i64,
+    },
+    /// A trader's `realized_pnl` fell to or below `-max_realized_loss`.
+    LossLimitBreached {
+        trader_id: u32,
+        realized_pnl: i64,
+        limit: i64,
+    },
+    /// A trader's notional (at cost basis) on a single symbol exceeded
+    /// `max_position_notional`.
+    PositionLimitBreached {
+        trader_id: u32,
+        symbol_id: u32,
+        notional: i64,
+        limit: i64,
+    },
+    /// The reference price moved more than `circuit_breaker_price_move_pct`
+    /// between consecutive fills. Every trader is banned.
+    PriceCircuitBreaker {
+        symbol_id: u32,
+        previous_price: i64,
+        new_price: i64,
+    },
+    /// Aggregate net exposure on a symbol exceeded
+    /// `circuit_breaker_max_net_exposure`. Every trader is banned.
+    ExposureCircuitBreaker {
+        symbol_id: u32,
+        net_exposure: i64,
+        limit: i64,
+    },
+}
+
+// ---------------------------------------------------------------------------
+// Non-Mutating Simulation
+// ---------------------------------------------------------------------------
+
+/// Margin that a hypothetical order would lock, as returned by
+/// `Guardian::would_pass` — an alias over the same `i64` `validate_and_lock`
+/// itself returns, named for readability at the call site.
+pub type LockedMargin = i64;
+
+/// The projected state of a trader's account after a hypothetical fill, as
+/// returned by `Guardian::simulate_fill`. Computed against a cloned
+/// sandbox — none of it reflects a mutation of the real `Guardian`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ProjectedAccount {
+    pub available_balance: i64,
+    pub locked_margin: i64,
+    pub position: i64,
+    /// Whether this projected state would already be `is_liquidatable`,
+    /// marked at the simulated `fill_price` (which `settle_fill_v2` also
+    /// adopts as the new reference price).
+    pub is_liquidatable: bool,
 }
 
 // ---------------------------------------------------------------------------
@@ -181,6 +423,7 @@ pub enum GuardianReject {
 ///
 /// Manages trader accounts, validates orders against margin and risk limits,
 /// and provides the Kill Switch for emergency trader bans.
+#[derive(Clone)]
 pub struct Guardian {
     /// All trader accounts indexed by trader_id.
     accounts: BTreeMap<u32, Account>,
@@ -190,6 +433,19 @@ pub struct Guardian {
     banned_traders: HashSet<u32>,
     /// Reference price for the volatility band. Updated after each trade.
     reference_price: Option<i64>,
+    /// Per-symbol leverage override, as a fixed-point factor (`10 × SCALE`
+    /// = 10x), set via `set_leverage`. A symbol absent here falls back to
+    /// `config.leverage`. See `leverage_scaled`.
+    symbol_leverage: BTreeMap<u32, i64>,
+    /// Counterparty/insurance pool that `apply_funding` clears against:
+    /// longs' payments land here and shorts draw their receipts from here
+    /// (and vice versa when the rate is negative), so no funding transfer
+    /// ever creates or destroys equity — see `apply_funding`'s doc comment.
+    funding_pool: i64,
+    /// Last timestamp `apply_funding` ran for a symbol, keyed by symbol ID.
+    /// Guards against double-applying the same funding interval if called
+    /// twice with the same (or an older) `now`.
+    last_funding_ts: BTreeMap<u32, i64>,
     /// Configuration.
     config: GuardianConfig,
 }
@@ -204,6 +460,9 @@ impl Guardian {
             accounts: BTreeMap::new(),
             banned_traders: HashSet::new(),
             reference_price: None,
+            symbol_leverage: BTreeMap::new(),
+            funding_pool: 0,
+            last_funding_ts: BTreeMap::new(),
             config,
         }
     }
@@ -238,6 +497,78 @@ impl Guardian {
         self.accounts.get_mut(&trader_id)
     }
 
+    /// Iterate over every trader account. Used by the checkpoint subsystem
+    /// to snapshot the full set of accounts.
+    pub fn accounts(&self) -> impl Iterator<Item = (u32, &Account)> {
+        self.accounts.iter().map(|(trader_id, account)| (*trader_id, account))
+    }
+
+    /// Overwrite (or create) a trader's account wholesale, bypassing the
+    /// `add_funds` incremental top-up path. Used to restore an account from
+    /// a checkpoint, where the full balance/position state is already known.
+    pub fn restore_account(&mut self, trader_id: u32, account: Account) {
+        self.accounts.insert(trader_id, account);
+    }
+
+    // -------------------------------------------------------------------
+    // MARGIN MODE (Cross vs. Isolated)
+    // -------------------------------------------------------------------
+
+    /// Switch a trader between pooled cross-margin and walled-off
+    /// per-symbol isolated margin. A no-op for an unknown trader. Existing
+    /// `isolated_allocations` and open-order ledgers are left as-is —
+    /// switching mode only changes which pool future `validate_and_lock`,
+    /// `settle_fill_v2`, `unlock_margin`, and `check_liquidation` calls
+    /// check against.
+    pub fn set_margin_mode(&mut self, trader_id: u32, mode: MarginMode) {
+        if let Some(account) = self.accounts.get_mut(&trader_id) {
+            account.margin_mode = mode;
+        }
+    }
+
+    /// A trader's current margin mode, or `None` if the trader is unknown.
+    pub fn margin_mode(&self, trader_id: u32) -> Option<MarginMode> {
+        self.accounts.get(&trader_id).map(|a| a.margin_mode)
+    }
+
+    /// Move `amount` out of a trader's cross-pool `available_balance` into
+    /// a walled-off collateral allocation for `symbol_id`. Meaningful once
+    /// the trader is in `MarginMode::Isolated` (see `set_margin_mode`) —
+    /// `validate_and_lock`/`settle_fill_v2`/`check_liquidation` on that
+    /// symbol then check against this allocation instead of the shared
+    /// balance, so a blow-up there can't touch collateral earmarked for
+    /// another symbol.
+    pub fn allocate_isolated(&mut self, trader_id: u32, symbol_id: u32, amount: i64) -> Result<(), GuardianReject> {
+        let account = self.accounts.get_mut(&trader_id).ok_or(GuardianReject::UnknownTrader { trader_id })?;
+        if account.available_balance < amount {
+            return Err(GuardianReject::InsufficientMargin {
+                required: amount,
+                available: account.available_balance,
+            });
+        }
+        account.available_balance -= amount;
+        *account.isolated_allocations.entry(symbol_id).or_insert(0) += amount;
+        Ok(())
+    }
+
+    /// Collateral currently walled off to `symbol_id` under
+    /// `MarginMode::Isolated`. `None` if the trader is unknown; `Some(0)`
+    /// if no allocation has been made yet.
+    pub fn isolated_allocation(&self, trader_id: u32, symbol_id: u32) -> Option<i64> {
+        let account = self.accounts.get(&trader_id)?;
+        Some(account.isolated_allocations.get(&symbol_id).copied().unwrap_or(0))
+    }
+
+    /// Resolve which collateral pool a margin operation on `symbol_id`
+    /// should use, based on the trader's current `MarginMode`. Defaults to
+    /// `Cross` for an unknown trader — the caller's own `UnknownTrader`
+    /// check is what actually rejects those, this just keeps the context
+    /// well-formed in the meantime.
+    fn margin_context(&self, trader_id: u32, symbol_id: u32) -> MarginContext {
+        let mode = self.accounts.get(&trader_id).map(|a| a.margin_mode).unwrap_or_default();
+        MarginContext { mode, symbol_id }
+    }
+
     // -------------------------------------------------------------------
     // KILL SWITCH
     // -------------------------------------------------------------------
@@ -279,6 +610,11 @@ impl Guardian {
         self.reference_price = Some(price);
     }
 
+    /// Current volatility-band reference price, if a trade has ever set one.
+    pub fn reference_price(&self) -> Option<i64> {
+        self.reference_price
+    }
+
     /// Get the current volatility band configuration.
     pub fn volatility_band_config(&self) -> &VolatilityBandConfig {
         &self.config.volatility_band
@@ -289,6 +625,113 @@ impl Guardian {
         self.config.volatility_band.band_pct = (pct * SCALE as f64).round() as i64;
     }
 
+    // -------------------------------------------------------------------
+    // AUTOMATIC RISK TRIGGERS
+    // -------------------------------------------------------------------
+
+    /// Evaluate `trader_id`'s per-account automatic Kill Switch triggers —
+    /// `max_drawdown_pct`, `max_realized_loss`, and `max_position_notional`
+    /// on `symbol_id` — and auto-ban the trader on the first one that
+    /// trips. Call this after `settle_fill_v2`/`check_liquidation` update
+    /// the account.
+    ///
+    /// Checked in order (drawdown, loss limit, position limit) and returns
+    /// on the first breach — a trader already banned by one trigger hasn't
+    /// had the others evaluated this call, but they're auto-banned either
+    /// way. Returns `None` if the trader is unknown, every configured
+    /// trigger is `None`, or the account is within all configured limits.
+    pub fn evaluate_risk_triggers(&mut self, trader_id: u32, symbol_id: u32) -> Option<RiskEvent> {
+        let account = self.accounts.get_mut(&trader_id)?;
+        let equity = account.total_equity();
+        if equity > account.high_water_mark {
+            account.high_water_mark = equity;
+        }
+
+        if let Some(max_drawdown_pct) = self.config.max_drawdown_pct {
+            let hwm = account.high_water_mark;
+            if hwm > 0 {
+                let drawdown_pct = (hwm - equity) as i128 * SCALE as i128 / hwm as i128;
+                if drawdown_pct >= max_drawdown_pct as i128 {
+                    self.ban_trader(trader_id);
+                    return Some(RiskEvent::DrawdownBreached { trader_id, equity, high_water_mark: hwm });
+                }
+            }
+        }
+
+        if let Some(max_realized_loss) = self.config.max_realized_loss {
+            let realized_pnl = account.realized_pnl;
+            if realized_pnl <= -max_realized_loss {
+                self.ban_trader(trader_id);
+                return Some(RiskEvent::LossLimitBreached { trader_id, realized_pnl, limit: max_realized_loss });
+            }
+        }
+
+        if let Some(max_position_notional) = self.config.max_position_notional {
+            let position = account.position(symbol_id);
+            if position != 0 {
+                if let Some(entry) = account.entry_price(symbol_id) {
+                    let notional = (position.unsigned_abs() as i128 * entry as i128) as i64;
+                    if notional > max_position_notional {
+                        self.ban_trader(trader_id);
+                        return Some(RiskEvent::PositionLimitBreached {
+                            trader_id,
+                            symbol_id,
+                            notional,
+                            limit: max_position_notional,
+                        });
+                    }
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Market-wide circuit breaker: if `new_reference_price` moves more
+    /// than `circuit_breaker_price_move_pct` away from the last reference
+    /// price, halt the entire market via `ban_all_traders` and report it.
+    /// A no-op (and `None`) if the trigger is unconfigured or there's no
+    /// prior reference price to compare against.
+    pub fn check_price_circuit_breaker(&mut self, symbol_id: u32, new_reference_price: i64) -> Option<RiskEvent> {
+        let threshold = self.config.circuit_breaker_price_move_pct?;
+        let previous_price = self.reference_price?;
+        if previous_price == 0 {
+            return None;
+        }
+        let move_pct = (new_reference_price - previous_price).unsigned_abs() as i128 * SCALE as i128
+            / previous_price.unsigned_abs() as i128;
+        if move_pct >= threshold as i128 {
+            self.ban_all_traders();
+            return Some(RiskEvent::PriceCircuitBreaker { symbol_id, previous_price, new_price: new_reference_price });
+        }
+        None
+    }
+
+    /// Market-wide circuit breaker: the aggregate signed exposure across
+    /// every trader's position on `symbol_id`, marked at `mark_price`. A
+    /// market that's heavily net long or net short on one symbol has no
+    /// natural counterparty left to absorb further moves.
+    pub fn aggregate_net_exposure(&self, symbol_id: u32, mark_price: i64) -> i64 {
+        let total: i128 = self.accounts.values()
+            .map(|account| account.position(symbol_id) as i128 * mark_price as i128)
+            .sum();
+        total as i64
+    }
+
+    /// Market-wide circuit breaker: if `aggregate_net_exposure` on
+    /// `symbol_id` exceeds `circuit_breaker_max_net_exposure` in either
+    /// direction, halt the entire market via `ban_all_traders` and report
+    /// it. A no-op (and `None`) if the trigger is unconfigured.
+    pub fn check_exposure_circuit_breaker(&mut self, symbol_id: u32, mark_price: i64) -> Option<RiskEvent> {
+        let limit = self.config.circuit_breaker_max_net_exposure?;
+        let net_exposure = self.aggregate_net_exposure(symbol_id, mark_price);
+        if net_exposure.unsigned_abs() as i128 > limit as i128 {
+            self.ban_all_traders();
+            return Some(RiskEvent::ExposureCircuitBreaker { symbol_id, net_exposure, limit });
+        }
+        None
+    }
+
     // -------------------------------------------------------------------
     // PRE-TRADE VALIDATION (Phase 1: LOCK)
     // -------------------------------------------------------------------
@@ -297,10 +740,16 @@ impl Guardian {
     ///
     /// This is the primary entry point called BEFORE the matching engine.
     ///
-    /// On success, the required cash is moved from `available_balance`
-    /// to `locked_margin` and the function returns `Ok(locked_amount)`.
+    /// Margin is reserved against the trader's NET exposure per symbol
+    /// (see `net_margin_requirement`): a resting buy and a resting sell
+    /// hedge each other, so this order only locks however much it pushes
+    /// the larger of the two directional notionals. On success, that
+    /// incremental amount (which can be zero, or negative to release
+    /// margin the opposite side no longer needs) moves between
+    /// `available_balance` and `locked_margin`, and is returned as
+    /// `Ok(incremental_margin)`.
     ///
-    /// The caller must later call `settle_fills()` or `unlock_margin()`
+    /// The caller must later call `settle_fill_v2()` or `unlock_margin()`
     /// to reconcile actual execution vs. the lock.
     pub fn validate_and_lock(
         &mut self,
@@ -347,146 +796,135 @@ impl Guardian {
         }
 
         // Check 4: Account exists
+        let ctx = self.margin_context(trader_id, symbol_id);
+        let leverage = self.leverage_scaled(symbol_id);
         let account = self.accounts.get_mut(&trader_id)
             .ok_or(GuardianReject::UnknownTrader { trader_id })?;
 
-        // Check 5: Margin / Position check
-        match side {
-            Side::Buy => {
-                // For buys: lock price × qty as margin.
-                // We use the LIMIT price (worst case cost).
-                let required_margin = Self::compute_notional(price, qty);
-                if account.available_balance < required_margin {
+        // Check 5: Position check (sells only — margin is handled below for
+        // both sides via the net-exposure reservation).
+        if side == Side::Sell && !self.config.allow_short_selling {
+            let current_pos = account.position(symbol_id);
+            if current_pos < qty as i64 {
+                return Err(GuardianReject::InsufficientPosition {
+                    required: qty as i64,
+                    current: current_pos,
+                });
+            }
+        }
+
+        // Check 6: Net (hedged) margin reservation. A resting buy and a
+        // resting sell on the same symbol partially hedge each other — the
+        // worst case is only one side ever fills — so the margin this
+        // order requires is however much it pushes the LARGER of the two
+        // directional notionals, not its own notional outright. An order
+        // that merely grows the currently-smaller side locks nothing.
+        let order_notional = Self::compute_notional(price, qty)?;
+        let buy_notional = account.open_buy_notional.get(&symbol_id).copied().unwrap_or(0);
+        let sell_notional = account.open_sell_notional.get(&symbol_id).copied().unwrap_or(0);
+        let before = Self::net_margin_requirement(buy_notional, sell_notional, leverage);
+        let (new_buy_notional, new_sell_notional) = match side {
+            Side::Buy => (buy_notional + order_notional, sell_notional),
+            Side::Sell => (buy_notional, sell_notional + order_notional),
+        };
+        let after = Self::net_margin_requirement(new_buy_notional, new_sell_notional, leverage);
+        let incremental_margin = after - before;
+
+        // Check 7: this order's worst-case taker fee — the rate charged if
+        // it crosses the book instead of resting — reserved up front
+        // alongside the margin, so a fill can't be under-margined for its
+        // own fee. See `settle_fill_v2` for the actual maker/taker rate
+        // determination at settlement time.
+        let worst_case_fee = Self::fee_on_notional(order_notional, self.config.taker_fee_bps);
+
+        // Phase 1: LOCK. Under `Cross`, move the incremental margin
+        // between `available_balance` and `locked_margin` (a non-positive
+        // `incremental_margin` releases margin instead, e.g. when this
+        // order is hedged entirely by the opposite side). Under
+        // `Isolated`, nothing moves — the symbol's `isolated_allocations`
+        // entry is a fixed ceiling checked against the total requirement,
+        // not a balance that drains as orders lock against it.
+        match ctx.mode {
+            MarginMode::Cross => {
+                let required = incremental_margin.max(0) + worst_case_fee;
+                if required > 0 && account.available_balance < required {
                     return Err(GuardianReject::InsufficientMargin {
-                        required: required_margin,
+                        required,
                         available: account.available_balance,
                     });
                 }
-                // Phase 1: LOCK — move cash to locked_margin.
-                account.available_balance -= required_margin;
-                account.locked_margin += required_margin;
-                Ok(required_margin)
+                account.available_balance -= incremental_margin;
+                account.locked_margin += incremental_margin;
             }
-            Side::Sell => {
-                if !self.config.allow_short_selling {
-                    // Must have sufficient position.
-                    let current_pos = account.position(symbol_id);
-                    if current_pos < qty as i64 {
-                        return Err(GuardianReject::InsufficientPosition {
-                            required: qty as i64,
-                            current: current_pos,
-                        });
-                    }
-                }
-                // For sells: lock margin for notional too (to cover potential losses
-                // on short positions). Same lock logic.
-                let required_margin = Self::compute_notional(price, qty);
-                if account.available_balance < required_margin {
+            MarginMode::Isolated => {
+                let allocation = account.isolated_allocations.get(&symbol_id).copied().unwrap_or(0);
+                let required = after + worst_case_fee;
+                if required > allocation {
                     return Err(GuardianReject::InsufficientMargin {
-                        required: required_margin,
-                        available: account.available_balance,
+                        required,
+                        available: allocation,
                     });
                 }
-                account.available_balance -= required_margin;
-                account.locked_margin += required_margin;
-                Ok(required_margin)
             }
         }
+        match side {
+            Side::Buy => account.open_buy_notional.insert(symbol_id, new_buy_notional),
+            Side::Sell => account.open_sell_notional.insert(symbol_id, new_sell_notional),
+        };
+        Ok(incremental_margin)
     }
 
     // -------------------------------------------------------------------
     // POST-TRADE SETTLEMENT (Phase 2: SETTLE)
     // -------------------------------------------------------------------
 
-    /// Settle fills after matching. Releases excess locked margin.
-    ///
-    /// For each fill:
-    /// - Release the locked margin for the filled quantity.
-    /// - Compute the actual cost at the fill price.
-    /// - Return the price improvement (locked - actual) to available_balance.
-    /// - Update the position.
+    /// Settle a fill: release locked margin, apply its cash effect, and
+    /// update the position's cost basis and realized PnL.
     ///
-    /// `order_price` is the LIMIT price (what we locked at).
-    /// Each fill has its own `fill_price` (the actual execution price).
-    pub fn settle_fill(
-        &mut self,
-        trader_id: u32,
-        side: Side,
-        order_price: i64,
-        fill_price: i64,
-        fill_qty: u32,
-        _symbol_id: u32,
-    ) {
-        if let Some(account) = self.accounts.get_mut(&trader_id) {
-            // How much we locked for this fill's quantity.
-            let locked_for_fill = Self::compute_notional(order_price, fill_qty);
-            // How much it actually cost at the execution price.
-            let actual_cost = Self::compute_notional(fill_price, fill_qty);
-
-            // Unlock the reserved margin for this fill.
-            account.locked_margin -= locked_for_fill;
-
-            // For buys: we locked at order_price but may have paid less.
-            // Price improvement goes back to available_balance.
-            // For sells: similar logic (we locked for risk, settle the actual).
-            match side {
-                Side::Buy => {
-                    // Price improvement = what we reserved - what we paid.
-                    // This is always >= 0 for buys (fill_price <= order_price).
-                    let improvement = locked_for_fill - actual_cost;
-                    account.available_balance += improvement;
-                    // Actual cost stays "spent" (consumed by the position).
-                }
-                Side::Sell => {
-                    // For sells: we receive the fill proceeds.
-                    let improvement = locked_for_fill - actual_cost;
-                    account.available_balance += improvement;
-                    // The actual_cost is "returned" since we sold.
-                    account.available_balance += actual_cost;
-                    // But we also need to subtract the cost for the buy side...
-                    // Actually for sells, the fill proceeds go to available:
-                    // We unlock the full locked amount, then add the fill proceeds.
-                    // Corrected: for sell, we get the proceeds + any improvement.
-                    // Net: available += locked_for_fill (full unlock) + (actual_cost - locked_for_fill)
-                    //     = actual_cost
-                    // Wait, let's be precise:
-                    // locked_margin -= locked_for_fill  (done above)
-                    // available += locked_for_fill      (refund the lock)
-                    // available += actual_cost           (sell proceeds)
-                    // But that double-counts. Let me redo this cleanly:
-                }
-            }
-
-            // Actually, let's use a cleaner model that works for both sides:
-            // Step 1: Fully unlock the margin for this fill.
-            //         (already done: locked_margin -= locked_for_fill)
-            // Step 2: Return what we didn't spend.
-            //         For Buy:  we spend actual_cost, so return (locked_for_fill - actual_cost)
-            //         For Sell: we receive actual_cost as proceeds
-            //
-            // Let me rewrite this cleanly:
-            // (The messy version above was a draft. Clean version below.)
-
-            // Reset: undo the match-arm adjustments above, use unified logic.
-            // ... Actually the match arms above already diverged. Let me restructure.
-        }
-    }
-
-    /// Clean, unified post-trade settlement.
+    /// Call this ONCE per fill. It handles both Buy and Sell sides correctly
+    /// — the single settlement routine, superseding an earlier `settle_fill`
+    /// draft that never got its Sell-side cash flow untangled.
     ///
-    /// Call this ONCE per fill. It handles both Buy and Sell sides correctly.
+    /// A fill retires `fill_qty` of this order's contribution to the
+    /// trader's `open_{buy,sell}_notional` ledger on `symbol_id`, and the
+    /// margin released is however much that retirement lowers the net
+    /// (hedged) requirement from `net_margin_requirement` — not
+    /// necessarily this order's own gross share, if an opposing order on
+    /// the same symbol is still absorbing some of the exposure.
     ///
     /// For BUYS:
-    ///   - We locked `order_price × fill_qty` before matching.
-    ///   - We actually paid `fill_price × fill_qty`.
-    ///   - Price improvement = `(order_price - fill_price) × fill_qty` goes back to available.
+    ///   - We actually paid `fill_price × fill_qty / leverage` — the same
+    ///     leverage factor `validate_and_lock` divided the lock by, so the
+    ///     cash effect never exceeds what was actually reserved.
+    ///   - Whatever margin this fill's retirement released, minus the
+    ///     actual cost, goes back to `available_balance` (can be negative
+    ///     if the released amount didn't cover the cost, same as a normal
+    ///     price-improvement refund running the other way).
     ///   - Position increases by `fill_qty`.
     ///
     /// For SELLS:
-    ///   - We locked `order_price × fill_qty` before matching.
-    ///   - We received `fill_price × fill_qty` as proceeds.
-    ///   - We unlock the full lock AND add the proceeds.
+    ///   - We received `fill_price × fill_qty / leverage` as proceeds,
+    ///     same leverage scaling as the Buy side.
+    ///   - The released margin plus the proceeds go back to `available_balance`.
     ///   - Position decreases by `fill_qty`.
+    ///
+    /// Either side then runs through `apply_position_fill`, which folds the
+    /// fill into the weighted-average entry price if it grows the position,
+    /// or realizes PnL against that average if it reduces, closes, or flips
+    /// the position — see its own doc comment for the cost-basis model.
+    ///
+    /// A maker/taker fee on `fill_price × fill_qty` is also deducted: the
+    /// maker rate (`GuardianConfig::maker_fee_bps`) if this fill executed
+    /// exactly at the order's own `order_price` (it rested on the book
+    /// until the other side crossed to meet it there), the taker rate
+    /// otherwise (this order itself crossed the book). Both default to
+    /// `0`. The fee accumulates in `Account::fees_paid`.
+    ///
+    /// Every notional computation is checked before any balance is touched:
+    /// if either the lock-release or the fill-side amount would overflow
+    /// `i64`, this returns `Err(GuardianReject::NotionalOverflow { .. })`
+    /// and the account is left completely untouched, rather than applying
+    /// half the update and wrapping the rest.
     pub fn settle_fill_v2(
         &mut self,
         trader_id: u32,
@@ -495,145 +933,709 @@ impl Guardian {
         fill_price: i64,
         fill_qty: u32,
         symbol_id: u32,
-    ) {
+    ) -> Result<(), GuardianReject> {
+        let leverage = self.leverage_scaled(symbol_id);
+        let ctx = self.margin_context(trader_id, symbol_id);
+
+        let order_notional = Self::compute_notional(order_price, fill_qty)?;
+        let fill_notional = Self::compute_notional(fill_price, fill_qty)?;
+
         let account = match self.accounts.get_mut(&trader_id) {
             Some(a) => a,
-            None => return, // Shouldn't happen but defensive.
+            None => return Ok(()), // Shouldn't happen but defensive.
         };
 
-        let locked_for_fill = Self::compute_notional(order_price, fill_qty);
-
-        // Step 1: Release the lock for this fill's portion.
-        account.locked_margin -= locked_for_fill;
-
+        // Step 1: Retire this fill's share of the order from the net-margin
+        // ledger, and release however much that lowers the requirement.
+        let buy_notional = account.open_buy_notional.get(&symbol_id).copied().unwrap_or(0);
+        let sell_notional = account.open_sell_notional.get(&symbol_id).copied().unwrap_or(0);
+        let before = Self::net_margin_requirement(buy_notional, sell_notional, leverage);
+        let (new_buy_notional, new_sell_notional) = match side {
+            Side::Buy => (buy_notional - order_notional, sell_notional),
+            Side::Sell => (buy_notional, sell_notional - order_notional),
+        };
+        let after = Self::net_margin_requirement(new_buy_notional, new_sell_notional, leverage);
+        let released = before - after;
         match side {
-            Side::Buy => {
-                // The actual cost of the purchase.
-                let actual_cost = Self::compute_notional(fill_price, fill_qty);
-                // Price improvement: locked more than we spent → refund the difference.
-                let refund = locked_for_fill - actual_cost;
-                account.available_balance += refund;
-                // Position increases.
-                *account.positions.entry(symbol_id).or_insert(0) += fill_qty as i64;
+            Side::Buy => account.open_buy_notional.insert(symbol_id, new_buy_notional),
+            Side::Sell => account.open_sell_notional.insert(symbol_id, new_sell_notional),
+        };
+
+        // This fill is a maker fill if it executed exactly at the order's
+        // own limit price — it rested on the book until the opposite side
+        // crossed to meet it there. A fill at any other (necessarily
+        // better, per the engine's own price protection) price means this
+        // order itself crossed the book to take liquidity instead.
+        let is_maker = fill_price == order_price;
+        let fee_bps = if is_maker { self.config.maker_fee_bps } else { self.config.taker_fee_bps };
+        let fee = Self::fee_on_notional(fill_notional, fee_bps);
+
+        // Step 2: Apply the fill's cash effect. `validate_and_lock` only
+        // ever locked `notional / leverage` (see `net_margin_requirement`),
+        // so the cash this fill actually moves must be scaled down by the
+        // same leverage factor — otherwise a leveraged fill would be
+        // debited/credited its full unleveraged notional while only ever
+        // having reserved a fraction of it, driving the balance arbitrarily
+        // far from what the margin checks approved. Reusing
+        // `net_margin_requirement(fill_notional, 0, leverage)` (the `0`
+        // standing in for an absent opposite side) gives exactly that
+        // scaled amount.
+        //
+        // Under `Cross`, the release comes out of `locked_margin` and
+        // lands back in `available_balance` alongside the
+        // price-improvement/proceeds. Under `Isolated`, `locked_margin`
+        // was never touched to begin with (see `validate_and_lock`) — the
+        // fill's cost/proceeds move directly against the symbol's
+        // `isolated_allocations` ceiling instead, same scaling. Either way,
+        // the fee is deducted from that same pool.
+        let leveraged_notional = Self::net_margin_requirement(fill_notional, 0, leverage);
+        match ctx.mode {
+            MarginMode::Cross => {
+                account.locked_margin -= released;
+                match side {
+                    Side::Buy => {
+                        let actual_cost = leveraged_notional;
+                        let refund = released - actual_cost;
+                        account.available_balance += refund;
+                    }
+                    Side::Sell => {
+                        let proceeds = leveraged_notional;
+                        account.available_balance += released + proceeds;
+                    }
+                }
+                account.available_balance -= fee;
             }
-            Side::Sell => {
-                // For sell: we get the full lock back (refund) PLUS the fill proceeds.
-                let proceeds = Self::compute_notional(fill_price, fill_qty);
-                account.available_balance += locked_for_fill + proceeds - locked_for_fill;
-                // Simplifies to: available_balance += proceeds
-                // But for clarity of the two-phase model, written explicitly.
-                // Actually let's just be clean:
-                account.available_balance += proceeds; // net effect after simplification
-                // Oops, we already subtracted locked_for_fill from locked_margin.
-                // And we need to return that to available:
-                // Actually: unlock returns the lock, and we also get proceeds from the sale.
-                // step 1: locked_margin -= locked_for_fill (done above)
-                // step 2: available += locked_for_fill (return the lock)
-                // step 3: available += proceeds (sale income)
-                // But step 2+3 combined = locked_for_fill + proceeds
-                // Let me redo cleanly outside this match.
-                // Position decreases.
-                *account.positions.entry(symbol_id).or_insert(0) -= fill_qty as i64;
+            MarginMode::Isolated => {
+                let pool = account.isolated_allocations.entry(symbol_id).or_insert(0);
+                match side {
+                    Side::Buy => *pool -= leveraged_notional,
+                    Side::Sell => *pool += leveraged_notional,
+                }
+                *pool -= fee;
             }
         }
+        account.fees_paid += fee;
+
+        match side {
+            Side::Buy => Self::apply_position_fill(account, symbol_id, fill_qty as i64, fill_price),
+            Side::Sell => Self::apply_position_fill(account, symbol_id, -(fill_qty as i64), fill_price),
+        }
 
         // Update reference price for volatility band.
         self.reference_price = Some(fill_price);
+        Ok(())
+    }
+
+    /// Apply a fill's `signed_qty` (positive for a buy, negative for a
+    /// sell) to `positions[symbol_id]`, updating cost basis and realized
+    /// PnL to match:
+    ///
+    /// - **Growing** a position (opening it, or adding to it in the same
+    ///   direction): folds into the weighted-average entry price —
+    ///   `new_avg = (old_qty × old_avg + added_qty × fill_price) / new_qty`
+    ///   — and `realized_pnl` is untouched.
+    /// - **Reducing or closing** it (same direction, smaller magnitude, or
+    ///   exactly flat): the average entry is unchanged by what's left, and
+    ///   the closed portion realizes `(fill_price - entry) × closed_qty`
+    ///   for a long, negated for a short, into `realized_pnl`. A full
+    ///   close also clears the entry price.
+    /// - **Flipping** it (a fill larger than the existing position, in the
+    ///   opposite direction): realizes PnL on the entire old position as
+    ///   above, then opens the remainder as a fresh position with its
+    ///   entry price reset to `fill_price`.
+    fn apply_position_fill(account: &mut Account, symbol_id: u32, signed_qty: i64, fill_price: i64) {
+        let old_pos = account.position(symbol_id);
+        let new_pos = old_pos + signed_qty;
+        let growing = old_pos == 0 || (old_pos > 0) == (signed_qty > 0);
+
+        if growing {
+            if new_pos != 0 {
+                let old_entry = account.entry_price(symbol_id).unwrap_or(fill_price);
+                let old_qty = old_pos.unsigned_abs() as i128;
+                let added_qty = signed_qty.unsigned_abs() as i128;
+                let new_qty = new_pos.unsigned_abs() as i128;
+                let weighted_avg = (old_qty * old_entry as i128 + added_qty * fill_price as i128) / new_qty;
+                account.entry_prices.insert(symbol_id, weighted_avg as i64);
+            }
+        } else {
+            let entry = account.entry_price(symbol_id).unwrap_or(fill_price);
+            let closed_qty = old_pos.unsigned_abs().min(signed_qty.unsigned_abs()) as i128;
+            let pnl_per_unit = if old_pos > 0 { fill_price - entry } else { entry - fill_price };
+            account.realized_pnl += (pnl_per_unit as i128 * closed_qty) as i64;
+
+            if new_pos == 0 {
+                account.entry_prices.remove(&symbol_id);
+            } else if (new_pos > 0) != (old_pos > 0) {
+                // Flip: the old position is fully closed above, and what's
+                // left opens fresh in the other direction at the fill price.
+                account.entry_prices.insert(symbol_id, fill_price);
+            }
+        }
+
+        *account.positions.entry(symbol_id).or_insert(0) += signed_qty;
     }
 
     /// Unlock margin for cancelled/unfilled quantity.
     ///
-    /// When an order is cancelled or rests unfilled, the locked margin
-    /// for the remaining quantity must be returned to available_balance.
+    /// When an order is cancelled or rests unfilled, its contribution to
+    /// `open_{buy,sell}_notional` on `symbol_id` is retired, and whatever
+    /// that lowers the net (hedged) margin requirement by (see
+    /// `net_margin_requirement`) is returned to `available_balance` — which
+    /// can be zero if an opposing order on the same symbol was still the
+    /// larger, dominant side. Checked the same way as `settle_fill_v2`: an
+    /// overflowing notional rejects rather than unlocking a wrapped amount.
     pub fn unlock_margin(
         &mut self,
         trader_id: u32,
+        side: Side,
         order_price: i64,
         unfilled_qty: u32,
-    ) {
+        symbol_id: u32,
+    ) -> Result<(), GuardianReject> {
+        let leverage = self.leverage_scaled(symbol_id);
+        let ctx = self.margin_context(trader_id, symbol_id);
+        let order_notional = Self::compute_notional(order_price, unfilled_qty)?;
+        if let Some(account) = self.accounts.get_mut(&trader_id) {
+            let buy_notional = account.open_buy_notional.get(&symbol_id).copied().unwrap_or(0);
+            let sell_notional = account.open_sell_notional.get(&symbol_id).copied().unwrap_or(0);
+            let before = Self::net_margin_requirement(buy_notional, sell_notional, leverage);
+            let (new_buy_notional, new_sell_notional) = match side {
+                Side::Buy => (buy_notional - order_notional, sell_notional),
+                Side::Sell => (buy_notional, sell_notional - order_notional),
+            };
+            let after = Self::net_margin_requirement(new_buy_notional, new_sell_notional, leverage);
+            match side {
+                Side::Buy => account.open_buy_notional.insert(symbol_id, new_buy_notional),
+                Side::Sell => account.open_sell_notional.insert(symbol_id, new_sell_notional),
+            };
+            // Under `Isolated`, `validate_and_lock` never moved cash out of
+            // `available_balance` in the first place (the allocation is a
+            // ceiling, not a moving balance) — so there's nothing to release
+            // here beyond retiring the ledger entry above.
+            if ctx.mode == MarginMode::Cross {
+                let released = before - after;
+                account.locked_margin -= released;
+                account.available_balance += released;
+            }
+        }
+        Ok(())
+    }
+
+    /// Charge (or, for a negative `fee`, rebate) a trade fee against a
+    /// trader's available balance. A no-op for an unknown trader — mirrors
+    /// `settle_fill_v2`'s defensive "shouldn't happen but don't panic".
+    pub fn charge_fee(&mut self, trader_id: u32, fee: i64) {
         if let Some(account) = self.accounts.get_mut(&trader_id) {
-            let unlock_amount = Self::compute_notional(order_price, unfilled_qty);
-            account.locked_margin -= unlock_amount;
-            account.available_balance += unlock_amount;
+            account.available_balance -= fee;
         }
     }
 
     // -------------------------------------------------------------------
-    // INTERNAL HELPERS
+    // LEVERAGED-MARGIN LIQUIDATION ENGINE
     // -------------------------------------------------------------------
 
-    /// Compute notional = price × qty in fixed-point.
-    /// This is exact integer math. No float. No rounding.
-    #[inline]
-    fn compute_notional(price: i64, qty: u32) -> i64 {
-        // price is in fixed-point (scaled by 10^8), qty is raw.
-        // notional = price * qty (result is in fixed-point scale).
-        price * (qty as i64)
-    }
-}
+    /// Mark-to-market a trader's account against `mark_price` (the price
+    /// for `symbol_id`) and check whether it has fallen below its
+    /// maintenance-margin requirement.
+    ///
+    /// Under `MarginMode::Cross`, this nets unrealized PnL across every
+    /// symbol the trader holds before judging them underwater:
+    /// `equity = available_balance + locked_margin + unrealized_pnl`, where
+    /// `unrealized_pnl` sums `(mark_price - entry_price) × position` across
+    /// every open position (the sign of `position` already makes this
+    /// negate correctly for shorts) — a loss on one symbol can be offset by
+    /// a gain on another. The maintenance requirement sums `|position| ×
+    /// mark_price × maintenance_margin_pct` the same way. Note `mark_price`
+    /// is applied to every symbol here, so this is only exact for
+    /// single-symbol portfolios; a multi-symbol Cross account needs a
+    /// mark price per symbol to net correctly.
+    ///
+    /// Under `MarginMode::Isolated`, the check is scoped to `symbol_id`
+    /// alone: `equity = isolated_allocations[symbol_id] + unrealized_pnl`
+    /// for just that symbol's position, so a blow-up there can't be masked
+    /// by (or bleed into) another symbol's collateral.
+    ///
+    /// Returns `None` if the trader is unknown or equity is still above the
+    /// requirement. Otherwise the trader is auto-banned via the Kill
+    /// Switch and a `LiquidationReport` is returned listing every position
+    /// that must be unwound.
+    pub fn check_liquidation(&mut self, trader_id: u32, symbol_id: u32, mark_price: i64) -> Option<LiquidationReport> {
+        let ctx = self.margin_context(trader_id, symbol_id);
+        let account = self.accounts.get(&trader_id)?;
+
+        let symbols: Vec<u32> = match ctx.mode {
+            MarginMode::Cross => account.positions.keys().copied().collect(),
+            MarginMode::Isolated => vec![symbol_id],
+        };
 
-impl Default for Guardian {
-    fn default() -> Self {
-        Self::new()
+        let mut unrealized_pnl: i128 = 0;
+        let mut maintenance_requirement: i128 = 0;
+        let mut positions_to_unwind = Vec::new();
+        for sym in symbols {
+            let position = account.position(sym);
+            if position == 0 {
+                continue;
+            }
+            let entry = account.entry_price(sym).unwrap_or(mark_price);
+            unrealized_pnl += (mark_price - entry) as i128 * position as i128;
+            maintenance_requirement += position.unsigned_abs() as i128 * mark_price as i128
+                * self.config.maintenance_margin_pct as i128
+                / SCALE as i128;
+            positions_to_unwind.push((sym, position));
+        }
+
+        let equity = match ctx.mode {
+            MarginMode::Cross => account.available_balance as i128 + account.locked_margin as i128 + unrealized_pnl,
+            MarginMode::Isolated => {
+                account.isolated_allocations.get(&symbol_id).copied().unwrap_or(0) as i128 + unrealized_pnl
+            }
+        };
+        if equity >= maintenance_requirement {
+            return None;
+        }
+
+        self.ban_trader(trader_id);
+        Some(LiquidationReport {
+            trader_id,
+            equity: equity as i64,
+            maintenance_requirement: maintenance_requirement as i64,
+            positions_to_unwind,
+        })
     }
-}
 
-// ===========================================================================
-// TESTS
-// ===========================================================================
+    /// Whether `trader_id` is currently undercollateralized against the
+    /// maintenance-margin requirement, marking every open position at the
+    /// Guardian's current `reference_price`. A read-only counterpart to
+    /// `check_liquidation` that doesn't ban the trader or require the
+    /// caller to supply a symbol/mark price — suited to a health-check
+    /// sweep across all accounts between fills.
+    ///
+    /// Nets unrealized PnL across every symbol the trader holds, the same
+    /// as `check_liquidation` does under `MarginMode::Cross`; it isn't
+    /// symbol-scoped, so an `Isolated` trader should use `check_liquidation`
+    /// with an explicit `symbol_id` instead. Returns `false` if the trader
+    /// is unknown, flat, or no reference price has been set yet.
+    pub fn is_liquidatable(&self, trader_id: u32) -> bool {
+        if let Some(mark_price) = self.reference_price {
+            if let Some(account) = self.accounts.get(&trader_id) {
+                let (equity, maintenance_requirement) = self.mark_to_market(account, mark_price);
+                return equity < maintenance_requirement;
+            }
+        }
+        false
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::types::Side;
+    /// Force-close every open position in `trader_id`'s account at the
+    /// Guardian's current `reference_price`, realizing the resulting PnL
+    /// into `available_balance` and releasing `locked_margin` back to it.
+    /// Bans the trader via the Kill Switch and returns a `LiquidationReport`
+    /// describing what was unwound.
+    ///
+    /// Also clears `open_buy_notional`/`open_sell_notional` for every
+    /// symbol — `locked_margin` is paid out in full here, so any resting
+    /// order still contributing to that ledger would otherwise compute a
+    /// nonzero `released` against already-paid-out margin the next time
+    /// `unlock_margin` or `settle_fill_v2` runs against it, double-crediting
+    /// `available_balance`. `ban_trader` stops new orders but not orders
+    /// already resting on the book, so this is required, not just tidy-up;
+    /// the caller is still responsible for actually cancelling those
+    /// resting orders on the matching engine's own book.
+    ///
+    /// A no-op (returns `None`, trader left unbanned) if the trader is
+    /// unknown, already flat, or no reference price has been set — there's
+    /// nothing to mark-to-market against.
+    ///
+    /// Under `MarginMode::Isolated`, `locked_margin` is never the collateral
+    /// backing a position (see `validate_and_lock`/`settle_fill_v2`) —
+    /// it's the symbol's own `isolated_allocations` entry. So each
+    /// unwound symbol settles its realized PnL into that symbol's pool
+    /// and sweeps whatever's left back to `available_balance`, rather
+    /// than stranding it under a symbol that no longer has a position.
+    pub fn liquidate(&mut self, trader_id: u32) -> Option<LiquidationReport> {
+        let mark_price = self.reference_price?;
+        let account = self.accounts.get_mut(&trader_id)?;
+
+        let symbols: Vec<u32> = account.positions.iter()
+            .filter(|&(_, &position)| position != 0)
+            .map(|(&sym, _)| sym)
+            .collect();
+        if symbols.is_empty() {
+            return None;
+        }
 
-    const S: i64 = crate::SCALE; // 10^8
+        let margin_mode = account.margin_mode;
+        let mut realized_delta: i64 = 0;
+        let mut maintenance_requirement: i128 = 0;
+        let mut positions_to_unwind = Vec::with_capacity(symbols.len());
+        for sym in symbols {
+            let position = account.position(sym);
+            let entry = account.entry_price(sym).unwrap_or(mark_price);
+            let sym_realized_delta = ((mark_price - entry) as i128 * position as i128) as i64;
+            realized_delta += sym_realized_delta;
+            maintenance_requirement += position.unsigned_abs() as i128 * mark_price as i128
+                * self.config.maintenance_margin_pct as i128
+                / SCALE as i128;
+            positions_to_unwind.push((sym, position));
+            account.positions.insert(sym, 0);
+            account.entry_prices.remove(&sym);
+
+            if margin_mode == MarginMode::Isolated {
+                let pool = account.isolated_allocations.entry(sym).or_insert(0);
+                *pool += sym_realized_delta;
+                account.available_balance += *pool;
+                *pool = 0;
+            }
+        }
 
-    fn price(v: i64) -> i64 { v * S }
+        account.realized_pnl += realized_delta;
+        if margin_mode == MarginMode::Cross {
+            account.available_balance += account.locked_margin + realized_delta;
+            account.locked_margin = 0;
+        }
+        account.open_buy_notional.clear();
+        account.open_sell_notional.clear();
+        let equity = account.total_equity();
+
+        self.ban_trader(trader_id);
+        Some(LiquidationReport {
+            trader_id,
+            equity,
+            maintenance_requirement: maintenance_requirement as i64,
+            positions_to_unwind,
+        })
+    }
 
-    fn setup_guardian() -> Guardian {
-        let mut g = Guardian::new();
-        // Trader 1: $10,000
-        g.add_funds(1, price(10_000));
-        // Trader 2: $5,000
-        g.add_funds(2, price(5_000));
-        g
+    /// Shared mark-to-market math behind `is_liquidatable`: nets unrealized
+    /// PnL across every open position at `mark_price` and sums the
+    /// maintenance requirement the same way — mirrors `check_liquidation`'s
+    /// `MarginMode::Cross` branch, but against a single account reference
+    /// rather than a `trader_id` lookup, so `liquidate` can also shape its
+    /// own totals without re-deriving this.
+    fn mark_to_market(&self, account: &Account, mark_price: i64) -> (i64, i64) {
+        let mut unrealized_pnl: i128 = 0;
+        let mut maintenance_requirement: i128 = 0;
+        for (&sym, &position) in &account.positions {
+            if position == 0 {
+                continue;
+            }
+            let entry = account.entry_price(sym).unwrap_or(mark_price);
+            unrealized_pnl += (mark_price - entry) as i128 * position as i128;
+            maintenance_requirement += position.unsigned_abs() as i128 * mark_price as i128
+                * self.config.maintenance_margin_pct as i128
+                / SCALE as i128;
+        }
+        let equity = account.available_balance as i128 + account.locked_margin as i128 + unrealized_pnl;
+        (equity as i64, maintenance_requirement as i64)
     }
 
     // -------------------------------------------------------------------
-    // Account Management Tests
+    // NON-MUTATING SIMULATION
     // -------------------------------------------------------------------
 
-    #[test]
-    fn test_add_funds_and_query() {
-        let g = setup_guardian();
-        let acc = g.get_account(1).unwrap();
-        assert_eq!(acc.available_balance, price(10_000));
-        assert_eq!(acc.locked_margin, 0);
-        assert_eq!(acc.total_equity(), price(10_000));
+    /// Dry-run `validate_and_lock` followed by `settle_fill_v2` — the full
+    /// lock-then-settle lifecycle of a hypothetical fill — against a cloned
+    /// sandbox, and report the resulting account's projected state. The
+    /// real `Guardian`, including `trader_id`'s actual account, is left
+    /// completely untouched; the clone (and anything it mutated) is
+    /// dropped once this returns.
+    ///
+    /// `limit_price` is the order's own limit (as passed to
+    /// `validate_and_lock`); `fill_price` is the hypothetical execution
+    /// price (as passed to `settle_fill_v2`) — the two may differ to
+    /// simulate price improvement or a worse taker fill.
+    ///
+    /// Returns whichever of the two calls rejects first, if either would.
+    pub fn simulate_fill(
+        &self,
+        trader_id: u32,
+        side: Side,
+        limit_price: i64,
+        fill_price: i64,
+        qty: u32,
+        symbol_id: u32,
+    ) -> Result<ProjectedAccount, GuardianReject> {
+        let mut sandbox = self.clone();
+        sandbox.validate_and_lock(trader_id, side, limit_price, qty, symbol_id)?;
+        sandbox.settle_fill_v2(trader_id, side, limit_price, fill_price, qty, symbol_id)?;
+
+        let is_liquidatable = sandbox.is_liquidatable(trader_id);
+        let account = sandbox.accounts.get(&trader_id).ok_or(GuardianReject::UnknownTrader { trader_id })?;
+        Ok(ProjectedAccount {
+            available_balance: account.available_balance,
+            locked_margin: account.locked_margin,
+            position: account.position(symbol_id),
+            is_liquidatable,
+        })
     }
 
-    #[test]
-    fn test_add_funds_float() {
-        let mut g = Guardian::new();
-        g.add_funds_float(1, 1000.50);
-        let acc = g.get_account(1).unwrap();
-        assert_eq!(acc.available_balance, 100_050_000_000); // $1000.50 × 10^8
+    /// Dry-run just `validate_and_lock` against a cloned sandbox — the
+    /// margin check and incremental-lock computation a resting order would
+    /// go through, without touching the real account. Returns the
+    /// incremental margin that would be locked, or the rejection
+    /// `validate_and_lock` would have produced.
+    pub fn would_pass(
+        &self,
+        trader_id: u32,
+        side: Side,
+        price: i64,
+        qty: u32,
+        symbol_id: u32,
+    ) -> Result<LockedMargin, GuardianReject> {
+        self.clone().validate_and_lock(trader_id, side, price, qty, symbol_id)
     }
 
-    #[test]
-    fn test_add_funds_incremental() {
-        let mut g = Guardian::new();
-        g.add_funds(1, price(1_000));
-        g.add_funds(1, price(500));
-        assert_eq!(g.get_account(1).unwrap().available_balance, price(1_500));
+    /// The mark price at which `symbol_id` would be force-closed under the
+    /// configured maintenance-margin ratio.
+    ///
+    /// Derived by solving `available + position × (liq - entry) = position
+    /// × liq × mm_pct` for `liq`, where `available` is the trader's
+    /// non-position equity (`available_balance + locked_margin`).
+    pub fn liquidation_price(&self, trader_id: u32, symbol_id: u32) -> Option<i64> {
+        self.margin_price_threshold(trader_id, symbol_id, self.config.maintenance_margin_pct)
+    }
+
+    /// The mark price at which `symbol_id`'s position would bring the
+    /// trader's equity to exactly zero — `liquidation_price` with
+    /// `mm_pct = 0`.
+    pub fn bankruptcy_price(&self, trader_id: u32, symbol_id: u32) -> Option<i64> {
+        self.margin_price_threshold(trader_id, symbol_id, 0)
+    }
+
+    /// Shared solver behind `liquidation_price`/`bankruptcy_price`:
+    /// `liq = (position × entry - available) × SCALE / (position × (SCALE - mm_pct))`,
+    /// the `SCALE`-aware rearrangement of the formula in their doc comments.
+    /// `available` is the trader's non-position equity backing `symbol_id`
+    /// — the shared `available_balance + locked_margin` under `Cross`, or
+    /// just that symbol's `isolated_allocations` entry under `Isolated`.
+    fn margin_price_threshold(&self, trader_id: u32, symbol_id: u32, mm_pct: i64) -> Option<i64> {
+        let account = self.accounts.get(&trader_id)?;
+        let position = account.position(symbol_id);
+        if position == 0 {
+            return None;
+        }
+        let entry = account.entry_price(symbol_id)?;
+        let available = match account.margin_mode {
+            MarginMode::Cross => account.available_balance as i128 + account.locked_margin as i128,
+            MarginMode::Isolated => account.isolated_allocations.get(&symbol_id).copied().unwrap_or(0) as i128,
+        };
+
+        let numerator = position as i128 * entry as i128 - available;
+        let denominator = position as i128 * (SCALE as i128 - mm_pct as i128);
+        if denominator == 0 {
+            return None;
+        }
+        i64::try_from(numerator * SCALE as i128 / denominator).ok()
     }
 
     // -------------------------------------------------------------------
-    // Kill Switch Tests
+    // FUNDING (PERPETUAL ACCRUAL)
     // -------------------------------------------------------------------
 
-    #[test]
+    /// Accrue one funding interval for every account holding a position in
+    /// `symbol_id`, marked at the Guardian's current `reference_price`.
+    ///
+    /// Each account's transfer is `position(symbol_id) × mark × rate_bps /
+    /// 10,000` (an `i128` intermediate guards against overflow, same as
+    /// `fee_on_notional`): a positive `rate_bps` debits longs (`position >
+    /// 0`) and credits shorts, a negative rate does the reverse. Every
+    /// transfer is cleared against `funding_pool` rather than between
+    /// accounts directly, so the sum of every account's equity plus
+    /// `funding_pool` is unchanged by construction — the testable global
+    /// conservation invariant the request calls for. Each account's net
+    /// lifetime transfer is tracked in `Account::cumulative_funding`.
+    ///
+    /// Under `MarginMode::Cross` the transfer hits `available_balance`, the
+    /// trader's shared pool. Under `MarginMode::Isolated` it instead hits
+    /// `isolated_allocations[symbol_id]` — the collateral actually backing
+    /// that position — the same pool `validate_and_lock`/`settle_fill_v2`
+    /// already route through for Isolated accounts.
+    ///
+    /// A no-op if no `reference_price` has been set yet. Idempotent per
+    /// `(symbol_id, now)`: returns `false` without transferring anything if
+    /// `now` is not strictly after the last timestamp this symbol was
+    /// funded at, so replaying the same interval (or an older one) twice
+    /// never double-charges. Returns `true` if funding was applied.
+    pub fn apply_funding(&mut self, symbol_id: u32, rate_bps: i64, now: i64) -> bool {
+        let mark_price = match self.reference_price {
+            Some(p) => p,
+            None => return false,
+        };
+        if let Some(&last) = self.last_funding_ts.get(&symbol_id) {
+            if now <= last {
+                return false;
+            }
+        }
+        self.last_funding_ts.insert(symbol_id, now);
+
+        for account in self.accounts.values_mut() {
+            let position = account.position(symbol_id);
+            if position == 0 {
+                continue;
+            }
+            let notional = position as i128 * mark_price as i128;
+            let transfer = (notional * rate_bps as i128 / 10_000) as i64;
+            match account.margin_mode {
+                MarginMode::Cross => account.available_balance -= transfer,
+                MarginMode::Isolated => {
+                    *account.isolated_allocations.entry(symbol_id).or_insert(0) -= transfer;
+                }
+            }
+            account.cumulative_funding += transfer;
+            self.funding_pool += transfer;
+        }
+        true
+    }
+
+    /// Current balance of the counterparty/insurance pool `apply_funding`
+    /// clears against.
+    pub fn funding_pool(&self) -> i64 {
+        self.funding_pool
+    }
+
+    // -------------------------------------------------------------------
+    // NET (HEDGED) MARGIN RESERVATION
+    // -------------------------------------------------------------------
+
+    /// The margin currently reserved for `trader_id`'s open orders on
+    /// `symbol_id` under the net-exposure model: `max(open_buy_notional,
+    /// open_sell_notional) / leverage`. `None` if the trader is unknown.
+    pub fn reserved_margin(&self, trader_id: u32, symbol_id: u32) -> Option<i64> {
+        let account = self.accounts.get(&trader_id)?;
+        let buy_notional = account.open_buy_notional.get(&symbol_id).copied().unwrap_or(0);
+        let sell_notional = account.open_sell_notional.get(&symbol_id).copied().unwrap_or(0);
+        Some(Self::net_margin_requirement(buy_notional, sell_notional, self.leverage_scaled(symbol_id)))
+    }
+
+    /// Set `symbol_id`'s leverage to `leverage` (e.g. `10.0` for 10x),
+    /// overriding `config.leverage` for every margin calculation on that
+    /// symbol from now on. Rejects with `GuardianReject::InvalidLeverage` if
+    /// `leverage` is zero or negative — every margin calculation divides by
+    /// the scaled factor, so zero would panic and negative would flip signs
+    /// and bypass risk checks — and with `GuardianReject::LeverageExceedsMax`
+    /// if `config.max_leverage` is set and `leverage` exceeds it. Existing
+    /// locked margin is untouched either way, since this only changes the
+    /// rate applied to orders placed after the call.
+    pub fn set_leverage(&mut self, symbol_id: u32, leverage: f64) -> Result<(), GuardianReject> {
+        let scaled = (leverage * SCALE as f64).round() as i64;
+        if scaled <= 0 {
+            return Err(GuardianReject::InvalidLeverage { requested: scaled });
+        }
+        if let Some(max) = self.config.max_leverage {
+            if scaled > max {
+                return Err(GuardianReject::LeverageExceedsMax { requested: scaled, max });
+            }
+        }
+        self.symbol_leverage.insert(symbol_id, scaled);
+        Ok(())
+    }
+
+    /// `symbol_id`'s effective leverage as a fixed-point factor (`10 ×
+    /// SCALE` = 10x): its `set_leverage` override if one exists, else
+    /// `config.leverage` scaled up to the same fixed-point representation.
+    fn leverage_scaled(&self, symbol_id: u32) -> i64 {
+        self.symbol_leverage.get(&symbol_id).copied().unwrap_or(self.config.leverage as i64 * SCALE)
+    }
+
+    /// The margin a pair of (buy, sell) directional notionals requires
+    /// under the hedged model: only the larger side is ever at risk of
+    /// fully filling, so the smaller side's notional is "free" — it's
+    /// already covered by the margin the larger side locked. `leverage`
+    /// is a fixed-point factor (`10 × SCALE` = 10x) from `leverage_scaled`.
+    #[inline]
+    fn net_margin_requirement(buy_notional: i64, sell_notional: i64, leverage_scaled: i64) -> i64 {
+        (buy_notional.max(sell_notional) as i128 * SCALE as i128 / leverage_scaled as i128) as i64
+    }
+
+    /// `notional × fee_bps / 10,000`, the same basis-point convention as
+    /// `matching::FeeSchedule`. Shared by `settle_fill_v2` (the actual
+    /// maker/taker fee charged on a fill) and `validate_and_lock` (the
+    /// worst-case taker fee reserved against up front).
+    #[inline]
+    fn fee_on_notional(notional: i64, fee_bps: i64) -> i64 {
+        (notional as i128 * fee_bps as i128 / 10_000) as i64
+    }
+
+    // -------------------------------------------------------------------
+    // INTERNAL HELPERS
+    // -------------------------------------------------------------------
+
+    /// Compute notional = price × qty in fixed-point.
+    /// This is exact integer math. No float. No rounding.
+    ///
+    /// Computed in `i128` so the multiplication itself can never wrap; the
+    /// `i64::try_from` is what actually catches a result too large for the
+    /// fixed-point scale the rest of the module works in.
+    #[inline]
+    fn compute_notional(price: i64, qty: u32) -> Result<i64, GuardianReject> {
+        // price is in fixed-point (scaled by 10^8), qty is raw.
+        // notional = price * qty (result is in fixed-point scale).
+        let notional = price as i128 * qty as i128;
+        i64::try_from(notional).map_err(|_| GuardianReject::NotionalOverflow { price, qty })
+    }
+}
+
+impl Default for Guardian {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// ===========================================================================
+// TESTS
+// ===========================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Side;
+
+    const S: i64 = crate::SCALE; // 10^8
+
+    fn price(v: i64) -> i64 { v * S }
+
+    fn setup_guardian() -> Guardian {
+        let mut g = Guardian::new();
+        // Trader 1: $10,000
+        g.add_funds(1, price(10_000));
+        // Trader 2: $5,000
+        g.add_funds(2, price(5_000));
+        g
+    }
+
+    // -------------------------------------------------------------------
+    // Account Management Tests
+    // -------------------------------------------------------------------
+
+    #[test]
+    fn test_add_funds_and_query() {
+        let g = setup_guardian();
+        let acc = g.get_account(1).unwrap();
+        assert_eq!(acc.available_balance, price(10_000));
+        assert_eq!(acc.locked_margin, 0);
+        assert_eq!(acc.total_equity(), price(10_000));
+    }
+
+    #[test]
+    fn test_add_funds_float() {
+        let mut g = Guardian::new();
+        g.add_funds_float(1, 1000.50);
+        let acc = g.get_account(1).unwrap();
+        assert_eq!(acc.available_balance, 100_050_000_000); // $1000.50 × 10^8
+    }
+
+    #[test]
+    fn test_add_funds_incremental() {
+        let mut g = Guardian::new();
+        g.add_funds(1, price(1_000));
+        g.add_funds(1, price(500));
+        assert_eq!(g.get_account(1).unwrap().available_balance, price(1_500));
+    }
+
+    // -------------------------------------------------------------------
+    // Kill Switch Tests
+    // -------------------------------------------------------------------
+
+    #[test]
     fn test_kill_switch_ban() {
         let mut g = setup_guardian();
         g.ban_trader(1);
@@ -669,6 +1671,148 @@ mod tests {
         assert!(!g.is_banned(1));
     }
 
+    // -------------------------------------------------------------------
+    // Automatic Risk Trigger Tests
+    // -------------------------------------------------------------------
+
+    #[test]
+    fn test_triggers_disabled_by_default() {
+        let mut g = setup_guardian();
+        g.charge_fee(1, price(9_999)); // Equity now $1 — would trip any drawdown limit.
+        assert_eq!(g.evaluate_risk_triggers(1, 0), None);
+        assert!(!g.is_banned(1));
+    }
+
+    #[test]
+    fn test_drawdown_breach_bans_trader() {
+        let mut g = Guardian::with_config(GuardianConfig {
+            max_drawdown_pct: Some(20_000_000), // 20%
+            ..GuardianConfig::default()
+        });
+        g.add_funds(1, price(10_000));
+        g.evaluate_risk_triggers(1, 0); // High-water mark is now $10,000.
+
+        // A $3,000 loss is a 30% drawdown from the $10,000 peak.
+        g.charge_fee(1, price(3_000));
+        let event = g.evaluate_risk_triggers(1, 0).expect("should trip drawdown");
+        assert_eq!(event, RiskEvent::DrawdownBreached {
+            trader_id: 1,
+            equity: price(7_000),
+            high_water_mark: price(10_000),
+        });
+        assert!(g.is_banned(1));
+    }
+
+    #[test]
+    fn test_drawdown_within_limit_is_not_breached() {
+        let mut g = Guardian::with_config(GuardianConfig {
+            max_drawdown_pct: Some(20_000_000), // 20%
+            ..GuardianConfig::default()
+        });
+        g.add_funds(1, price(10_000));
+        g.evaluate_risk_triggers(1, 0);
+
+        g.charge_fee(1, price(1_000)); // 10% drawdown — within the 20% limit.
+        assert_eq!(g.evaluate_risk_triggers(1, 0), None);
+        assert!(!g.is_banned(1));
+    }
+
+    #[test]
+    fn test_loss_limit_breach_bans_trader() {
+        let mut g = Guardian::with_config(GuardianConfig {
+            max_realized_loss: Some(price(500)),
+            ..GuardianConfig::default()
+        });
+        g.add_funds(1, price(10_000));
+        let mut acc = g.get_account(1).unwrap().clone();
+        acc.realized_pnl = -price(500);
+        g.restore_account(1, acc);
+
+        let event = g.evaluate_risk_triggers(1, 0).expect("should trip loss limit");
+        assert_eq!(event, RiskEvent::LossLimitBreached {
+            trader_id: 1,
+            realized_pnl: -price(500),
+            limit: price(500),
+        });
+        assert!(g.is_banned(1));
+    }
+
+    #[test]
+    fn test_position_limit_breach_bans_trader() {
+        let mut g = Guardian::with_config(GuardianConfig {
+            max_position_notional: Some(price(5_000)),
+            ..GuardianConfig::default()
+        });
+        g.add_funds(1, price(10_000));
+        let mut acc = g.get_account(1).unwrap().clone();
+        acc.positions.insert(0, 100);
+        acc.entry_prices.insert(0, price(100)); // Notional = 100 × $100 = $10,000.
+        g.restore_account(1, acc);
+
+        let event = g.evaluate_risk_triggers(1, 0).expect("should trip position limit");
+        assert_eq!(event, RiskEvent::PositionLimitBreached {
+            trader_id: 1,
+            symbol_id: 0,
+            notional: price(10_000),
+            limit: price(5_000),
+        });
+        assert!(g.is_banned(1));
+    }
+
+    #[test]
+    fn test_price_circuit_breaker_bans_all_traders() {
+        let mut g = Guardian::with_config(GuardianConfig {
+            circuit_breaker_price_move_pct: Some(15_000_000), // 15%
+            ..GuardianConfig::default()
+        });
+        g.add_funds(1, price(10_000));
+        g.add_funds(2, price(10_000));
+        g.set_reference_price(price(100));
+
+        // $100 -> $120 is a 20% move, past the 15% threshold.
+        let event = g.check_price_circuit_breaker(0, price(120)).expect("should trip");
+        assert_eq!(event, RiskEvent::PriceCircuitBreaker {
+            symbol_id: 0,
+            previous_price: price(100),
+            new_price: price(120),
+        });
+        assert!(g.is_banned(1));
+        assert!(g.is_banned(2));
+    }
+
+    #[test]
+    fn test_price_circuit_breaker_within_limit_does_not_trip() {
+        let mut g = Guardian::with_config(GuardianConfig {
+            circuit_breaker_price_move_pct: Some(15_000_000), // 15%
+            ..GuardianConfig::default()
+        });
+        g.set_reference_price(price(100));
+        assert_eq!(g.check_price_circuit_breaker(0, price(105)), None);
+    }
+
+    #[test]
+    fn test_exposure_circuit_breaker_bans_all_traders() {
+        let mut g = Guardian::with_config(GuardianConfig {
+            circuit_breaker_max_net_exposure: Some(price(5_000)),
+            ..GuardianConfig::default()
+        });
+        g.add_funds(1, price(100_000));
+        g.add_funds(2, price(100_000));
+        let mut acc1 = g.get_account(1).unwrap().clone();
+        acc1.positions.insert(0, 100); // Long 100 @ mark $100 = $10,000.
+        g.restore_account(1, acc1);
+
+        assert_eq!(g.aggregate_net_exposure(0, price(100)), price(10_000));
+        let event = g.check_exposure_circuit_breaker(0, price(100)).expect("should trip");
+        assert_eq!(event, RiskEvent::ExposureCircuitBreaker {
+            symbol_id: 0,
+            net_exposure: price(10_000),
+            limit: price(5_000),
+        });
+        assert!(g.is_banned(1));
+        assert!(g.is_banned(2));
+    }
+
     // -------------------------------------------------------------------
     // Margin Validation Tests
     // -------------------------------------------------------------------
@@ -712,6 +1856,96 @@ mod tests {
         }
     }
 
+    // -------------------------------------------------------------------
+    // Margin Mode (Cross vs. Isolated) Tests
+    // -------------------------------------------------------------------
+
+    #[test]
+    fn test_default_margin_mode_is_cross() {
+        let g = setup_guardian();
+        assert_eq!(g.margin_mode(1), Some(MarginMode::Cross));
+        assert_eq!(g.margin_mode(999), None);
+    }
+
+    #[test]
+    fn test_set_margin_mode_unknown_trader_is_noop() {
+        let mut g = setup_guardian();
+        g.set_margin_mode(999, MarginMode::Isolated);
+        assert_eq!(g.margin_mode(999), None);
+    }
+
+    #[test]
+    fn test_allocate_isolated_moves_from_available_balance() {
+        let mut g = setup_guardian();
+        g.set_margin_mode(1, MarginMode::Isolated);
+        g.allocate_isolated(1, 0, price(1_000)).unwrap();
+
+        let acc = g.get_account(1).unwrap();
+        assert_eq!(acc.available_balance, price(10_000) - price(1_000));
+        assert_eq!(g.isolated_allocation(1, 0), Some(price(1_000)));
+        assert_eq!(g.isolated_allocation(1, 1), Some(0));
+    }
+
+    #[test]
+    fn test_allocate_isolated_insufficient_balance() {
+        let mut g = setup_guardian();
+        let result = g.allocate_isolated(2, 0, price(10_000));
+        assert!(result.is_err());
+        match result.unwrap_err() {
+            GuardianReject::InsufficientMargin { required, available } => {
+                assert_eq!(required, price(10_000));
+                assert_eq!(available, price(5_000));
+            }
+            other => panic!("Expected InsufficientMargin, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_isolated_lock_checks_only_that_symbols_allocation() {
+        let mut g = setup_guardian();
+        g.set_margin_mode(1, MarginMode::Isolated);
+        g.allocate_isolated(1, 0, price(1_000)).unwrap();
+
+        // $1,000 allocated to symbol 0 covers a $1,000 order there...
+        assert!(g.validate_and_lock(1, Side::Buy, price(100), 10, 0).is_ok());
+        // ...but the same order on symbol 1 has no allocation to draw on,
+        // even though plenty of available_balance remains unallocated.
+        let result = g.validate_and_lock(1, Side::Buy, price(100), 10, 1);
+        assert!(result.is_err());
+        match result.unwrap_err() {
+            GuardianReject::InsufficientMargin { required, available } => {
+                assert_eq!(required, price(1_000));
+                assert_eq!(available, 0);
+            }
+            other => panic!("Expected InsufficientMargin, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_isolated_liquidation_does_not_net_across_symbols() {
+        let mut g = Guardian::with_config(GuardianConfig {
+            maintenance_margin_pct: 10_000_000, // 10%
+            ..GuardianConfig::default()
+        });
+        let mut acc = Account::new(0);
+        acc.margin_mode = MarginMode::Isolated;
+        acc.isolated_allocations.insert(0, price(100));
+        acc.positions.insert(0, 100);
+        acc.entry_prices.insert(0, price(100));
+        // A richly-margined position on symbol 1 that would mask symbol 0's
+        // blow-up if the engine netted across symbols the way Cross does.
+        acc.isolated_allocations.insert(1, price(100_000));
+        acc.positions.insert(1, 1_000);
+        acc.entry_prices.insert(1, price(100));
+        g.restore_account(1, acc);
+
+        // Symbol 0 alone: equity = $100 allocation, unrealized PnL = 0,
+        // maintenance requirement = 100 × $100 × 10% = $1,000 — underwater.
+        let report = g.check_liquidation(1, 0, price(100)).expect("should be liquidatable");
+        assert_eq!(report.positions_to_unwind, vec![(0, 100)]);
+        assert!(g.is_banned(1));
+    }
+
     // -------------------------------------------------------------------
     // Dynamic Volatility Band Tests
     // -------------------------------------------------------------------
@@ -809,7 +2043,7 @@ mod tests {
         // Lock: Buy 10 @ $100.
         g.validate_and_lock(1, Side::Buy, price(100), 10, 0).unwrap();
         // Fill: all 10 at exactly $100.
-        g.settle_fill_v2(1, Side::Buy, price(100), price(100), 10, 0);
+        g.settle_fill_v2(1, Side::Buy, price(100), price(100), 10, 0).unwrap();
 
         let acc = g.get_account(1).unwrap();
         assert_eq!(acc.locked_margin, 0); // Nothing locked anymore.
@@ -823,7 +2057,7 @@ mod tests {
         // Lock: Buy 10 @ $100.
         g.validate_and_lock(1, Side::Buy, price(100), 10, 0).unwrap();
         // Fill: all 10 at $95 (BETTER price!).
-        g.settle_fill_v2(1, Side::Buy, price(100), price(95), 10, 0);
+        g.settle_fill_v2(1, Side::Buy, price(100), price(95), 10, 0).unwrap();
 
         let acc = g.get_account(1).unwrap();
         assert_eq!(acc.locked_margin, 0);
@@ -840,7 +2074,7 @@ mod tests {
         g.validate_and_lock(1, Side::Buy, price(100), 10, 0).unwrap();
 
         // Fill: only 6 at $100.
-        g.settle_fill_v2(1, Side::Buy, price(100), price(100), 6, 0);
+        g.settle_fill_v2(1, Side::Buy, price(100), price(100), 6, 0).unwrap();
 
         let acc = g.get_account(1).unwrap();
         // 6 filled: $600 spent. 4 still locked: $400.
@@ -856,10 +2090,10 @@ mod tests {
         g.validate_and_lock(1, Side::Buy, price(100), 10, 0).unwrap();
 
         // Fill: 6 at $98 (price improvement).
-        g.settle_fill_v2(1, Side::Buy, price(100), price(98), 6, 0);
+        g.settle_fill_v2(1, Side::Buy, price(100), price(98), 6, 0).unwrap();
 
         // Cancel remaining 4.
-        g.unlock_margin(1, price(100), 4);
+        g.unlock_margin(1, Side::Buy, price(100), 4, 0).unwrap();
 
         let acc = g.get_account(1).unwrap();
         assert_eq!(acc.locked_margin, 0); // Everything settled.
@@ -876,7 +2110,7 @@ mod tests {
     fn test_position_tracking_buy() {
         let mut g = setup_guardian();
         g.validate_and_lock(1, Side::Buy, price(100), 10, 0).unwrap();
-        g.settle_fill_v2(1, Side::Buy, price(100), price(100), 10, 0);
+        g.settle_fill_v2(1, Side::Buy, price(100), price(100), 10, 0).unwrap();
 
         let acc = g.get_account(1).unwrap();
         assert_eq!(acc.position(0), 10); // Long 10 units.
@@ -888,11 +2122,11 @@ mod tests {
 
         // First buy 10 to have a position.
         g.validate_and_lock(1, Side::Buy, price(100), 10, 0).unwrap();
-        g.settle_fill_v2(1, Side::Buy, price(100), price(100), 10, 0);
+        g.settle_fill_v2(1, Side::Buy, price(100), price(100), 10, 0).unwrap();
 
         // Now sell 5.
         g.validate_and_lock(1, Side::Sell, price(100), 5, 0).unwrap();
-        g.settle_fill_v2(1, Side::Sell, price(100), price(100), 5, 0);
+        g.settle_fill_v2(1, Side::Sell, price(100), price(100), 5, 0).unwrap();
 
         let acc = g.get_account(1).unwrap();
         assert_eq!(acc.position(0), 5); // 10 - 5 = 5 remaining.
@@ -913,15 +2147,875 @@ mod tests {
         assert_eq!(g.get_account(1).unwrap().total_equity(), price(10_000));
 
         // Partial fill.
-        g.settle_fill_v2(1, Side::Buy, price(100), price(100), 5, 0);
+        g.settle_fill_v2(1, Side::Buy, price(100), price(100), 5, 0).unwrap();
         // Equity should have decreased by the cost of 5 units.
         // available = $9000, locked = $500, total = $9500.
         // The "missing" $500 is in the position.
         assert_eq!(g.get_account(1).unwrap().total_equity(), price(9_500));
 
         // Cancel remainder.
-        g.unlock_margin(1, price(100), 5);
+        g.unlock_margin(1, Side::Buy, price(100), 5, 0).unwrap();
         assert_eq!(g.get_account(1).unwrap().total_equity(), price(9_500));
         assert_eq!(g.get_account(1).unwrap().locked_margin, 0);
     }
+
+    // -------------------------------------------------------------------
+    // Maker/Taker Fee Tests
+    // -------------------------------------------------------------------
+
+    fn setup_fee_guardian(maker_fee_bps: i64, taker_fee_bps: i64) -> Guardian {
+        let mut g = Guardian::with_config(GuardianConfig {
+            maker_fee_bps,
+            taker_fee_bps,
+            ..GuardianConfig::default()
+        });
+        g.add_funds(1, price(10_000));
+        g
+    }
+
+    #[test]
+    fn test_settle_fill_v2_charges_maker_fee_when_filled_at_own_price() {
+        let mut g = setup_fee_guardian(10, 25); // 0.10% maker / 0.25% taker.
+        g.validate_and_lock(1, Side::Buy, price(100), 10, 0).unwrap();
+        // Filled exactly at the order's own limit price — a resting maker fill.
+        g.settle_fill_v2(1, Side::Buy, price(100), price(100), 10, 0).unwrap();
+
+        let acc = g.get_account(1).unwrap();
+        // Notional $1000 × 0.10% = $1.
+        assert_eq!(acc.fees_paid, price(1));
+        assert_eq!(acc.available_balance, price(9_000) - price(1));
+    }
+
+    #[test]
+    fn test_settle_fill_v2_charges_taker_fee_when_crossing_the_book() {
+        let mut g = setup_fee_guardian(10, 25); // 0.10% maker / 0.25% taker.
+        // Willing to pay up to $105, but the fill prints at the better $100
+        // resting price — crossing the book makes this the taker.
+        g.validate_and_lock(1, Side::Buy, price(105), 10, 0).unwrap();
+        g.settle_fill_v2(1, Side::Buy, price(105), price(100), 10, 0).unwrap();
+
+        let acc = g.get_account(1).unwrap();
+        // Notional $1000 × 0.25% = $2.50.
+        assert_eq!(acc.fees_paid, price(1000) * 25 / 10_000);
+        assert!(acc.fees_paid > 0);
+    }
+
+    #[test]
+    fn test_settle_fill_v2_sell_maker_at_own_price() {
+        let mut g = setup_fee_guardian(10, 25);
+        g.validate_and_lock(1, Side::Sell, price(100), 10, 0).unwrap();
+        // Filled exactly at the sell's own limit price — maker.
+        g.settle_fill_v2(1, Side::Sell, price(100), price(100), 10, 0).unwrap();
+
+        let acc = g.get_account(1).unwrap();
+        assert_eq!(acc.fees_paid, price(1)); // $1000 × 0.10%.
+    }
+
+    #[test]
+    fn test_negative_maker_fee_bps_is_a_rebate() {
+        let mut g = setup_fee_guardian(-10, 0); // Maker rebate of 0.10%.
+        g.validate_and_lock(1, Side::Buy, price(100), 10, 0).unwrap();
+        g.settle_fill_v2(1, Side::Buy, price(100), price(100), 10, 0).unwrap();
+
+        let acc = g.get_account(1).unwrap();
+        assert_eq!(acc.fees_paid, -price(1));
+        assert_eq!(acc.available_balance, price(9_000) + price(1));
+    }
+
+    #[test]
+    fn test_zero_fee_config_matches_pre_fee_behavior() {
+        let mut g = setup_guardian();
+        g.validate_and_lock(1, Side::Buy, price(100), 10, 0).unwrap();
+        g.settle_fill_v2(1, Side::Buy, price(100), price(100), 10, 0).unwrap();
+
+        let acc = g.get_account(1).unwrap();
+        assert_eq!(acc.fees_paid, 0);
+        assert_eq!(acc.available_balance, price(9_000));
+    }
+
+    #[test]
+    fn test_validate_and_lock_rejects_margin_plus_worst_case_taker_fee() {
+        // $1,000 available. A $1,000 buy alone would just barely lock, but
+        // the 1% worst-case taker fee ($10) pushes it over the edge.
+        let mut g = Guardian::with_config(GuardianConfig {
+            taker_fee_bps: 100, // 1%.
+            ..GuardianConfig::default()
+        });
+        g.add_funds(1, price(1_000));
+
+        let result = g.validate_and_lock(1, Side::Buy, price(100), 10, 0);
+        match result.unwrap_err() {
+            GuardianReject::InsufficientMargin { required, available } => {
+                assert_eq!(required, price(1_000) + price(10));
+                assert_eq!(available, price(1_000));
+            }
+            other => panic!("Expected InsufficientMargin, got {:?}", other),
+        }
+    }
+
+    // -------------------------------------------------------------------
+    // Leverage & Liquidation Tests
+    // -------------------------------------------------------------------
+
+    fn setup_leveraged_guardian(leverage: u32) -> Guardian {
+        let mut g = Guardian::with_config(GuardianConfig {
+            leverage,
+            ..GuardianConfig::default()
+        });
+        g.add_funds(1, price(10_000));
+        g
+    }
+
+    #[test]
+    fn test_leverage_reduces_locked_margin() {
+        let mut g = setup_leveraged_guardian(5);
+        // Notional = $100 × 10 = $1000. At 5x leverage, margin = $200.
+        let locked = g.validate_and_lock(1, Side::Buy, price(100), 10, 0).unwrap();
+        assert_eq!(locked, price(200));
+
+        let acc = g.get_account(1).unwrap();
+        assert_eq!(acc.locked_margin, price(200));
+        assert_eq!(acc.available_balance, price(10_000) - price(200));
+    }
+
+    #[test]
+    fn test_settle_fill_v2_scales_buy_cash_effect_by_leverage() {
+        let mut g = setup_leveraged_guardian(5);
+        // Notional = $100 × 10 = $1000. At 5x leverage, only $200 is ever
+        // locked — settlement must debit that same $200, not the full
+        // $1000 unleveraged notional.
+        g.validate_and_lock(1, Side::Buy, price(100), 10, 0).unwrap();
+        g.settle_fill_v2(1, Side::Buy, price(100), price(100), 10, 0).unwrap();
+
+        let acc = g.get_account(1).unwrap();
+        assert_eq!(acc.locked_margin, 0);
+        assert_eq!(acc.available_balance, price(10_000) - price(200));
+        assert_eq!(acc.position(0), 10);
+    }
+
+    #[test]
+    fn test_settle_fill_v2_scales_sell_proceeds_by_leverage() {
+        let mut g = setup_leveraged_guardian(5);
+        // Opening a short: notional = $1000, locked margin at 5x = $200.
+        // Proceeds credited at settlement must be leverage-scaled too, or
+        // the account would be credited 5x what it ever had at risk.
+        g.validate_and_lock(1, Side::Sell, price(100), 10, 0).unwrap();
+        g.settle_fill_v2(1, Side::Sell, price(100), price(100), 10, 0).unwrap();
+
+        let acc = g.get_account(1).unwrap();
+        assert_eq!(acc.locked_margin, 0);
+        // Released ($200) + leveraged proceeds ($200) = $400 back on top of
+        // the $9,800 left over after the lock.
+        assert_eq!(acc.available_balance, price(10_000) - price(200) + price(400));
+        assert_eq!(acc.position(0), -10);
+    }
+
+    #[test]
+    fn test_leverage_unlock_round_trip_is_consistent() {
+        let mut g = setup_leveraged_guardian(5);
+        g.validate_and_lock(1, Side::Buy, price(100), 10, 0).unwrap();
+        g.unlock_margin(1, Side::Buy, price(100), 10, 0).unwrap();
+
+        let acc = g.get_account(1).unwrap();
+        assert_eq!(acc.locked_margin, 0);
+        assert_eq!(acc.available_balance, price(10_000));
+    }
+
+    #[test]
+    fn test_set_leverage_overrides_config_default_per_symbol() {
+        let mut g = setup_leveraged_guardian(1); // Config default stays 1x.
+        g.set_leverage(0, 10.0).unwrap();
+
+        // Notional = $100 × 10 = $1000. At the symbol's 10x override, margin = $100.
+        let locked = g.validate_and_lock(1, Side::Buy, price(100), 10, 0).unwrap();
+        assert_eq!(locked, price(100));
+
+        // A different symbol keeps the 1x config default.
+        let locked_other_symbol = g.validate_and_lock(1, Side::Buy, price(100), 10, 1).unwrap();
+        assert_eq!(locked_other_symbol, price(1_000));
+    }
+
+    #[test]
+    fn test_set_leverage_fractional() {
+        let mut g = setup_leveraged_guardian(1);
+        g.set_leverage(0, 2.5).unwrap();
+
+        // Notional = $100 × 10 = $1000. At 2.5x leverage, margin = $400.
+        let locked = g.validate_and_lock(1, Side::Buy, price(100), 10, 0).unwrap();
+        assert_eq!(locked, price(400));
+    }
+
+    #[test]
+    fn test_set_leverage_rejects_above_configured_cap() {
+        let mut g = Guardian::with_config(GuardianConfig {
+            max_leverage: Some(20 * S), // 20x
+            ..GuardianConfig::default()
+        });
+
+        let result = g.set_leverage(0, 25.0);
+        assert!(result.is_err());
+        match result.unwrap_err() {
+            GuardianReject::LeverageExceedsMax { requested, max } => {
+                assert_eq!(requested, 25 * S);
+                assert_eq!(max, 20 * S);
+            }
+            other => panic!("Expected LeverageExceedsMax, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_set_leverage_rejects_zero() {
+        let mut g = setup_leveraged_guardian(1);
+        let result = g.set_leverage(0, 0.0);
+        match result.unwrap_err() {
+            GuardianReject::InvalidLeverage { requested } => assert_eq!(requested, 0),
+            other => panic!("Expected InvalidLeverage, got {:?}", other),
+        }
+        // The rejected call must not have taken effect: the symbol still
+        // falls back to the config default, not a leverage of zero (which
+        // would divide-by-zero-panic on the very next margin check).
+        let locked = g.validate_and_lock(1, Side::Buy, price(100), 10, 0).unwrap();
+        assert_eq!(locked, price(1_000));
+    }
+
+    #[test]
+    fn test_set_leverage_rejects_negative() {
+        let mut g = setup_leveraged_guardian(1);
+        let result = g.set_leverage(0, -5.0);
+        match result.unwrap_err() {
+            GuardianReject::InvalidLeverage { requested } => assert_eq!(requested, -5 * S),
+            other => panic!("Expected InvalidLeverage, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_leverage_round_trip_consistent_with_symbol_override() {
+        let mut g = setup_leveraged_guardian(1);
+        g.set_leverage(0, 5.0).unwrap();
+
+        g.validate_and_lock(1, Side::Buy, price(100), 10, 0).unwrap();
+        g.unlock_margin(1, Side::Buy, price(100), 10, 0).unwrap();
+
+        let acc = g.get_account(1).unwrap();
+        assert_eq!(acc.locked_margin, 0);
+        assert_eq!(acc.available_balance, price(10_000));
+    }
+
+    #[test]
+    fn test_entry_price_weighted_average_on_growing_position() {
+        let mut g = setup_guardian();
+        g.validate_and_lock(1, Side::Buy, price(100), 10, 0).unwrap();
+        g.settle_fill_v2(1, Side::Buy, price(100), price(100), 10, 0).unwrap();
+        // $105 stays within the 10% volatility band around the new $100 reference.
+        g.validate_and_lock(1, Side::Buy, price(105), 10, 0).unwrap();
+        g.settle_fill_v2(1, Side::Buy, price(105), price(105), 10, 0).unwrap();
+
+        let acc = g.get_account(1).unwrap();
+        assert_eq!(acc.position(0), 20);
+        // (10×100 + 10×105) / 20 = 102.5.
+        assert_eq!(acc.entry_price(0), Some(price(100) + price(5) / 2));
+    }
+
+    #[test]
+    fn test_entry_price_unchanged_on_reducing_fill() {
+        let mut g = setup_guardian();
+        g.validate_and_lock(1, Side::Buy, price(100), 10, 0).unwrap();
+        g.settle_fill_v2(1, Side::Buy, price(100), price(100), 10, 0).unwrap();
+
+        g.validate_and_lock(1, Side::Sell, price(102), 5, 0).unwrap();
+        g.settle_fill_v2(1, Side::Sell, price(102), price(102), 5, 0).unwrap();
+
+        let acc = g.get_account(1).unwrap();
+        assert_eq!(acc.position(0), 5);
+        assert_eq!(acc.entry_price(0), Some(price(100))); // Unchanged by a reduction.
+    }
+
+    #[test]
+    fn test_realized_pnl_on_partial_reduction() {
+        let mut g = setup_guardian();
+        g.validate_and_lock(1, Side::Buy, price(100), 10, 0).unwrap();
+        g.settle_fill_v2(1, Side::Buy, price(100), price(100), 10, 0).unwrap();
+
+        // Sell 5 @ $110: closes 5 units bought at $100 → +$50 realized.
+        g.validate_and_lock(1, Side::Sell, price(110), 5, 0).unwrap();
+        g.settle_fill_v2(1, Side::Sell, price(110), price(110), 5, 0).unwrap();
+
+        let acc = g.get_account(1).unwrap();
+        assert_eq!(acc.realized_pnl, price(50));
+        assert_eq!(acc.position(0), 5);
+        assert_eq!(acc.entry_price(0), Some(price(100))); // Remainder keeps its cost basis.
+    }
+
+    #[test]
+    fn test_realized_pnl_on_full_close_clears_entry_price() {
+        let mut g = setup_guardian();
+        g.validate_and_lock(1, Side::Buy, price(100), 10, 0).unwrap();
+        g.settle_fill_v2(1, Side::Buy, price(100), price(100), 10, 0).unwrap();
+
+        g.validate_and_lock(1, Side::Sell, price(90), 10, 0).unwrap();
+        g.settle_fill_v2(1, Side::Sell, price(90), price(90), 10, 0).unwrap();
+
+        let acc = g.get_account(1).unwrap();
+        // Closed 10 units bought at $100, sold at $90 → -$100 realized.
+        assert_eq!(acc.realized_pnl, -price(100));
+        assert_eq!(acc.position(0), 0);
+        assert_eq!(acc.entry_price(0), None);
+    }
+
+    #[test]
+    fn test_realized_pnl_on_position_flip() {
+        let mut g = setup_guardian();
+        g.validate_and_lock(1, Side::Buy, price(100), 10, 0).unwrap();
+        g.settle_fill_v2(1, Side::Buy, price(100), price(100), 10, 0).unwrap();
+
+        // Sell 15 @ $105: closes the 10-long at $105 (+$50 realized), then
+        // opens a fresh 5-short with its own entry price.
+        g.validate_and_lock(1, Side::Sell, price(105), 15, 0).unwrap();
+        g.settle_fill_v2(1, Side::Sell, price(105), price(105), 15, 0).unwrap();
+
+        let acc = g.get_account(1).unwrap();
+        assert_eq!(acc.realized_pnl, price(50));
+        assert_eq!(acc.position(0), -5);
+        assert_eq!(acc.entry_price(0), Some(price(105)));
+    }
+
+    #[test]
+    fn test_check_liquidation_flags_underwater_account_and_bans() {
+        let mut g = Guardian::with_config(GuardianConfig {
+            maintenance_margin_pct: 10_000_000, // 10%
+            ..GuardianConfig::default()
+        });
+        let mut acc = Account::new(price(50));
+        acc.positions.insert(0, 100);
+        acc.entry_prices.insert(0, price(100));
+        g.restore_account(1, acc);
+
+        // Maintenance requirement = 100 × $100 × 10% = $1000, equity = $50.
+        let report = g.check_liquidation(1, 0, price(100)).expect("should be liquidatable");
+        assert_eq!(report.trader_id, 1);
+        assert_eq!(report.positions_to_unwind, vec![(0, 100)]);
+        assert!(g.is_banned(1));
+    }
+
+    #[test]
+    fn test_check_liquidation_leaves_well_margined_account_alone() {
+        let mut g = Guardian::with_config(GuardianConfig::default());
+        let mut acc = Account::new(price(100_000));
+        acc.positions.insert(0, 100);
+        acc.entry_prices.insert(0, price(100));
+        g.restore_account(1, acc);
+
+        assert!(g.check_liquidation(1, 0, price(100)).is_none());
+        assert!(!g.is_banned(1));
+    }
+
+    #[test]
+    fn test_liquidation_price_and_bankruptcy_price() {
+        let mut g = Guardian::with_config(GuardianConfig {
+            maintenance_margin_pct: 10_000_000, // 10%
+            ..GuardianConfig::default()
+        });
+        let mut acc = Account::new(price(1_900));
+        acc.positions.insert(0, 100);
+        acc.entry_prices.insert(0, price(100));
+        g.restore_account(1, acc);
+
+        assert_eq!(g.liquidation_price(1, 0), Some(price(90)));
+        assert_eq!(g.bankruptcy_price(1, 0), Some(price(81)));
+    }
+
+    #[test]
+    fn test_liquidation_price_none_without_open_position() {
+        let g = setup_guardian();
+        assert_eq!(g.liquidation_price(1, 0), None);
+        assert_eq!(g.bankruptcy_price(1, 0), None);
+    }
+
+    #[test]
+    fn test_is_liquidatable_marks_underwater_position_against_reference_price() {
+        let mut g = Guardian::with_config(GuardianConfig {
+            maintenance_margin_pct: 10_000_000, // 10%
+            ..GuardianConfig::default()
+        });
+        let mut acc = Account::new(price(50));
+        acc.positions.insert(0, 100);
+        acc.entry_prices.insert(0, price(100));
+        g.restore_account(1, acc);
+        g.set_reference_price(price(100));
+
+        // Maintenance requirement = 100 × $100 × 10% = $1000, equity = $50.
+        assert!(g.is_liquidatable(1));
+    }
+
+    #[test]
+    fn test_is_liquidatable_false_for_well_margined_account() {
+        let mut g = setup_guardian();
+        g.validate_and_lock(1, Side::Buy, price(100), 10, 0).unwrap();
+        g.settle_fill_v2(1, Side::Buy, price(100), price(100), 10, 0).unwrap();
+        g.set_reference_price(price(100));
+
+        assert!(!g.is_liquidatable(1));
+    }
+
+    #[test]
+    fn test_is_liquidatable_false_without_reference_price() {
+        let mut g = Guardian::with_config(GuardianConfig::default());
+        let mut acc = Account::new(price(50));
+        acc.positions.insert(0, 100);
+        acc.entry_prices.insert(0, price(100));
+        g.restore_account(1, acc);
+
+        assert!(!g.is_liquidatable(1));
+    }
+
+    #[test]
+    fn test_is_liquidatable_false_for_unknown_trader() {
+        let mut g = setup_guardian();
+        g.set_reference_price(price(100));
+        assert!(!g.is_liquidatable(99));
+    }
+
+    #[test]
+    fn test_liquidate_force_closes_position_and_realizes_pnl() {
+        let mut g = setup_guardian();
+        g.validate_and_lock(1, Side::Buy, price(100), 10, 0).unwrap();
+        g.settle_fill_v2(1, Side::Buy, price(100), price(100), 10, 0).unwrap();
+        g.set_reference_price(price(80));
+
+        let report = g.liquidate(1).expect("should unwind the open position");
+        assert_eq!(report.trader_id, 1);
+        assert_eq!(report.positions_to_unwind, vec![(0, 10)]);
+
+        let acc = g.get_account(1).unwrap();
+        // Bought 10 @ $100, closed at $80 → -$200 realized.
+        assert_eq!(acc.realized_pnl, -price(200));
+        assert_eq!(acc.position(0), 0);
+        assert_eq!(acc.entry_price(0), None);
+        assert_eq!(acc.locked_margin, 0);
+        // Fully filled, so locked_margin was already released to 0 by
+        // settle_fill_v2; available_balance is $9000 going in, minus the
+        // $200 realized loss from closing at $80.
+        assert_eq!(acc.available_balance, price(9_000) - price(200));
+        assert!(g.is_banned(1));
+    }
+
+    #[test]
+    fn test_liquidate_of_flat_account_is_a_no_op() {
+        let mut g = setup_guardian();
+        g.set_reference_price(price(100));
+
+        assert_eq!(g.liquidate(1), None);
+        assert!(!g.is_banned(1));
+    }
+
+    #[test]
+    fn test_liquidate_clears_maintenance_requirement() {
+        let mut g = Guardian::with_config(GuardianConfig {
+            maintenance_margin_pct: 10_000_000, // 10%
+            ..GuardianConfig::default()
+        });
+        let mut acc = Account::new(price(50));
+        acc.positions.insert(0, 100);
+        acc.entry_prices.insert(0, price(100));
+        g.restore_account(1, acc);
+        g.set_reference_price(price(100));
+
+        g.liquidate(1).expect("should unwind the open position");
+
+        // Fully unwound: a follow-up liquidation attempt finds nothing left.
+        assert_eq!(g.liquidate(1), None);
+        assert!(!g.is_liquidatable(1));
+    }
+
+    #[test]
+    fn test_liquidate_clears_open_order_notional_so_a_later_cancel_cannot_double_credit() {
+        let mut g = setup_guardian();
+        g.validate_and_lock(1, Side::Buy, price(100), 10, 0).unwrap();
+        g.settle_fill_v2(1, Side::Buy, price(100), price(100), 10, 0).unwrap();
+        // A still-resting sell, never filled or cancelled before liquidation.
+        g.validate_and_lock(1, Side::Sell, price(110), 5, 0).unwrap();
+        g.set_reference_price(price(80));
+
+        g.liquidate(1).expect("should unwind the open position");
+        let equity_after_liquidation = g.get_account(1).unwrap().total_equity();
+
+        // The matching engine later notifies the Guardian that the resting
+        // sell (placed before liquidation) was cancelled. Without clearing
+        // open_sell_notional, this would compute a nonzero `released`
+        // against margin `liquidate` already paid out in full, crediting
+        // available_balance a second time and driving locked_margin negative.
+        g.unlock_margin(1, Side::Sell, price(110), 5, 0).unwrap();
+
+        let acc = g.get_account(1).unwrap();
+        assert_eq!(acc.locked_margin, 0);
+        assert_eq!(acc.total_equity(), equity_after_liquidation);
+    }
+
+    #[test]
+    fn test_liquidate_settles_isolated_allocations_instead_of_available_balance() {
+        let mut g = Guardian::with_config(GuardianConfig {
+            maintenance_margin_pct: 10_000_000, // 10%
+            ..GuardianConfig::default()
+        });
+        let mut acc = Account::new(500);
+        acc.margin_mode = MarginMode::Isolated;
+        // $1,000 walled off to symbol 0, backing a long 10 @ $100.
+        acc.isolated_allocations.insert(0, price(1_000));
+        acc.positions.insert(0, 10);
+        acc.entry_prices.insert(0, price(100));
+        // A second symbol's isolated allocation, untouched by symbol 0's
+        // blow-up — this must survive the liquidation unscathed.
+        acc.isolated_allocations.insert(1, price(777));
+        g.restore_account(1, acc);
+        g.set_reference_price(price(80));
+
+        // realized PnL on symbol 0 = (80 - 100) * 10 = -$200.
+        let report = g.liquidate(1).expect("should unwind the underwater position");
+        assert_eq!(report.positions_to_unwind, vec![(0, 10)]);
+
+        let acc = g.get_account(1).unwrap();
+        // Symbol 0's pool is fully settled and swept back, not stranded.
+        assert_eq!(acc.isolated_allocations.get(&0), Some(&0));
+        assert_eq!(acc.available_balance, 500 + price(1_000) - price(200));
+        assert_eq!(acc.locked_margin, 0);
+        // Symbol 1's isolated collateral is untouched — it never had a
+        // position to unwind.
+        assert_eq!(acc.isolated_allocations.get(&1), Some(&price(777)));
+        assert!(g.is_banned(1));
+    }
+
+    // -------------------------------------------------------------------
+    // Overflow-Safety Tests
+    // -------------------------------------------------------------------
+
+    #[test]
+    fn test_validate_and_lock_rejects_notional_overflow() {
+        let mut g = setup_guardian();
+        let result = g.validate_and_lock(1, Side::Buy, i64::MAX, 2, 0);
+        match result.unwrap_err() {
+            GuardianReject::NotionalOverflow { price, qty } => {
+                assert_eq!(price, i64::MAX);
+                assert_eq!(qty, 2);
+            }
+            other => panic!("Expected NotionalOverflow, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_settle_fill_v2_rejects_notional_overflow_without_mutating_account() {
+        let mut g = setup_guardian();
+        g.validate_and_lock(1, Side::Buy, price(100), 10, 0).unwrap();
+        let before = g.get_account(1).unwrap().clone();
+
+        let result = g.settle_fill_v2(1, Side::Buy, price(100), i64::MAX, 10, 0);
+        assert!(matches!(result, Err(GuardianReject::NotionalOverflow { .. })));
+
+        let after = g.get_account(1).unwrap();
+        assert_eq!(after.available_balance, before.available_balance);
+        assert_eq!(after.locked_margin, before.locked_margin);
+        assert_eq!(after.positions, before.positions);
+    }
+
+    #[test]
+    fn test_unlock_margin_rejects_notional_overflow() {
+        let mut g = setup_guardian();
+        let result = g.unlock_margin(1, Side::Buy, i64::MAX, 2, 0);
+        assert!(matches!(result, Err(GuardianReject::NotionalOverflow { .. })));
+    }
+
+    // -------------------------------------------------------------------
+    // Net (Hedged) Margin Reservation Tests
+    // -------------------------------------------------------------------
+
+    #[test]
+    fn test_opposing_order_is_free_when_it_does_not_exceed_existing_exposure() {
+        let mut g = setup_guardian();
+        // Buy 10 @ $100 locks the full $1000 (no offsetting sell yet).
+        let buy_locked = g.validate_and_lock(1, Side::Buy, price(100), 10, 0).unwrap();
+        assert_eq!(buy_locked, price(1_000));
+
+        // A resting sell of half the size is already covered by the buy's
+        // margin — net exposure (max(buy, sell)) doesn't grow, so this
+        // locks nothing extra.
+        let sell_locked = g.validate_and_lock(1, Side::Sell, price(100), 5, 0).unwrap();
+        assert_eq!(sell_locked, 0);
+
+        let acc = g.get_account(1).unwrap();
+        assert_eq!(acc.locked_margin, price(1_000));
+        assert_eq!(g.reserved_margin(1, 0), Some(price(1_000)));
+    }
+
+    #[test]
+    fn test_opposing_order_exceeding_existing_exposure_locks_only_the_excess() {
+        let mut g = setup_guardian();
+        g.validate_and_lock(1, Side::Buy, price(100), 10, 0).unwrap(); // $1000 buy exposure.
+
+        // A 15-unit sell ($1500) exceeds the $1000 buy exposure — only the
+        // $500 excess needs fresh margin.
+        let sell_locked = g.validate_and_lock(1, Side::Sell, price(100), 15, 0).unwrap();
+        assert_eq!(sell_locked, price(500));
+
+        let acc = g.get_account(1).unwrap();
+        assert_eq!(acc.locked_margin, price(1_500));
+        assert_eq!(g.reserved_margin(1, 0), Some(price(1_500)));
+    }
+
+    #[test]
+    fn test_cancelling_the_smaller_side_releases_no_margin() {
+        let mut g = setup_guardian();
+        g.validate_and_lock(1, Side::Buy, price(100), 10, 0).unwrap(); // $1000 buy.
+        g.validate_and_lock(1, Side::Sell, price(100), 15, 0).unwrap(); // $1500 sell dominates.
+
+        // Cancelling the smaller (dominated) buy side doesn't free margin —
+        // the $1500 sell still needs the full $1500.
+        g.unlock_margin(1, Side::Buy, price(100), 10, 0).unwrap();
+        assert_eq!(g.get_account(1).unwrap().locked_margin, price(1_500));
+
+        // Cancelling the dominant sell side now releases everything.
+        g.unlock_margin(1, Side::Sell, price(100), 15, 0).unwrap();
+        assert_eq!(g.get_account(1).unwrap().locked_margin, 0);
+    }
+
+    #[test]
+    fn test_net_margin_tracked_independently_per_symbol() {
+        let mut g = setup_guardian();
+        g.validate_and_lock(1, Side::Buy, price(100), 10, 0).unwrap(); // Symbol 0: $1000.
+        g.validate_and_lock(1, Side::Buy, price(50), 10, 1).unwrap(); // Symbol 1: $500.
+
+        assert_eq!(g.reserved_margin(1, 0), Some(price(1_000)));
+        assert_eq!(g.reserved_margin(1, 1), Some(price(500)));
+        assert_eq!(g.get_account(1).unwrap().locked_margin, price(1_500));
+    }
+
+    #[test]
+    fn test_reserved_margin_unknown_trader_is_none() {
+        let g = setup_guardian();
+        assert_eq!(g.reserved_margin(999, 0), None);
+    }
+
+    #[test]
+    fn test_two_sided_market_making_quote_only_margins_the_dominant_side() {
+        // A market maker quoting both sides of the book: growing either
+        // quote only locks fresh margin once it overtakes the other side,
+        // never the sum of both — see `net_margin_requirement`.
+        let mut g = setup_guardian();
+        g.validate_and_lock(1, Side::Buy, price(100), 20, 0).unwrap(); // $2000 buy.
+        g.validate_and_lock(1, Side::Sell, price(100), 20, 0).unwrap(); // $2000 sell: fully hedged, locks nothing extra.
+        assert_eq!(g.get_account(1).unwrap().locked_margin, price(2_000));
+
+        // Widen the buy quote to 30 units ($3000): now the dominant side,
+        // locks only the $1000 it adds on top of the sell.
+        let extra = g.validate_and_lock(1, Side::Buy, price(100), 10, 0).unwrap();
+        assert_eq!(extra, price(1_000));
+        assert_eq!(g.get_account(1).unwrap().locked_margin, price(3_000));
+        assert_eq!(g.reserved_margin(1, 0), Some(price(3_000)));
+
+        // Cancel 10 units off the now-dominant buy side: back down to $2000,
+        // still fully covering the untouched $2000 sell.
+        g.unlock_margin(1, Side::Buy, price(100), 10, 0).unwrap();
+        assert_eq!(g.get_account(1).unwrap().locked_margin, price(2_000));
+        assert_eq!(g.reserved_margin(1, 0), Some(price(2_000)));
+    }
+
+    // -------------------------------------------------------------------
+    // Non-Mutating Simulation Tests
+    // -------------------------------------------------------------------
+
+    #[test]
+    fn test_simulate_fill_projects_state_without_mutating_the_real_account() {
+        let mut g = setup_guardian();
+        let before = g.get_account(1).unwrap().clone();
+
+        let projected = g.simulate_fill(1, Side::Buy, price(100), price(100), 10, 0).unwrap();
+        assert_eq!(projected.available_balance, price(9_000));
+        assert_eq!(projected.locked_margin, 0);
+        assert_eq!(projected.position, 10);
+        assert!(!projected.is_liquidatable);
+
+        // The real account (and the Guardian's reference price) are untouched.
+        let after = g.get_account(1).unwrap();
+        assert_eq!(after.available_balance, before.available_balance);
+        assert_eq!(after.locked_margin, before.locked_margin);
+        assert_eq!(after.position(0), 0);
+        assert_eq!(g.reference_price(), None);
+    }
+
+    #[test]
+    fn test_simulate_fill_propagates_insufficient_margin_rejection() {
+        let mut g = setup_guardian();
+        // Trader 2 only has $5000; a $10,000 buy would be rejected.
+        let result = g.simulate_fill(2, Side::Buy, price(100), price(100), 100, 0);
+        assert!(matches!(result, Err(GuardianReject::InsufficientMargin { .. })));
+        assert_eq!(g.get_account(2).unwrap().available_balance, price(5_000));
+    }
+
+    #[test]
+    fn test_simulate_fill_flags_projected_liquidation() {
+        let mut g = Guardian::with_config(GuardianConfig {
+            maintenance_margin_pct: 10_000_000, // 10%
+            ..GuardianConfig::default()
+        });
+        g.add_funds(1, price(1_050));
+
+        // Buying 10 units @ $100 locks $1,000 of the $1,050 available; the
+        // $50 left over, marked back at its own fill price, falls well
+        // short of the $100 maintenance requirement (10 × $100 × 10%).
+        let projected = g.simulate_fill(1, Side::Buy, price(100), price(100), 10, 0).unwrap();
+        assert!(projected.is_liquidatable);
+    }
+
+    #[test]
+    fn test_would_pass_returns_incremental_margin_without_locking() {
+        let mut g = setup_guardian();
+        let locked = g.would_pass(1, Side::Buy, price(100), 10, 0).unwrap();
+        assert_eq!(locked, price(1_000));
+
+        // Nothing actually locked against the real account.
+        let acc = g.get_account(1).unwrap();
+        assert_eq!(acc.available_balance, price(10_000));
+        assert_eq!(acc.locked_margin, 0);
+    }
+
+    #[test]
+    fn test_would_pass_reflects_an_existing_real_lock() {
+        let mut g = setup_guardian();
+        g.validate_and_lock(1, Side::Buy, price(100), 10, 0).unwrap(); // $1000 real buy exposure.
+
+        // A same-size resting sell is fully hedged by the real buy above —
+        // would_pass sees that existing state and reports zero incremental.
+        let locked = g.would_pass(1, Side::Sell, price(100), 10, 0).unwrap();
+        assert_eq!(locked, 0);
+    }
+
+    #[test]
+    fn test_would_pass_propagates_rejection() {
+        let mut g = setup_guardian();
+        let result = g.would_pass(999, Side::Buy, price(100), 10, 0);
+        assert!(matches!(result, Err(GuardianReject::UnknownTrader { .. })));
+    }
+
+    // -------------------------------------------------------------------
+    // Funding (Perpetual Accrual) Tests
+    // -------------------------------------------------------------------
+
+    /// Two traders facing off on symbol 0: trader 1 long 100, trader 2
+    /// short 100, both entered at $100, reference price also $100 (so
+    /// funding is the only thing moving balances — there's no unrealized
+    /// PnL to confound the assertions).
+    fn setup_funding_guardian() -> Guardian {
+        let mut g = setup_guardian();
+        g.set_reference_price(price(100));
+
+        let mut long = g.get_account(1).unwrap().clone();
+        long.positions.insert(0, 100);
+        long.entry_prices.insert(0, price(100));
+        g.restore_account(1, long);
+
+        let mut short = g.get_account(2).unwrap().clone();
+        short.positions.insert(0, -100);
+        short.entry_prices.insert(0, price(100));
+        g.restore_account(2, short);
+
+        g
+    }
+
+    #[test]
+    fn test_apply_funding_longs_pay_shorts_at_a_positive_rate() {
+        let mut g = setup_funding_guardian();
+        // 1 bps of $10,000 notional (100 × $100) = $1.
+        assert!(g.apply_funding(0, 1, 1_000));
+
+        assert_eq!(g.get_account(1).unwrap().available_balance, price(10_000) - price(1));
+        assert_eq!(g.get_account(1).unwrap().cumulative_funding, price(1));
+        assert_eq!(g.get_account(2).unwrap().available_balance, price(5_000) + price(1));
+        assert_eq!(g.get_account(2).unwrap().cumulative_funding, -price(1));
+        assert_eq!(g.funding_pool(), 0);
+    }
+
+    #[test]
+    fn test_apply_funding_negative_rate_reverses_direction() {
+        let mut g = setup_funding_guardian();
+        assert!(g.apply_funding(0, -1, 1_000));
+
+        assert_eq!(g.get_account(1).unwrap().available_balance, price(10_000) + price(1));
+        assert_eq!(g.get_account(2).unwrap().available_balance, price(5_000) - price(1));
+        assert_eq!(g.funding_pool(), 0);
+    }
+
+    #[test]
+    fn test_apply_funding_skips_flat_accounts() {
+        let mut g = setup_guardian(); // Both traders flat on every symbol.
+        g.set_reference_price(price(100));
+        assert!(g.apply_funding(0, 100, 1_000));
+
+        assert_eq!(g.get_account(1).unwrap().available_balance, price(10_000));
+        assert_eq!(g.get_account(1).unwrap().cumulative_funding, 0);
+        assert_eq!(g.get_account(2).unwrap().available_balance, price(5_000));
+        assert_eq!(g.funding_pool(), 0);
+    }
+
+    #[test]
+    fn test_apply_funding_is_idempotent_per_timestamp() {
+        let mut g = setup_funding_guardian();
+        assert!(g.apply_funding(0, 1, 1_000));
+        let after_first = g.get_account(1).unwrap().available_balance;
+
+        // Same timestamp replayed: no-op.
+        assert!(!g.apply_funding(0, 1, 1_000));
+        assert_eq!(g.get_account(1).unwrap().available_balance, after_first);
+
+        // An older timestamp than the last applied one: also a no-op.
+        assert!(!g.apply_funding(0, 1, 500));
+        assert_eq!(g.get_account(1).unwrap().available_balance, after_first);
+
+        // A later timestamp: applies again.
+        assert!(g.apply_funding(0, 1, 2_000));
+        assert_eq!(g.get_account(1).unwrap().available_balance, after_first - price(1));
+    }
+
+    #[test]
+    fn test_apply_funding_without_reference_price_is_a_no_op() {
+        let mut g = setup_guardian();
+        let mut long = g.get_account(1).unwrap().clone();
+        long.positions.insert(0, 100);
+        long.entry_prices.insert(0, price(100));
+        g.restore_account(1, long);
+
+        assert!(!g.apply_funding(0, 100, 1_000));
+        assert_eq!(g.get_account(1).unwrap().available_balance, price(10_000));
+    }
+
+    #[test]
+    fn test_apply_funding_conserves_total_equity_across_accounts_and_pool() {
+        let mut g = setup_funding_guardian();
+        let before: i64 = [1u32, 2].iter()
+            .map(|&id| g.get_account(id).unwrap().total_equity())
+            .sum::<i64>() + g.funding_pool();
+
+        g.apply_funding(0, 37, 1_000); // An odd rate, to exercise rounding.
+
+        let after: i64 = [1u32, 2].iter()
+            .map(|&id| g.get_account(id).unwrap().total_equity())
+            .sum::<i64>() + g.funding_pool();
+        assert_eq!(before, after);
+    }
+
+    #[test]
+    fn test_apply_funding_isolated_trader_pays_from_its_own_pool() {
+        let mut g = setup_funding_guardian();
+        let mut long = g.get_account(1).unwrap().clone();
+        long.margin_mode = MarginMode::Isolated;
+        long.isolated_allocations.insert(0, price(1_000));
+        g.restore_account(1, long);
+
+        // 1 bps of $10,000 notional (100 × $100) = $1.
+        assert!(g.apply_funding(0, 1, 1_000));
+
+        let acc = g.get_account(1).unwrap();
+        // The isolated pool pays, not the shared available_balance.
+        assert_eq!(acc.isolated_allocations.get(&0), Some(&(price(1_000) - price(1))));
+        assert_eq!(acc.available_balance, price(10_000));
+        assert_eq!(acc.cumulative_funding, price(1));
+        assert_eq!(g.funding_pool(), 0);
+    }
 }
@@ -5,8 +5,8 @@
 // WHY THIS IS FASTER:
 // IEEE 754 floats cannot represent 0.1 exactly (it becomes 0.1000000000000000055...).
 // Over millions of trades, this drift causes real financial losses.
-// Fixed-point uses a 64-bit integer scaled by 10^8, giving us 8 decimal places
-// of precision with ZERO rounding error on addition and subtraction.
+// Fixed-point uses a 64-bit integer scaled by 10^DECIMALS, giving us DECIMALS
+// decimal places of precision with ZERO rounding error on addition and subtraction.
 //
 // WHY NO HEAP ALLOCATION:
 // `Price` and `Quantity` are both `#[derive(Copy, Clone)]`. They live entirely
@@ -17,134 +17,393 @@
 use pyo3::prelude::*;
 use std::fmt;
 
-/// Scale factor: 10^8. All prices are stored as `raw_value = human_price * SCALE`.
+/// Scale factor: 10^8. All `Price` (i.e. `ScaledPrice<8>`) values are stored
+/// as `raw_value = human_price * SCALE`.
 ///
 /// Example: $100.05 → `10_005_000_000i64`
 pub const SCALE: i64 = 100_000_000;
 
-/// Fixed-point price representation.
+/// Fixed-point price representation, parameterized by how many decimal
+/// places of precision it's scaled to. Different instruments want different
+/// precision (FX wants 5, crypto wants 8+, some indices want 2) — pick it at
+/// the type level instead of hardcoding one scale for everything.
 ///
-/// Internally stored as `i64` scaled by `SCALE` (10^8).
-/// Supports exact addition, subtraction, and notional computation.
+/// `Price` is a type alias for `ScaledPrice<8>`, today's scale, so every
+/// existing call site in this crate that spells `Price` keeps compiling and
+/// behaving exactly as before; only code that explicitly wants a different
+/// precision needs to reach for `ScaledPrice<N>` directly. (A plain
+/// `struct Price<const DECIMALS: u32 = 8>` with a defaulted const generic
+/// looks tempting but doesn't actually preserve compatibility: unlike a type
+/// alias, a default const generic parameter isn't used to resolve type
+/// inference — `let p = Price::new(1);` with no further context would stop
+/// compiling, since nothing pins `DECIMALS` down. A type alias has no such
+/// gap, since `Price` is textually `ScaledPrice<8>` everywhere.)
+///
+/// Internally stored as `i64` scaled by `10^DECIMALS`.
+/// Supports exact addition, subtraction, and notional computation, as long
+/// as both operands share the same `DECIMALS` — the compiler rejects mixing
+/// precisions (e.g. `ScaledPrice<8> + ScaledPrice<5>` doesn't typecheck).
+/// Converting between precisions is always explicit, via `rescale`.
 ///
 /// # Memory Layout
 /// Exactly 8 bytes. Fits in a single CPU register. No heap.
-#[cfg_attr(feature = "python", pyclass)]
+///
+/// # Python bindings
+/// pyo3's `#[pyclass]` can't bind a generic type, so `ScaledPrice` itself
+/// carries no pyo3 attributes. The Python-facing binding lives on a
+/// concrete, non-generic wrapper around `Price` (`ScaledPrice<8>`) — see
+/// `PyPrice` in `python.rs`.
+#[cfg_attr(feature = "fuzz", derive(arbitrary::Arbitrary))]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub struct Price {
+pub struct ScaledPrice<const DECIMALS: u32> {
     raw: i64,
 }
 
-#[cfg_attr(feature = "python", pymethods)]
-impl Price {
-    /// Create a Price from a raw integer value (already scaled by 10^8).
-    #[cfg_attr(feature = "python", new)]
+/// Today's fixed-point price: 8 decimal places, exactly as before this type
+/// was parameterized by precision.
+pub type Price = ScaledPrice<8>;
+
+impl<const DECIMALS: u32> ScaledPrice<DECIMALS> {
+    /// The lowest value a price at this precision can represent — mirrors
+    /// `rust_decimal::Decimal::MIN`.
+    pub const MIN: ScaledPrice<DECIMALS> = ScaledPrice { raw: i64::MIN };
+    /// The highest value a price at this precision can represent — mirrors
+    /// `rust_decimal::Decimal::MAX`.
+    pub const MAX: ScaledPrice<DECIMALS> = ScaledPrice { raw: i64::MAX };
+
+    /// `10^DECIMALS`, computed at compile time. For `Price` (`ScaledPrice<8>`)
+    /// this is exactly `SCALE`.
+    pub const SCALE: i64 = {
+        let mut scale: i64 = 1;
+        let mut i = 0;
+        while i < DECIMALS {
+            scale *= 10;
+            i += 1;
+        }
+        scale
+    };
+}
+
+/// `div_round`, generalized to `i128` operands so `checked_div` can round its
+/// `SCALE`-widened numerator without narrowing back to `i64` first.
+fn div_round_i128(num: i128, den: i128) -> i128 {
+    let q = num / den;
+    let r = num % den;
+    if r == 0 {
+        return q;
+    }
+
+    let twice_r_abs = 2 * r.abs();
+    let den_abs = den.abs();
+    let away_from_zero: i128 = if (num < 0) != (den < 0) { -1 } else { 1 };
+
+    match twice_r_abs.cmp(&den_abs) {
+        std::cmp::Ordering::Less => q,
+        std::cmp::Ordering::Greater => q + away_from_zero,
+        std::cmp::Ordering::Equal => {
+            if q % 2 != 0 {
+                q + away_from_zero
+            } else {
+                q
+            }
+        }
+    }
+}
+
+impl<const DECIMALS: u32> ScaledPrice<DECIMALS> {
+    /// Create a price from a raw integer value (already scaled by `10^DECIMALS`).
     pub fn new(raw: i64) -> Self {
         Self { raw }
     }
 
-    /// Create a Price from a human-readable float string (e.g., "100.05").
-    /// This is the ONLY place float-to-fixed conversion happens.
-    /// After this, all math is pure integer.
-    #[cfg_attr(feature = "python", staticmethod)]
+    /// Create a price from a human-readable decimal string (e.g. "100.05",
+    /// "-0.5", "1.23e4", "2.5E-10"). This is the ONLY place string-to-fixed
+    /// conversion happens. After this, all math is pure integer.
+    ///
+    /// Grammar (same shape as Rust's own `dec2flt`): an optional sign, an
+    /// optional integral part, an optional `.` + fractional part, and an
+    /// optional `e`/`E` exponent — each part parsed as plain digits, so
+    /// `2.5E-10` and `1.23e4` both work. More than `DECIMALS` fractional
+    /// digits (once the exponent is folded in) round to the last place
+    /// half-to-even (see `div_round`) instead of being rejected.
     pub fn from_str_decimal(s: &str) -> Result<Self, String> {
         let trimmed = s.trim();
-        let parts: Vec<&str> = trimmed.split('.').collect();
+        if trimmed.is_empty() {
+            return Err("Invalid price string format".to_string());
+        }
+
+        // Split off an optional `e`/`E` exponent before looking at sign or
+        // decimal point, e.g. "1.23e4" -> mantissa "1.23", exponent 4.
+        let (mantissa, exponent) = match trimmed.find(['e', 'E']) {
+            Some(idx) => {
+                let exponent: i32 = trimmed[idx + 1..]
+                    .parse()
+                    .map_err(|_| "Invalid exponent".to_string())?;
+                (&trimmed[..idx], exponent)
+            }
+            None => (trimmed, 0i32),
+        };
+
+        // The sign comes from the mantissa's leading `-`, not from whether
+        // the parsed integer part happens to be nonzero — "-0.5" must stay
+        // negative even though its integer part parses to 0.
+        let negative = mantissa.starts_with('-');
+        let unsigned = mantissa
+            .strip_prefix(['-', '+'])
+            .unwrap_or(mantissa);
+
+        let parts: Vec<&str> = unsigned.split('.').collect();
         if parts.is_empty() || parts.len() > 2 {
             return Err("Invalid price string format".to_string());
         }
+        let integer_digits = if parts[0].is_empty() { "0" } else { parts[0] };
+        let fractional_digits = if parts.len() == 2 { parts[1] } else { "" };
+        if !integer_digits.bytes().all(|b| b.is_ascii_digit())
+            || !fractional_digits.bytes().all(|b| b.is_ascii_digit())
+        {
+            return Err("Invalid price string format".to_string());
+        }
 
-        let integer_part: i64 = parts[0]
-            .parse()
-            .map_err(|_| "Invalid integer part".to_string())?;
+        // Concatenate integer + fractional digits into one unsigned integer
+        // (e.g. "100.05" -> digits "10005") — the decimal point's position
+        // is tracked separately via `fractional_digits.len()`.
+        let mut digits = String::with_capacity(integer_digits.len() + fractional_digits.len());
+        digits.push_str(integer_digits);
+        digits.push_str(fractional_digits);
+        let digits_value: i128 = digits.parse().map_err(|_| "Invalid price string format".to_string())?;
 
-        let fractional_raw: i64 = if parts.len() == 2 && !parts[1].is_empty() {
-            let frac_str = parts[1];
-            let frac_digits = frac_str.len();
-            if frac_digits > 8 {
-                return Err("Max 8 decimal places supported".to_string());
-            }
-            let frac_val: i64 = frac_str
-                .parse()
-                .map_err(|_| "Invalid fractional part".to_string())?;
-            let multiplier = 10i64.pow((8 - frac_digits) as u32);
-            frac_val * multiplier
+        // value = digits_value * 10^(exponent - fractional_digits.len())
+        // raw   = value * 10^DECIMALS = digits_value * 10^shift.
+        //
+        // Widened to `i64` before any arithmetic: `exponent` is attacker-
+        // controlled (parsed straight from the input string, e.g.
+        // "1e2147483647"), and `DECIMALS as i32 + exponent` in plain `i32`
+        // can overflow and panic. `DECIMALS`/`exponent`/the digit count are
+        // all comfortably within `i64` range, so the sum itself can't
+        // overflow here — only the later cast to `u32` (for `checked_pow`)
+        // needs its own explicit range check.
+        let shift: i64 = DECIMALS as i64 + exponent as i64 - fractional_digits.len() as i64;
+
+        // The sign is applied before narrowing to `i64`, so `Price::MIN`
+        // (whose magnitude is one past `i64::MAX`) parses as well.
+        let raw: i64 = if shift >= 0 {
+            let shift = u32::try_from(shift).map_err(|_| "Exponent out of range".to_string())?;
+            let multiplier = 10i128
+                .checked_pow(shift)
+                .ok_or_else(|| "Exponent out of range".to_string())?;
+            let scaled = digits_value
+                .checked_mul(multiplier)
+                .ok_or_else(|| "Price out of range".to_string())?;
+            let signed = if negative { -scaled } else { scaled };
+            i64::try_from(signed).map_err(|_| "Price out of range".to_string())?
         } else {
-            0
+            let shift = u32::try_from(-shift).map_err(|_| "Exponent out of range".to_string())?;
+            let divisor = 10i128
+                .checked_pow(shift)
+                .ok_or_else(|| "Exponent out of range".to_string())?;
+            let num = i64::try_from(digits_value).map_err(|_| "Price out of range".to_string())?;
+            let den = i64::try_from(divisor).map_err(|_| "Exponent out of range".to_string())?;
+            let magnitude = Self::div_round(num, den);
+            if negative { -magnitude } else { magnitude }
         };
 
-        let sign = if integer_part < 0 { -1i64 } else { 1i64 };
-        let raw = integer_part * SCALE + sign * fractional_raw;
         Ok(Self { raw })
     }
 
-    /// Create a Price from a floating point value.
+    /// Create a price from a floating point value.
     /// WARNING: Use `from_str_decimal` when possible.
-    #[cfg_attr(feature = "python", staticmethod)]
     pub fn from_float(value: f64) -> Self {
         Self {
-            raw: (value * SCALE as f64).round() as i64,
+            raw: (value * Self::SCALE as f64).round() as i64,
         }
     }
 
-    /// The raw i64 value (scaled by 10^8).
+    /// The raw i64 value (scaled by `10^DECIMALS`).
     pub fn raw(&self) -> i64 {
         self.raw
     }
 
     /// Convert to human-readable float for display / Python interop.
     pub fn to_float(&self) -> f64 {
-        self.raw as f64 / SCALE as f64
+        self.raw as f64 / Self::SCALE as f64
+    }
+
+    /// Compute notional value: price × quantity, widened to `i128` so the
+    /// multiplication can never overflow — `i64::MAX * u32::MAX` comfortably
+    /// fits in 128 bits, unlike the `i64` it used to return.
+    pub fn notional(&self, qty: u32) -> i128 {
+        self.raw as i128 * qty as i128
+    }
+
+    /// `notional`, but checked in `i64` for callers that need the result to
+    /// stay a raw fixed-point `i64` (e.g. before handing it to code that
+    /// hasn't been widened to `i128` yet). Returns `None` on overflow
+    /// instead of wrapping into a nonsensical (often negative) amount.
+    pub fn checked_notional(&self, qty: u32) -> Option<i64> {
+        self.raw.checked_mul(qty as i64)
+    }
+
+    /// `self + other`, or `None` if the sum overflows `i64`.
+    pub fn checked_add(&self, other: Self) -> Option<Self> {
+        self.raw.checked_add(other.raw).map(|raw| Self { raw })
     }
 
-    /// Compute notional value: price × quantity. Exact integer math.
-    pub fn notional(&self, qty: u32) -> i64 {
-        self.raw * (qty as i64)
+    /// `self - other`, or `None` if the difference overflows `i64`.
+    pub fn checked_sub(&self, other: Self) -> Option<Self> {
+        self.raw.checked_sub(other.raw).map(|raw| Self { raw })
     }
 
-    /// Weighted average of two prices (integer division truncates).
-    #[cfg_attr(feature = "python", staticmethod)]
-    pub fn weighted_avg(old_avg: &Price, old_qty: u32, new_price: &Price, new_qty: u32) -> Price {
+    /// `self + other`, clamped to `MIN`/`MAX` instead of overflowing.
+    pub fn saturating_add(&self, other: Self) -> Self {
+        Self { raw: self.raw.saturating_add(other.raw) }
+    }
+
+    /// `self - other`, clamped to `MIN`/`MAX` instead of overflowing.
+    pub fn saturating_sub(&self, other: Self) -> Self {
+        Self { raw: self.raw.saturating_sub(other.raw) }
+    }
+
+    /// Weighted average of two prices, rounded half-to-even.
+    pub fn weighted_avg(old_avg: &Self, old_qty: u32, new_price: &Self, new_qty: u32) -> Self {
         let total_qty = old_qty as i64 + new_qty as i64;
         if total_qty == 0 {
-            return Price { raw: 0 };
+            return Self { raw: 0 };
+        }
+        let numerator = old_avg.raw * old_qty as i64 + new_price.raw * new_qty as i64;
+        Self { raw: Self::div_round(numerator, total_qty) }
+    }
+
+    /// Midpoint of two prices, rounded half-to-even.
+    pub fn midpoint(&self, other: &Self) -> Self {
+        Self { raw: Self::div_round(self.raw + other.raw, 2) }
+    }
+
+    /// Divide `num` by `den` using round-half-to-even ("banker's rounding"),
+    /// the same tie-breaking rule IEEE decimal arithmetic uses, instead of
+    /// `/`'s round-toward-zero truncation — truncation biases VWAP/midpoint
+    /// calculations downward (in magnitude) over millions of fills, since it
+    /// always discards the remainder rather than rounding it.
+    ///
+    /// `q = num / den`, `r = num % den`; if `2|r| < |den|` the truncated `q`
+    /// is already nearest and is kept; if `2|r| > |den|` `q` moves one step
+    /// away from zero; on an exact tie `2|r| == |den|`, `q` moves away from
+    /// zero only if that makes it even (i.e. only if `q` is currently odd).
+    ///
+    /// # Panics
+    /// Panics if `den` is zero, same as integer division.
+    pub fn div_round(num: i64, den: i64) -> i64 {
+        div_round_i128(num as i128, den as i128) as i64
+    }
+
+    /// `self * other`, rescaled back down from `SCALE²` to `SCALE` — the two
+    /// operands are each `SCALE`-scaled, so their raw product is scaled by
+    /// `SCALE²` and must be divided by `SCALE` once to land back on a single
+    /// price's scale, the same rescaling `rust_decimal` performs internally
+    /// after multiplying two scaled mantissas.
+    ///
+    /// Returns `None` if the rescaled result doesn't fit in `i64`.
+    pub fn checked_mul(&self, other: &Self) -> Option<Self> {
+        let product = self.raw as i128 * other.raw as i128 / Self::SCALE as i128;
+        i64::try_from(product).ok().map(|raw| Self { raw })
+    }
+
+    /// `self / other`, rescaled by multiplying the numerator by `SCALE`
+    /// first so the division doesn't just collapse back to the unscaled
+    /// ratio — rounded half-to-even (see `div_round`) rather than truncated,
+    /// since a truncated ratio biases fee and conversion-rate math downward.
+    ///
+    /// Returns `None` if `other` is zero or the rescaled result doesn't fit
+    /// in `i64`.
+    pub fn checked_div(&self, other: &Self) -> Option<Self> {
+        if other.raw == 0 {
+            return None;
         }
-        let raw = (old_avg.raw * old_qty as i64 + new_price.raw * new_qty as i64) / total_qty;
-        Price { raw }
+        let numerator = self.raw as i128 * Self::SCALE as i128;
+        let quotient = div_round_i128(numerator, other.raw as i128);
+        i64::try_from(quotient).ok().map(|raw| Self { raw })
     }
 
-    /// Midpoint of two prices (integer division truncates).
-    pub fn midpoint(&self, other: &Price) -> Price {
-        Price {
-            raw: (self.raw + other.raw) / 2,
+    /// Convert to a price scaled to `OTHER` decimal places instead of
+    /// `DECIMALS`. Widening precision (`OTHER > DECIMALS`) multiplies
+    /// exactly; narrowing precision (`OTHER < DECIMALS`) rounds the dropped
+    /// digits half-to-even via `div_round`, the same rule `from_str_decimal`
+    /// uses when folding in excess fractional digits.
+    ///
+    /// There's no implicit conversion between precisions on purpose:
+    /// mixed-precision math (e.g. comparing an FX `ScaledPrice<5>` against a
+    /// crypto `Price`) must go through `rescale` explicitly rather than
+    /// silently lining up decimal points wrong.
+    pub fn rescale<const OTHER: u32>(&self) -> ScaledPrice<OTHER> {
+        if OTHER >= DECIMALS {
+            let shift = OTHER - DECIMALS;
+            ScaledPrice::<OTHER>::new(self.raw * 10i64.pow(shift))
+        } else {
+            let shift = DECIMALS - OTHER;
+            ScaledPrice::<OTHER>::new(Self::div_round(self.raw, 10i64.pow(shift)))
         }
     }
 }
 
-impl std::ops::Add for Price {
-    type Output = Price;
-    fn add(self, rhs: Price) -> Price {
-        Price { raw: self.raw + rhs.raw }
+impl<const DECIMALS: u32> std::ops::Add for ScaledPrice<DECIMALS> {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self {
+        Self { raw: self.raw + rhs.raw }
     }
 }
 
-impl std::ops::Sub for Price {
-    type Output = Price;
-    fn sub(self, rhs: Price) -> Price {
-        Price { raw: self.raw - rhs.raw }
+impl<const DECIMALS: u32> std::ops::Sub for ScaledPrice<DECIMALS> {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self {
+        Self { raw: self.raw - rhs.raw }
     }
 }
 
-impl fmt::Display for Price {
+impl<const DECIMALS: u32> fmt::Display for ScaledPrice<DECIMALS> {
+    /// Defaults to the price's full native precision (`100.05000000`), the
+    /// same as before this impl learned to read format specs. An explicit
+    /// `{:.N}` rounds to `N` fractional digits half-to-even via `div_round`
+    /// rather than truncating, same rule as `rescale`. `{:#}` ("%g"-like)
+    /// trims trailing zeros off the fractional part down to the shortest
+    /// exact representation, dropping the decimal point entirely once
+    /// nothing's left of it (`100.05`, `100`). Both can combine, e.g.
+    /// `{:#.2}`.
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let integer_part = self.raw / SCALE;
-        let fractional_part = (self.raw % SCALE).unsigned_abs();
-        write!(f, "{}.{:08}", integer_part, fractional_part)
+        let out_decimals = f.precision().map(|p| p as u32).unwrap_or(DECIMALS);
+        let display_raw = if out_decimals == DECIMALS {
+            self.raw
+        } else if out_decimals < DECIMALS {
+            Self::div_round(self.raw, 10i64.pow(DECIMALS - out_decimals))
+        } else {
+            self.raw * 10i64.pow(out_decimals - DECIMALS)
+        };
+
+        let scale = 10i64.pow(out_decimals);
+        // The sign is written on its own: for a value between -1 and 0 the
+        // integer part is 0 and would not carry it.
+        let sign = if display_raw < 0 { "-" } else { "" };
+        let integer_part = (display_raw / scale).unsigned_abs();
+        let mut fractional = format!(
+            "{:0width$}",
+            (display_raw % scale).unsigned_abs(),
+            width = out_decimals as usize
+        );
+        if f.alternate() {
+            while fractional.ends_with('0') {
+                fractional.pop();
+            }
+        }
+
+        if fractional.is_empty() {
+            write!(f, "{}{}", sign, integer_part)
+        } else {
+            write!(f, "{}{}.{}", sign, integer_part, fractional)
+        }
     }
 }
 
 /// Fixed-point quantity. Exactly 4 bytes. Fits in a single register.
 #[cfg_attr(feature = "python", pyclass)]
+#[cfg_attr(feature = "fuzz", derive(arbitrary::Arbitrary))]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Quantity {
     raw: u32,
@@ -188,12 +447,89 @@ mod tests {
         assert_eq!(p.raw(), 10_000_000_000);
     }
 
+    #[test]
+    fn test_price_from_str_negative_fraction_keeps_sign() {
+        // Regression: integer_part parses to 0 (non-negative), so deriving
+        // the sign from it (rather than the string's leading '-') used to
+        // silently drop the sign of inputs like "-0.5".
+        let p = Price::from_str_decimal("-0.5").unwrap();
+        assert_eq!(p.raw(), -50_000_000);
+    }
+
+    #[test]
+    fn test_price_from_str_scientific_notation() {
+        assert_eq!(Price::from_str_decimal("1.23e4").unwrap().raw(), Price::from_str_decimal("12300").unwrap().raw());
+        assert_eq!(Price::from_str_decimal("2.5E-10").unwrap(), Price::from_str_decimal("0").unwrap());
+        assert_eq!(Price::from_str_decimal("1e2").unwrap().raw(), 10_000_000_000);
+        assert_eq!(Price::from_str_decimal("-1.5e1").unwrap().raw(), -1_500_000_000);
+    }
+
+    #[test]
+    fn test_price_from_str_rounds_beyond_8_fractional_digits() {
+        // 9 fractional digits: the 9th rounds the 8th place half-to-even
+        // instead of erroring like it used to.
+        let p = Price::from_str_decimal("1.234567895").unwrap();
+        assert_eq!(p.raw(), 123_456_790); // ...89|5 ties, 9 rounds up to even 90.
+        let q = Price::from_str_decimal("1.234567885").unwrap();
+        assert_eq!(q.raw(), 123_456_788); // ...88|5 ties, 8 stays (already even).
+    }
+
+    #[test]
+    fn test_price_from_str_rejects_malformed_input() {
+        assert!(Price::from_str_decimal("abc").is_err());
+        assert!(Price::from_str_decimal("1.2.3").is_err());
+        assert!(Price::from_str_decimal("").is_err());
+        assert!(Price::from_str_decimal("1.2e").is_err());
+    }
+
+    #[test]
+    fn test_price_from_str_rejects_rather_than_panics_on_extreme_exponents() {
+        // Regression: `DECIMALS as i32 + exponent - fractional_digits.len()`
+        // used to be computed in plain `i32` and overflow-panic on an
+        // attacker-controlled exponent this large.
+        assert!(Price::from_str_decimal("1e2147483647").is_err());
+        assert!(Price::from_str_decimal("1e-2147483648").is_err());
+        assert!(Price::from_str_decimal("1.23456789e2147483647").is_err());
+    }
+
     #[test]
     fn test_price_display() {
         let p = Price::new(10_005_000_000);
         assert_eq!(format!("{}", p), "100.05000000");
     }
 
+    #[test]
+    fn test_price_display_precision_rounds_half_to_even() {
+        let a = Price::from_str_decimal("100.055").unwrap();
+        assert_eq!(format!("{:.2}", a), "100.06"); // ...5|5 ties, 5 rounds up to even 6.
+        let b = Price::from_str_decimal("100.045").unwrap();
+        assert_eq!(format!("{:.2}", b), "100.04"); // ...4|5 ties, 4 stays (already even).
+    }
+
+    #[test]
+    fn test_price_display_precision_wider_than_native_pads_with_zeros() {
+        let p = Price::new(10_005_000_000); // 100.05
+        assert_eq!(format!("{:.10}", p), "100.0500000000");
+    }
+
+    #[test]
+    fn test_price_display_alternate_trims_trailing_zeros() {
+        let p = Price::from_str_decimal("100.5").unwrap();
+        assert_eq!(format!("{:#}", p), "100.5");
+    }
+
+    #[test]
+    fn test_price_display_alternate_drops_decimal_point_for_whole_numbers() {
+        let p = Price::from_str_decimal("100").unwrap();
+        assert_eq!(format!("{:#}", p), "100");
+    }
+
+    #[test]
+    fn test_price_display_alternate_combines_with_precision() {
+        let p = Price::new(10_005_000_000); // 100.05
+        assert_eq!(format!("{:#.2}", p), "100.05");
+    }
+
     #[test]
     fn test_price_addition_exact() {
         // 0.1 + 0.2 must equal 0.3 EXACTLY. Floats cannot do this.
@@ -209,7 +545,37 @@ mod tests {
         let price = Price::from_str_decimal("100.00").unwrap();
         let notional = price.notional(50);
         let expected = Price::from_str_decimal("5000.00").unwrap().raw();
-        assert_eq!(notional, expected);
+        assert_eq!(notional, expected as i128);
+    }
+
+    #[test]
+    fn test_notional_never_overflows() {
+        let price = Price::new(i64::MAX);
+        // i64::MAX * u32::MAX would wrap an i64 product; i128 has room to spare.
+        let notional = price.notional(u32::MAX);
+        assert_eq!(notional, i64::MAX as i128 * u32::MAX as i128);
+    }
+
+    #[test]
+    fn test_checked_notional_overflow() {
+        let price = Price::new(i64::MAX);
+        assert_eq!(price.checked_notional(2), None);
+        assert_eq!(Price::new(100).checked_notional(2), Some(200));
+    }
+
+    #[test]
+    fn test_checked_add_sub_overflow() {
+        assert_eq!(Price::MAX.checked_add(Price::new(1)), None);
+        assert_eq!(Price::MIN.checked_sub(Price::new(1)), None);
+        assert_eq!(Price::new(1).checked_add(Price::new(2)), Some(Price::new(3)));
+        assert_eq!(Price::new(3).checked_sub(Price::new(1)), Some(Price::new(2)));
+    }
+
+    #[test]
+    fn test_saturating_add_sub_clamp() {
+        assert_eq!(Price::MAX.saturating_add(Price::new(1)), Price::MAX);
+        assert_eq!(Price::MIN.saturating_sub(Price::new(1)), Price::MIN);
+        assert_eq!(Price::new(1).saturating_add(Price::new(2)), Price::new(3));
     }
 
     #[test]
@@ -230,10 +596,106 @@ mod tests {
         assert_eq!(mid, expected);
     }
 
+    #[test]
+    fn test_midpoint_tie_rounds_to_even() {
+        // Raw sum of 3, divided by 2, is an exact tie (1.5): the two
+        // candidates are 1 (odd) and 2 (even) — banker's rounding picks 2.
+        let mid = Price::new(1).midpoint(&Price::new(2));
+        assert_eq!(mid.raw(), 2);
+
+        // Raw sum of 1 (e.g. 0 and 1), divided by 2, is also a tie (0.5):
+        // candidates are 0 (even) and 1 (odd) — banker's rounding keeps 0.
+        let mid_even = Price::new(0).midpoint(&Price::new(1));
+        assert_eq!(mid_even.raw(), 0);
+    }
+
+    #[test]
+    fn test_div_round_ties_to_even() {
+        assert_eq!(Price::div_round(5, 2), 2); // 2.5 -> 2 (even)
+        assert_eq!(Price::div_round(7, 2), 4); // 3.5 -> 4 (even)
+        assert_eq!(Price::div_round(-5, 2), -2); // -2.5 -> -2 (even)
+        assert_eq!(Price::div_round(-7, 2), -4); // -3.5 -> -4 (even)
+    }
+
+    #[test]
+    fn test_div_round_non_tie_rounds_nearest() {
+        assert_eq!(Price::div_round(10, 3), 3); // 3.33.. -> 3
+        assert_eq!(Price::div_round(11, 3), 4); // 3.66.. -> 4
+        assert_eq!(Price::div_round(-11, 3), -4); // -3.66.. -> -4
+    }
+
+    #[test]
+    fn test_div_round_exact() {
+        assert_eq!(Price::div_round(10, 2), 5);
+        assert_eq!(Price::div_round(-10, 2), -5);
+    }
+
+    #[test]
+    fn test_checked_mul_rescales_to_single_scale() {
+        let price = Price::from_str_decimal("2.00").unwrap();
+        let factor = Price::from_str_decimal("0.5").unwrap();
+        let result = price.checked_mul(&factor).unwrap();
+        assert_eq!(result, Price::from_str_decimal("1.00").unwrap());
+    }
+
+    #[test]
+    fn test_checked_mul_overflow() {
+        assert_eq!(Price::MAX.checked_mul(&Price::MAX), None);
+        assert_eq!(Price::new(100).checked_mul(&Price::new(SCALE)), Some(Price::new(100)));
+    }
+
+    #[test]
+    fn test_checked_div_rescales_to_single_scale() {
+        let price = Price::from_str_decimal("5.00").unwrap();
+        let divisor = Price::from_str_decimal("2.00").unwrap();
+        let result = price.checked_div(&divisor).unwrap();
+        assert_eq!(result, Price::from_str_decimal("2.50").unwrap());
+    }
+
+    #[test]
+    fn test_checked_div_by_zero() {
+        assert_eq!(Price::new(100).checked_div(&Price::new(0)), None);
+    }
+
+    #[test]
+    fn test_checked_div_rounds_half_to_even() {
+        // raw 5 / raw 2 -> 2.5 ties; 2 is even, kept.
+        let result = Price::new(5).checked_div(&Price::new(2 * SCALE)).unwrap();
+        assert_eq!(result.raw(), 2);
+    }
+
     #[test]
     fn test_price_from_float_round_trip() {
         let p = Price::from_float(99.95);
         assert_eq!(p.raw(), 9_995_000_000);
         assert!((p.to_float() - 99.95).abs() < 1e-10);
     }
+
+    #[test]
+    fn test_rescale_widening_precision_is_exact() {
+        let fx = ScaledPrice::<5>::from_str_decimal("1.23456").unwrap();
+        let wider: Price = fx.rescale();
+        assert_eq!(wider, Price::from_str_decimal("1.23456").unwrap());
+    }
+
+    #[test]
+    fn test_rescale_narrowing_precision_rounds_half_to_even() {
+        // 8-decimal 1.234565 narrowed to 5 decimals: the dropped "65" rounds
+        // the last kept digit half-to-even, same tie rule as div_round.
+        let wide = Price::new(123_456_500); // 1.234565 at 8 decimals
+        let narrow: ScaledPrice<5> = wide.rescale();
+        assert_eq!(narrow.raw(), 123_456); // 1.23456 (6 is even, stays)
+    }
+
+    #[test]
+    fn test_rescale_same_precision_is_identity() {
+        let p = Price::from_str_decimal("42.5").unwrap();
+        let same: Price = p.rescale();
+        assert_eq!(p, same);
+    }
+
+    #[test]
+    fn test_price_scale_matches_module_constant() {
+        assert_eq!(Price::SCALE, SCALE);
+    }
 }
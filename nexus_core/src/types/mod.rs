@@ -5,5 +5,5 @@
 pub mod fixed_point;
 pub mod side;
 
-pub use fixed_point::{Price, Quantity, SCALE};
+pub use fixed_point::{Price, Quantity, ScaledPrice, SCALE};
 pub use side::Side;
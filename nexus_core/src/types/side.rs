@@ -14,6 +14,8 @@ use std::fmt;
 
 /// Order side: Buy or Sell. Represented as a single byte (`u8`).
 #[cfg_attr(feature = "python", pyclass(eq, eq_int))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "u8", into = "u8"))]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[repr(u8)]
 pub enum Side {
@@ -21,6 +23,20 @@ pub enum Side {
     Sell = 2,
 }
 
+impl TryFrom<u8> for Side {
+    type Error = String;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        Side::from_u8(value)
+    }
+}
+
+impl From<Side> for u8 {
+    fn from(side: Side) -> Self {
+        side.as_u8()
+    }
+}
+
 #[cfg_attr(feature = "python", pymethods)]
 impl Side {
     /// Parse from a string (case-insensitive).
@@ -106,6 +122,24 @@ mod tests {
         assert_eq!(Side::Sell.opposite(), Side::Buy);
     }
 
+    #[test]
+    fn test_side_try_from_u8_roundtrip() {
+        assert_eq!(Side::try_from(1u8).unwrap(), Side::Buy);
+        assert_eq!(Side::try_from(2u8).unwrap(), Side::Sell);
+        assert!(Side::try_from(0u8).is_err());
+        assert_eq!(u8::from(Side::Buy), 1);
+        assert_eq!(u8::from(Side::Sell), 2);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_side_serde_json_roundtrip() {
+        let buy_json = serde_json::to_string(&Side::Buy).unwrap();
+        assert_eq!(buy_json, "1");
+        assert_eq!(serde_json::from_str::<Side>(&buy_json).unwrap(), Side::Buy);
+        assert!(serde_json::from_str::<Side>("0").is_err());
+    }
+
     #[test]
     fn test_side_sign() {
         assert_eq!(Side::Buy.sign(), 1);
@@ -0,0 +1,231 @@
+// nexus_core/src/wire/decode.rs
+//
+// Safe, validated entry point for turning raw bytes into typed SBE messages.
+//
+// WHY NOT `&*(bytes.as_ptr() as *const NewOrder)`:
+// A reference into a `#[repr(C, packed)]` struct is unsound the moment the
+// buffer is too short, misaligned, or simply garbage (a random `msg_type`
+// byte from a probe connection). `decode()` reads only the 8-byte header via
+// `ptr::read_unaligned`, validates length and discriminants against the
+// buffer we actually have, and only then copies out a typed value — never a
+// reference — so there is no path from "bad bytes on the wire" to UB.
+
+use std::mem::size_of;
+use std::ptr;
+
+use super::messages::{msg_type, order_type, tif, MessageHeader, NewOrder, OrderCancel, TradeUpdate};
+use crate::types::Side;
+
+/// A decoded wire message, tagged by its `msg_type`.
+#[derive(Debug, Clone, Copy)]
+pub enum WireMessage {
+    NewOrder(NewOrder),
+    OrderCancel(OrderCancel),
+    Trade(TradeUpdate),
+}
+
+/// Why `decode` rejected a buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WireError {
+    /// Buffer is too short to even hold a `MessageHeader`.
+    TooShort,
+    /// `header.msg_length` does not match the expected `size_of` for this
+    /// message type. (A buffer shorter than `msg_length` is `TooShort`.)
+    LengthMismatch { expected: usize, got: usize },
+    /// `header.msg_type` is not one of the known constants.
+    UnknownType(u8),
+    /// `header.version` is not the one this decoder understands.
+    BadVersion(u8),
+    /// A discriminant byte (side / order_type / time_in_force) does not map
+    /// to a known value. The string names which one.
+    BadEnum(&'static str),
+}
+
+/// The only schema version this decoder accepts.
+const SUPPORTED_VERSION: u8 = 1;
+
+/// Decode a single SBE message from `buf`.
+///
+/// Reads the 8-byte `MessageHeader` via `ptr::read_unaligned` first (never a
+/// reference into `buf`), validates it, then validates and copies out the
+/// full typed message. Returns an error instead of panicking/UB on short,
+/// truncated, or malformed input.
+pub fn decode(buf: &[u8]) -> Result<WireMessage, WireError> {
+    if buf.len() < size_of::<MessageHeader>() {
+        return Err(WireError::TooShort);
+    }
+
+    // Safety: we just checked `buf.len() >= size_of::<MessageHeader>()`, and
+    // `read_unaligned` does not require the source pointer to be aligned.
+    let header: MessageHeader =
+        unsafe { ptr::read_unaligned(buf.as_ptr() as *const MessageHeader) };
+
+    if header.version != SUPPORTED_VERSION {
+        return Err(WireError::BadVersion(header.version));
+    }
+
+    let msg_length = header.msg_length as usize;
+    if buf.len() < msg_length {
+        return Err(WireError::TooShort);
+    }
+
+    match header.msg_type {
+        msg_type::NEW_ORDER => {
+            let expected = size_of::<NewOrder>();
+            if msg_length != expected {
+                return Err(WireError::LengthMismatch { expected, got: msg_length });
+            }
+            let order: NewOrder = unsafe { ptr::read_unaligned(buf.as_ptr() as *const NewOrder) };
+            validate_new_order(&order)?;
+            Ok(WireMessage::NewOrder(order))
+        }
+        msg_type::ORDER_CANCEL => {
+            let expected = size_of::<OrderCancel>();
+            if msg_length != expected {
+                return Err(WireError::LengthMismatch { expected, got: msg_length });
+            }
+            let cancel: OrderCancel =
+                unsafe { ptr::read_unaligned(buf.as_ptr() as *const OrderCancel) };
+            Ok(WireMessage::OrderCancel(cancel))
+        }
+        msg_type::TRADE_UPDATE => {
+            let expected = size_of::<TradeUpdate>();
+            if msg_length != expected {
+                return Err(WireError::LengthMismatch { expected, got: msg_length });
+            }
+            let trade: TradeUpdate =
+                unsafe { ptr::read_unaligned(buf.as_ptr() as *const TradeUpdate) };
+            Ok(WireMessage::Trade(trade))
+        }
+        other => Err(WireError::UnknownType(other)),
+    }
+}
+
+/// Validate the discriminant bytes of a `NewOrder` before trusting it.
+fn validate_new_order(order: &NewOrder) -> Result<(), WireError> {
+    let side = order.side;
+    if Side::from_u8(side).is_err() {
+        return Err(WireError::BadEnum("side"));
+    }
+    let ot = order.order_type;
+    if ot != order_type::LIMIT && ot != order_type::MARKET {
+        return Err(WireError::BadEnum("order_type"));
+    }
+    let t = order.time_in_force;
+    if t != tif::GTC && t != tif::IOC && t != tif::FOK {
+        return Err(WireError::BadEnum("time_in_force"));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Price;
+
+    fn raw_bytes<T: Copy>(value: &T) -> Vec<u8> {
+        unsafe {
+            std::slice::from_raw_parts(value as *const T as *const u8, size_of::<T>()).to_vec()
+        }
+    }
+
+    #[test]
+    fn test_decode_new_order_roundtrip() {
+        let price = Price::from_str_decimal("100.05").unwrap();
+        let order = NewOrder::new(1, 42, 7, price, 50, Side::Buy, order_type::LIMIT, tif::GTC);
+        let bytes = raw_bytes(&order);
+
+        match decode(&bytes).unwrap() {
+            WireMessage::NewOrder(decoded) => {
+                assert_eq!({ decoded.trader_id }, 42);
+                assert_eq!({ decoded.quantity }, 50);
+                assert_eq!(decoded.side_enum(), Some(Side::Buy));
+            }
+            other => panic!("expected NewOrder, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_decode_too_short() {
+        let bytes = [0u8; 4];
+        assert_eq!(decode(&bytes).unwrap_err(), WireError::TooShort);
+    }
+
+    #[test]
+    fn test_decode_header_only_buffer_too_short_for_payload() {
+        let price = Price::from_str_decimal("1.00").unwrap();
+        let order = NewOrder::new(1, 1, 1, price, 1, Side::Buy, order_type::LIMIT, tif::GTC);
+        let bytes = raw_bytes(&order);
+        // Truncate to just the header.
+        assert_eq!(decode(&bytes[..size_of::<MessageHeader>()]).unwrap_err(), WireError::TooShort);
+    }
+
+    #[test]
+    fn test_decode_unknown_type() {
+        let price = Price::from_str_decimal("1.00").unwrap();
+        let mut order = NewOrder::new(1, 1, 1, price, 1, Side::Buy, order_type::LIMIT, tif::GTC);
+        order.header.msg_type = 0x99;
+        let bytes = raw_bytes(&order);
+        assert_eq!(decode(&bytes).unwrap_err(), WireError::UnknownType(0x99));
+    }
+
+    #[test]
+    fn test_decode_bad_version() {
+        let price = Price::from_str_decimal("1.00").unwrap();
+        let mut order = NewOrder::new(1, 1, 1, price, 1, Side::Buy, order_type::LIMIT, tif::GTC);
+        order.header.version = 2;
+        let bytes = raw_bytes(&order);
+        assert_eq!(decode(&bytes).unwrap_err(), WireError::BadVersion(2));
+    }
+
+    #[test]
+    fn test_decode_length_mismatch() {
+        let price = Price::from_str_decimal("1.00").unwrap();
+        let mut order = NewOrder::new(1, 1, 1, price, 1, Side::Buy, order_type::LIMIT, tif::GTC);
+        order.header.msg_length = size_of::<OrderCancel>() as u16;
+        let bytes = raw_bytes(&order);
+        assert_eq!(
+            decode(&bytes).unwrap_err(),
+            WireError::LengthMismatch {
+                expected: size_of::<NewOrder>(),
+                got: size_of::<OrderCancel>(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_decode_bad_side_enum() {
+        let price = Price::from_str_decimal("1.00").unwrap();
+        let mut order = NewOrder::new(1, 1, 1, price, 1, Side::Buy, order_type::LIMIT, tif::GTC);
+        order.side = 9;
+        let bytes = raw_bytes(&order);
+        assert_eq!(decode(&bytes).unwrap_err(), WireError::BadEnum("side"));
+    }
+
+    #[test]
+    fn test_decode_order_cancel() {
+        let cancel = OrderCancel::new(5, 10, 999);
+        let bytes = raw_bytes(&cancel);
+        match decode(&bytes).unwrap() {
+            WireMessage::OrderCancel(decoded) => {
+                assert_eq!({ decoded.trader_id }, 10);
+                assert_eq!({ decoded.target_order_id }, 999);
+            }
+            other => panic!("expected OrderCancel, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_decode_trade_update() {
+        let price = Price::from_str_decimal("50.00").unwrap();
+        let trade = TradeUpdate::new(1, 1, price, 10, 2, 3, 12345);
+        let bytes = raw_bytes(&trade);
+        match decode(&bytes).unwrap() {
+            WireMessage::Trade(decoded) => {
+                assert_eq!({ decoded.trade_id }, 1);
+                assert_eq!({ decoded.quantity }, 10);
+            }
+            other => panic!("expected Trade, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,210 @@
+// nexus_core/src/wire/frame.rs
+//
+// Connection framing on top of the SBE messages in `wire::messages`.
+//
+// Every connection begins with a fixed 4-byte magic plus a `u16` schema
+// version, sent exactly once. After that, each SBE message is already
+// length-delimited by its own `MessageHeader.msg_length`, so framing is
+// just "read enough bytes to cover the next header, then enough to cover
+// the whole message." `FrameDecoder` buffers partial TCP reads across
+// `feed()` calls and yields complete `WireMessage`s from `poll()`.
+
+use std::mem::size_of;
+
+use super::decode::{self, WireError, WireMessage};
+use super::messages::MessageHeader;
+
+/// Fixed magic prefixing every connection, once.
+pub const MAGIC: [u8; 4] = *b"NEXS";
+
+/// The schema version this build of the crate negotiates.
+pub const SCHEMA_VERSION: u16 = 1;
+
+/// Size of the one-time connection preamble: magic + version.
+pub const PREAMBLE_SIZE: usize = MAGIC.len() + size_of::<u16>();
+
+/// Errors from framing a byte stream into `WireMessage`s.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameError {
+    /// The connection preamble did not start with `MAGIC`.
+    BadMagic,
+    /// The peer's negotiated schema version is one we don't understand.
+    UnsupportedVersion { peer: u16, ours: u16 },
+    /// The framed message itself failed `wire::decode::decode`.
+    Wire(WireError),
+}
+
+impl From<WireError> for FrameError {
+    fn from(e: WireError) -> Self {
+        FrameError::Wire(e)
+    }
+}
+
+/// Encodes the one-time preamble and individual messages onto a byte stream.
+pub struct FrameEncoder;
+
+impl FrameEncoder {
+    /// Build the 6-byte connection preamble: `MAGIC` followed by
+    /// `SCHEMA_VERSION` as little-endian `u16`. Send this once, before any
+    /// framed message.
+    pub fn encode_preamble() -> [u8; PREAMBLE_SIZE] {
+        let mut out = [0u8; PREAMBLE_SIZE];
+        out[0..4].copy_from_slice(&MAGIC);
+        out[4..6].copy_from_slice(&SCHEMA_VERSION.to_le_bytes());
+        out
+    }
+
+    /// Frame a message for the wire. Messages are already self-delimiting
+    /// via `MessageHeader.msg_length`, so framing is just appending the raw
+    /// bytes — this exists so callers don't have to know that.
+    pub fn encode_message(bytes: &[u8], out: &mut Vec<u8>) {
+        out.extend_from_slice(bytes);
+    }
+}
+
+/// Buffers partial TCP reads and yields complete `WireMessage`s once the
+/// connection preamble has been validated.
+pub struct FrameDecoder {
+    buf: Vec<u8>,
+    preamble_validated: bool,
+}
+
+impl FrameDecoder {
+    pub fn new() -> Self {
+        Self {
+            buf: Vec::new(),
+            preamble_validated: false,
+        }
+    }
+
+    /// Append freshly-received bytes to the internal buffer.
+    pub fn feed(&mut self, bytes: &[u8]) {
+        self.buf.extend_from_slice(bytes);
+    }
+
+    /// Try to make progress on the buffered bytes.
+    ///
+    /// Returns `Ok(Some(msg))` for each complete message as it becomes
+    /// available, `Ok(None)` when more bytes are needed, and `Err` if the
+    /// preamble or a framed message is invalid (the connection should be
+    /// rejected/closed).
+    pub fn poll(&mut self) -> Result<Option<WireMessage>, FrameError> {
+        if !self.preamble_validated {
+            if self.buf.len() < PREAMBLE_SIZE {
+                return Ok(None);
+            }
+            if self.buf[0..4] != MAGIC {
+                return Err(FrameError::BadMagic);
+            }
+            let peer_version = u16::from_le_bytes(self.buf[4..6].try_into().unwrap());
+            if peer_version != SCHEMA_VERSION {
+                return Err(FrameError::UnsupportedVersion {
+                    peer: peer_version,
+                    ours: SCHEMA_VERSION,
+                });
+            }
+            self.buf.drain(0..PREAMBLE_SIZE);
+            self.preamble_validated = true;
+        }
+
+        if self.buf.len() < size_of::<MessageHeader>() {
+            return Ok(None);
+        }
+
+        // Peek the header to learn the full message length without
+        // consuming anything yet — we may not have the whole message.
+        let header = MessageHeader::decode_from(&self.buf)?;
+        let msg_length = header.msg_length as usize;
+        if self.buf.len() < msg_length {
+            return Ok(None);
+        }
+
+        let msg = decode::decode(&self.buf[..msg_length])?;
+        self.buf.drain(0..msg_length);
+        Ok(Some(msg))
+    }
+}
+
+impl Default for FrameDecoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Price, Side};
+    use crate::wire::messages::{order_type, tif, NewOrder};
+
+    fn order_bytes() -> Vec<u8> {
+        let price = Price::from_str_decimal("100.05").unwrap();
+        let order = NewOrder::new(1, 42, 7, price, 50, Side::Buy, order_type::LIMIT, tif::GTC);
+        let mut buf = vec![0u8; size_of::<NewOrder>()];
+        order.encode_to(&mut buf);
+        buf
+    }
+
+    #[test]
+    fn test_decode_single_message_after_preamble() {
+        let mut decoder = FrameDecoder::new();
+        decoder.feed(&FrameEncoder::encode_preamble());
+        decoder.feed(&order_bytes());
+
+        match decoder.poll().unwrap() {
+            Some(WireMessage::NewOrder(order)) => assert_eq!({ order.trader_id }, 42),
+            other => panic!("expected NewOrder, got {:?}", other),
+        }
+        assert!(decoder.poll().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_partial_reads_buffer_until_complete() {
+        let mut decoder = FrameDecoder::new();
+        let preamble = FrameEncoder::encode_preamble();
+        let msg = order_bytes();
+
+        // Feed the preamble split across two partial reads.
+        decoder.feed(&preamble[..2]);
+        assert!(decoder.poll().unwrap().is_none());
+        decoder.feed(&preamble[2..]);
+        assert!(decoder.poll().unwrap().is_none()); // Preamble done, no message yet.
+
+        // Feed the message split across two partial reads.
+        decoder.feed(&msg[..10]);
+        assert!(decoder.poll().unwrap().is_none());
+        decoder.feed(&msg[10..]);
+        assert!(matches!(decoder.poll().unwrap(), Some(WireMessage::NewOrder(_))));
+    }
+
+    #[test]
+    fn test_bad_magic_rejected() {
+        let mut decoder = FrameDecoder::new();
+        decoder.feed(b"XXXX\x01\x00");
+        assert_eq!(decoder.poll().unwrap_err(), FrameError::BadMagic);
+    }
+
+    #[test]
+    fn test_unsupported_version_rejected() {
+        let mut decoder = FrameDecoder::new();
+        let mut preamble = FrameEncoder::encode_preamble();
+        preamble[4..6].copy_from_slice(&99u16.to_le_bytes());
+        decoder.feed(&preamble);
+        assert_eq!(
+            decoder.poll().unwrap_err(),
+            FrameError::UnsupportedVersion { peer: 99, ours: SCHEMA_VERSION }
+        );
+    }
+
+    #[test]
+    fn test_multiple_messages_back_to_back() {
+        let mut decoder = FrameDecoder::new();
+        decoder.feed(&FrameEncoder::encode_preamble());
+        decoder.feed(&order_bytes());
+        decoder.feed(&order_bytes());
+
+        assert!(decoder.poll().unwrap().is_some());
+        assert!(decoder.poll().unwrap().is_some());
+        assert!(decoder.poll().unwrap().is_none());
+    }
+}
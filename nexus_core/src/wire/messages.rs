@@ -10,10 +10,8 @@
 //
 // L1 CACHE OPTIMIZATION:
 // A typical L1 data cache line on modern CPUs (Intel, AMD) is 64 bytes.
-// Our `NewOrder` struct is exactly 36 bytes. This means:
-//   - TWO complete NewOrder messages fit in a single L1 cache line.
-//   - When the matching engine reads an order, the NEXT order is likely
-//     already pre-fetched into L1 cache by the CPU's hardware prefetcher.
+// `NewOrder` is 48 bytes, so one still fits in a single L1 cache line, and
+// the matching engine's sequential reads stay hardware-prefetch-friendly.
 //   - This eliminates cache misses on sequential order processing,
 //     which is the #1 source of latency jitter in naive implementations.
 //
@@ -21,10 +19,14 @@
 //   - A JSON `{"type":"order","side":"buy","price":100.05,"qty":50}` is ~60+ bytes
 //     of UTF-8 text that must be parsed character-by-character into a Python dict
 //     (which itself allocates ~300+ bytes of heap memory for the dict + string keys).
-//   - That's 300+ bytes vs 36 bytes. Nearly 10× more cache pressure.
+//   - That's 300+ bytes vs 48 bytes. Still roughly 6× more cache pressure.
 
 use crate::types::{Price, Side};
+use crate::wire::decode::WireError;
 use std::fmt;
+use std::mem::size_of;
+use std::num::NonZeroU32;
+use std::ptr;
 
 // ---------------------------------------------------------------------------
 // Common Message Header (8 bytes)
@@ -57,12 +59,12 @@ pub mod msg_type {
 }
 
 // ---------------------------------------------------------------------------
-// NewOrder (36 bytes)
+// NewOrder (48 bytes)
 // ---------------------------------------------------------------------------
 
 /// Inbound order entry message.
 ///
-/// # Layout (36 bytes total)
+/// # Layout (48 bytes total)
 /// ```text
 /// Offset | Size | Field
 /// -------|------|----------------
@@ -75,12 +77,19 @@ pub mod msg_type {
 /// 33     |  1   | order_type (u8: 1=Limit, 2=Market)
 /// 34     |  1   | time_in_force (u8: 1=GTC, 2=IOC, 3=FOK)
 /// 35     |  1   | _padding
+/// 36     |  8   | stop_price (i64, fixed-point; 0 = absent)
+/// 44     |  4   | display_qty (u32; 0 = absent)
 /// ```
 ///
+/// `stop_price` and `display_qty` use the "0 = absent, else present"
+/// sentinel convention rather than a variable-length optional field, so the
+/// message stays a single fixed-size, zero-copy-castable struct. See
+/// `stop_price_opt`/`display_qty_opt`.
+///
 /// # Cache Performance
-/// At 36 bytes, exactly **1.78 orders fit per 64-byte L1 cache line**.
-/// The hardware prefetcher will load the next cache line while the current
-/// order is being processed, effectively giving us zero-latency sequential reads.
+/// At 48 bytes, **fewer than two `NewOrder`s fit per 64-byte L1 cache line**,
+/// but the layout is still a single hardware-prefetch-friendly block with no
+/// variable-length fields to parse.
 #[derive(Debug, Clone, Copy)]
 #[repr(C, packed)]
 pub struct NewOrder {
@@ -93,6 +102,10 @@ pub struct NewOrder {
     pub order_type: u8,
     pub time_in_force: u8,
     pub _padding: u8,
+    /// Stop-trigger price. `0` means "no stop" (a plain order).
+    pub stop_price: i64,
+    /// Visible/iceberg display quantity. `0` means "no display cap".
+    pub display_qty: u32,
 }
 
 /// Order type constants.
@@ -138,9 +151,24 @@ impl NewOrder {
             order_type: order_type_val,
             time_in_force,
             _padding: 0,
+            stop_price: 0,
+            display_qty: 0,
         }
     }
 
+    /// Attach a stop-trigger price (builder-style). A plain order never
+    /// needs this, so the base `new()` leaves it absent (`0`).
+    pub fn with_stop_price(mut self, stop_price: Price) -> Self {
+        self.stop_price = stop_price.raw();
+        self
+    }
+
+    /// Attach a display (iceberg) quantity (builder-style).
+    pub fn with_display_qty(mut self, display_qty: NonZeroU32) -> Self {
+        self.display_qty = display_qty.get();
+        self
+    }
+
     /// Extract the Side enum from the raw byte.
     pub fn side_enum(&self) -> Option<Side> {
         match self.side {
@@ -154,6 +182,22 @@ impl NewOrder {
     pub fn price_fixed(&self) -> Price {
         Price::new(self.price)
     }
+
+    /// The stop-trigger price, or `None` if this is a plain (non-stop) order.
+    /// `0` is the sentinel for "absent" — see the struct-level layout doc.
+    pub fn stop_price_opt(&self) -> Option<Price> {
+        let raw = self.stop_price;
+        if raw == 0 {
+            None
+        } else {
+            Some(Price::new(raw))
+        }
+    }
+
+    /// The display (iceberg) quantity, or `None` if no cap was set.
+    pub fn display_qty_opt(&self) -> Option<NonZeroU32> {
+        NonZeroU32::new(self.display_qty)
+    }
 }
 
 impl fmt::Display for NewOrder {
@@ -289,6 +333,418 @@ impl fmt::Display for TradeUpdate {
     }
 }
 
+// ---------------------------------------------------------------------------
+// Little-Endian Wire Encoding (byte-order portability)
+// ---------------------------------------------------------------------------
+//
+// The structs above are `#[repr(C, packed)]`, which fixes field ORDER and
+// padding but says nothing about byte ORDER — multi-byte fields are stored
+// in whatever endianness the host CPU uses. We define the wire format as
+// little-endian. On a little-endian host (the only kind this crate ships
+// on in practice) the in-memory layout already matches the wire, so
+// `encode_to`/`decode_from` take the zero-cost pointer-cast path. Only a
+// big-endian host pays for the explicit byteswap.
+
+impl MessageHeader {
+    /// Encode into `out` as little-endian bytes. `out` must be at least
+    /// `size_of::<MessageHeader>()` bytes.
+    pub fn encode_to(&self, out: &mut [u8]) {
+        #[cfg(target_endian = "little")]
+        {
+            let bytes: &[u8] = unsafe {
+                std::slice::from_raw_parts(self as *const Self as *const u8, size_of::<Self>())
+            };
+            out[..size_of::<Self>()].copy_from_slice(bytes);
+        }
+        #[cfg(not(target_endian = "little"))]
+        {
+            self.encode_to_le(out);
+        }
+    }
+
+    /// Decode a little-endian `MessageHeader` from `buf`.
+    pub fn decode_from(buf: &[u8]) -> Result<Self, WireError> {
+        if buf.len() < size_of::<Self>() {
+            return Err(WireError::TooShort);
+        }
+        #[cfg(target_endian = "little")]
+        {
+            Ok(unsafe { ptr::read_unaligned(buf.as_ptr() as *const Self) })
+        }
+        #[cfg(not(target_endian = "little"))]
+        {
+            Ok(Self::decode_from_le(buf))
+        }
+    }
+
+    /// Explicit little-endian byteswap path. Used unconditionally on
+    /// big-endian hosts, and exercised directly by tests on any host so the
+    /// portable path stays covered regardless of where `cargo test` runs.
+    fn encode_to_le(&self, out: &mut [u8]) {
+        out[0..2].copy_from_slice(&self.msg_length.to_le_bytes());
+        out[2] = self.msg_type;
+        out[3] = self.version;
+        out[4..8].copy_from_slice(&self.sequence_num.to_le_bytes());
+    }
+
+    fn decode_from_le(buf: &[u8]) -> Self {
+        Self {
+            msg_length: u16::from_le_bytes(buf[0..2].try_into().unwrap()),
+            msg_type: buf[2],
+            version: buf[3],
+            sequence_num: u32::from_le_bytes(buf[4..8].try_into().unwrap()),
+        }
+    }
+}
+
+impl NewOrder {
+    /// Encode into `out` as little-endian bytes. `out` must be at least
+    /// `size_of::<NewOrder>()` bytes.
+    pub fn encode_to(&self, out: &mut [u8]) {
+        #[cfg(target_endian = "little")]
+        {
+            let bytes: &[u8] = unsafe {
+                std::slice::from_raw_parts(self as *const Self as *const u8, size_of::<Self>())
+            };
+            out[..size_of::<Self>()].copy_from_slice(bytes);
+        }
+        #[cfg(not(target_endian = "little"))]
+        {
+            self.encode_to_le(out);
+        }
+    }
+
+    /// Decode a little-endian `NewOrder` from `buf`.
+    pub fn decode_from(buf: &[u8]) -> Result<Self, WireError> {
+        if buf.len() < size_of::<Self>() {
+            return Err(WireError::TooShort);
+        }
+        #[cfg(target_endian = "little")]
+        {
+            Ok(unsafe { ptr::read_unaligned(buf.as_ptr() as *const Self) })
+        }
+        #[cfg(not(target_endian = "little"))]
+        {
+            Ok(Self::decode_from_le(buf))
+        }
+    }
+
+    fn encode_to_le(&self, out: &mut [u8]) {
+        self.header.encode_to_le(&mut out[0..8]);
+        out[8..12].copy_from_slice(&self.trader_id.to_le_bytes());
+        out[12..20].copy_from_slice(&self.client_order_id.to_le_bytes());
+        out[20..28].copy_from_slice(&self.price.to_le_bytes());
+        out[28..32].copy_from_slice(&self.quantity.to_le_bytes());
+        out[32] = self.side;
+        out[33] = self.order_type;
+        out[34] = self.time_in_force;
+        out[35] = self._padding;
+        out[36..44].copy_from_slice(&self.stop_price.to_le_bytes());
+        out[44..48].copy_from_slice(&self.display_qty.to_le_bytes());
+    }
+
+    fn decode_from_le(buf: &[u8]) -> Self {
+        Self {
+            header: MessageHeader::decode_from_le(&buf[0..8]),
+            trader_id: u32::from_le_bytes(buf[8..12].try_into().unwrap()),
+            client_order_id: u64::from_le_bytes(buf[12..20].try_into().unwrap()),
+            price: i64::from_le_bytes(buf[20..28].try_into().unwrap()),
+            quantity: u32::from_le_bytes(buf[28..32].try_into().unwrap()),
+            side: buf[32],
+            order_type: buf[33],
+            time_in_force: buf[34],
+            _padding: buf[35],
+            stop_price: i64::from_le_bytes(buf[36..44].try_into().unwrap()),
+            display_qty: u32::from_le_bytes(buf[44..48].try_into().unwrap()),
+        }
+    }
+}
+
+impl OrderCancel {
+    /// Encode into `out` as little-endian bytes. `out` must be at least
+    /// `size_of::<OrderCancel>()` bytes.
+    pub fn encode_to(&self, out: &mut [u8]) {
+        #[cfg(target_endian = "little")]
+        {
+            let bytes: &[u8] = unsafe {
+                std::slice::from_raw_parts(self as *const Self as *const u8, size_of::<Self>())
+            };
+            out[..size_of::<Self>()].copy_from_slice(bytes);
+        }
+        #[cfg(not(target_endian = "little"))]
+        {
+            self.encode_to_le(out);
+        }
+    }
+
+    /// Decode a little-endian `OrderCancel` from `buf`.
+    pub fn decode_from(buf: &[u8]) -> Result<Self, WireError> {
+        if buf.len() < size_of::<Self>() {
+            return Err(WireError::TooShort);
+        }
+        #[cfg(target_endian = "little")]
+        {
+            Ok(unsafe { ptr::read_unaligned(buf.as_ptr() as *const Self) })
+        }
+        #[cfg(not(target_endian = "little"))]
+        {
+            Ok(Self::decode_from_le(buf))
+        }
+    }
+
+    fn encode_to_le(&self, out: &mut [u8]) {
+        self.header.encode_to_le(&mut out[0..8]);
+        out[8..12].copy_from_slice(&self.trader_id.to_le_bytes());
+        out[12..20].copy_from_slice(&self.target_order_id.to_le_bytes());
+    }
+
+    fn decode_from_le(buf: &[u8]) -> Self {
+        Self {
+            header: MessageHeader::decode_from_le(&buf[0..8]),
+            trader_id: u32::from_le_bytes(buf[8..12].try_into().unwrap()),
+            target_order_id: u64::from_le_bytes(buf[12..20].try_into().unwrap()),
+        }
+    }
+}
+
+impl TradeUpdate {
+    /// Encode into `out` as little-endian bytes. `out` must be at least
+    /// `size_of::<TradeUpdate>()` bytes.
+    pub fn encode_to(&self, out: &mut [u8]) {
+        #[cfg(target_endian = "little")]
+        {
+            let bytes: &[u8] = unsafe {
+                std::slice::from_raw_parts(self as *const Self as *const u8, size_of::<Self>())
+            };
+            out[..size_of::<Self>()].copy_from_slice(bytes);
+        }
+        #[cfg(not(target_endian = "little"))]
+        {
+            self.encode_to_le(out);
+        }
+    }
+
+    /// Decode a little-endian `TradeUpdate` from `buf`.
+    pub fn decode_from(buf: &[u8]) -> Result<Self, WireError> {
+        if buf.len() < size_of::<Self>() {
+            return Err(WireError::TooShort);
+        }
+        #[cfg(target_endian = "little")]
+        {
+            Ok(unsafe { ptr::read_unaligned(buf.as_ptr() as *const Self) })
+        }
+        #[cfg(not(target_endian = "little"))]
+        {
+            Ok(Self::decode_from_le(buf))
+        }
+    }
+
+    fn encode_to_le(&self, out: &mut [u8]) {
+        self.header.encode_to_le(&mut out[0..8]);
+        out[8..16].copy_from_slice(&self.trade_id.to_le_bytes());
+        out[16..24].copy_from_slice(&self.price.to_le_bytes());
+        out[24..28].copy_from_slice(&self.quantity.to_le_bytes());
+        out[28..32].copy_from_slice(&self.buy_trader_id.to_le_bytes());
+        out[32..36].copy_from_slice(&self.sell_trader_id.to_le_bytes());
+        out[36..44].copy_from_slice(&self.timestamp_ns.to_le_bytes());
+        out[44..48].copy_from_slice(&self._padding.to_le_bytes());
+    }
+
+    fn decode_from_le(buf: &[u8]) -> Self {
+        Self {
+            header: MessageHeader::decode_from_le(&buf[0..8]),
+            trade_id: u64::from_le_bytes(buf[8..16].try_into().unwrap()),
+            price: i64::from_le_bytes(buf[16..24].try_into().unwrap()),
+            quantity: u32::from_le_bytes(buf[24..28].try_into().unwrap()),
+            buy_trader_id: u32::from_le_bytes(buf[28..32].try_into().unwrap()),
+            sell_trader_id: u32::from_le_bytes(buf[32..36].try_into().unwrap()),
+            timestamp_ns: u64::from_le_bytes(buf[36..44].try_into().unwrap()),
+            _padding: u32::from_le_bytes(buf[44..48].try_into().unwrap()),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// serde integration (feature-gated)
+// ---------------------------------------------------------------------------
+//
+// These structs are `#[repr(C, packed)]`, so `#[derive(Serialize)]` won't
+// compile — derive-generated code takes `&self.field`, which is unsound on
+// an unaligned packed field. Each impl below copies fields to locals first
+// (the same rule `Display` above follows) and validates every byte-coded
+// discriminant through `wire::serde_support` so a malformed textual record
+// (JSON config, CSV fixture) fails with "Invalid code" instead of silently
+// producing an out-of-range `side`/`order_type`/`time_in_force`.
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for NewOrder {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::{Error, SerializeStruct};
+        let sequence_num = self.header.sequence_num;
+        let (trader_id, client_order_id, price, quantity) =
+            (self.trader_id, self.client_order_id, self.price, self.quantity);
+        let (side, order_type, time_in_force) = (self.side, self.order_type, self.time_in_force);
+        let (stop_price, display_qty) = (self.stop_price, self.display_qty);
+
+        crate::wire::serde_support::validate_side(side).map_err(S::Error::custom)?;
+        crate::wire::serde_support::validate_order_type(order_type).map_err(S::Error::custom)?;
+        crate::wire::serde_support::validate_tif(time_in_force).map_err(S::Error::custom)?;
+
+        let mut s = serializer.serialize_struct("NewOrder", 10)?;
+        s.serialize_field("sequence_num", &sequence_num)?;
+        s.serialize_field("trader_id", &trader_id)?;
+        s.serialize_field("client_order_id", &client_order_id)?;
+        s.serialize_field("price", &price)?;
+        s.serialize_field("quantity", &quantity)?;
+        s.serialize_field("side", &side)?;
+        s.serialize_field("order_type", &order_type)?;
+        s.serialize_field("time_in_force", &time_in_force)?;
+        s.serialize_field("stop_price", &stop_price)?;
+        s.serialize_field("display_qty", &display_qty)?;
+        s.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for NewOrder {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+
+        #[derive(serde::Deserialize)]
+        struct Fields {
+            sequence_num: u32,
+            trader_id: u32,
+            client_order_id: u64,
+            price: i64,
+            quantity: u32,
+            side: u8,
+            order_type: u8,
+            time_in_force: u8,
+            #[serde(default)]
+            stop_price: i64,
+            #[serde(default)]
+            display_qty: u32,
+        }
+
+        let f = Fields::deserialize(deserializer)?;
+        crate::wire::serde_support::validate_side(f.side).map_err(D::Error::custom)?;
+        crate::wire::serde_support::validate_order_type(f.order_type).map_err(D::Error::custom)?;
+        crate::wire::serde_support::validate_tif(f.time_in_force).map_err(D::Error::custom)?;
+
+        Ok(NewOrder {
+            header: MessageHeader {
+                msg_length: size_of::<NewOrder>() as u16,
+                msg_type: msg_type::NEW_ORDER,
+                version: 1,
+                sequence_num: f.sequence_num,
+            },
+            trader_id: f.trader_id,
+            client_order_id: f.client_order_id,
+            price: f.price,
+            quantity: f.quantity,
+            side: f.side,
+            order_type: f.order_type,
+            time_in_force: f.time_in_force,
+            _padding: 0,
+            stop_price: f.stop_price,
+            display_qty: f.display_qty,
+        })
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for OrderCancel {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+        let sequence_num = self.header.sequence_num;
+        let (trader_id, target_order_id) = (self.trader_id, self.target_order_id);
+
+        let mut s = serializer.serialize_struct("OrderCancel", 3)?;
+        s.serialize_field("sequence_num", &sequence_num)?;
+        s.serialize_field("trader_id", &trader_id)?;
+        s.serialize_field("target_order_id", &target_order_id)?;
+        s.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for OrderCancel {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(serde::Deserialize)]
+        struct Fields {
+            sequence_num: u32,
+            trader_id: u32,
+            target_order_id: u64,
+        }
+
+        let f = Fields::deserialize(deserializer)?;
+        Ok(OrderCancel {
+            header: MessageHeader {
+                msg_length: size_of::<OrderCancel>() as u16,
+                msg_type: msg_type::ORDER_CANCEL,
+                version: 1,
+                sequence_num: f.sequence_num,
+            },
+            trader_id: f.trader_id,
+            target_order_id: f.target_order_id,
+        })
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for TradeUpdate {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+        let sequence_num = self.header.sequence_num;
+        let (trade_id, price, quantity) = (self.trade_id, self.price, self.quantity);
+        let (buy_trader_id, sell_trader_id, timestamp_ns) =
+            (self.buy_trader_id, self.sell_trader_id, self.timestamp_ns);
+
+        let mut s = serializer.serialize_struct("TradeUpdate", 7)?;
+        s.serialize_field("sequence_num", &sequence_num)?;
+        s.serialize_field("trade_id", &trade_id)?;
+        s.serialize_field("price", &price)?;
+        s.serialize_field("quantity", &quantity)?;
+        s.serialize_field("buy_trader_id", &buy_trader_id)?;
+        s.serialize_field("sell_trader_id", &sell_trader_id)?;
+        s.serialize_field("timestamp_ns", &timestamp_ns)?;
+        s.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for TradeUpdate {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(serde::Deserialize)]
+        struct Fields {
+            sequence_num: u32,
+            trade_id: u64,
+            price: i64,
+            quantity: u32,
+            buy_trader_id: u32,
+            sell_trader_id: u32,
+            timestamp_ns: u64,
+        }
+
+        let f = Fields::deserialize(deserializer)?;
+        Ok(TradeUpdate {
+            header: MessageHeader {
+                msg_length: size_of::<TradeUpdate>() as u16,
+                msg_type: msg_type::TRADE_UPDATE,
+                version: 1,
+                sequence_num: f.sequence_num,
+            },
+            trade_id: f.trade_id,
+            price: f.price,
+            quantity: f.quantity,
+            buy_trader_id: f.buy_trader_id,
+            sell_trader_id: f.sell_trader_id,
+            timestamp_ns: f.timestamp_ns,
+            _padding: 0,
+        })
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Compile-time size assertions
 // ---------------------------------------------------------------------------
@@ -297,7 +753,7 @@ impl fmt::Display for TradeUpdate {
 // to update the spec, the build fails immediately.
 
 const _: () = assert!(std::mem::size_of::<MessageHeader>() == 8);
-const _: () = assert!(std::mem::size_of::<NewOrder>() == 36);
+const _: () = assert!(std::mem::size_of::<NewOrder>() == 48);
 const _: () = assert!(std::mem::size_of::<OrderCancel>() == 20);
 const _: () = assert!(std::mem::size_of::<TradeUpdate>() == 48);
 
@@ -307,7 +763,7 @@ mod tests {
 
     #[test]
     fn test_new_order_size() {
-        assert_eq!(std::mem::size_of::<NewOrder>(), 36);
+        assert_eq!(std::mem::size_of::<NewOrder>(), 48);
     }
 
     #[test]
@@ -358,7 +814,7 @@ mod tests {
                 std::mem::size_of::<NewOrder>(),
             )
         };
-        assert_eq!(bytes.len(), 36);
+        assert_eq!(bytes.len(), 48);
 
         // Deserialize by casting the pointer back (zero-copy).
         let recovered: &NewOrder = unsafe { &*(bytes.as_ptr() as *const NewOrder) };
@@ -370,4 +826,152 @@ mod tests {
         assert_eq!(recovered.side_enum(), Some(Side::Sell));
         assert_eq!(recovered.price_fixed(), price);
     }
+
+    // -------------------------------------------------------------------
+    // Little-Endian Encoding Tests (force the byteswap path)
+    // -------------------------------------------------------------------
+    // These call the `*_le` helpers directly so the portable path is
+    // exercised even when `cargo test` runs on a little-endian machine.
+
+    #[test]
+    fn test_message_header_le_roundtrip() {
+        let header = MessageHeader {
+            msg_length: 48,
+            msg_type: msg_type::NEW_ORDER,
+            version: 1,
+            sequence_num: 0xDEAD_BEEF,
+        };
+        let mut buf = [0u8; 8];
+        header.encode_to_le(&mut buf);
+        assert_eq!(&buf[4..8], &0xDEAD_BEEFu32.to_le_bytes());
+
+        let decoded = MessageHeader::decode_from_le(&buf);
+        assert_eq!({ decoded.msg_length }, 48);
+        assert_eq!({ decoded.sequence_num }, 0xDEAD_BEEF);
+    }
+
+    #[test]
+    fn test_new_order_le_roundtrip() {
+        let price = Price::from_str_decimal("100.05").unwrap();
+        let order = NewOrder::new(1, 42, 12345, price, 50, Side::Buy, order_type::LIMIT, tif::GTC);
+
+        let mut buf = [0u8; 48];
+        order.encode_to_le(&mut buf);
+        let decoded = NewOrder::decode_from_le(&buf);
+
+        assert_eq!({ decoded.trader_id }, 42);
+        assert_eq!({ decoded.client_order_id }, 12345);
+        assert_eq!({ decoded.quantity }, 50);
+        assert_eq!(decoded.side_enum(), Some(Side::Buy));
+        assert_eq!(decoded.price_fixed(), price);
+        assert_eq!(decoded.stop_price_opt(), None);
+        assert_eq!(decoded.display_qty_opt(), None);
+    }
+
+    #[test]
+    fn test_new_order_stop_price_and_display_qty_sentinels() {
+        let price = Price::from_str_decimal("100.05").unwrap();
+        let plain = NewOrder::new(1, 42, 12345, price, 50, Side::Buy, order_type::LIMIT, tif::GTC);
+        assert_eq!(plain.stop_price_opt(), None);
+        assert_eq!(plain.display_qty_opt(), None);
+
+        let stop = Price::from_str_decimal("99.00").unwrap();
+        let display_qty = NonZeroU32::new(10).unwrap();
+        let order = plain.with_stop_price(stop).with_display_qty(display_qty);
+        assert_eq!(order.stop_price_opt(), Some(stop));
+        assert_eq!(order.display_qty_opt(), Some(display_qty));
+
+        let mut buf = [0u8; 48];
+        order.encode_to_le(&mut buf);
+        let decoded = NewOrder::decode_from_le(&buf);
+        assert_eq!(decoded.stop_price_opt(), Some(stop));
+        assert_eq!(decoded.display_qty_opt(), Some(display_qty));
+    }
+
+    #[test]
+    fn test_order_cancel_le_roundtrip() {
+        let cancel = OrderCancel::new(5, 10, 999);
+        let mut buf = [0u8; 20];
+        cancel.encode_to_le(&mut buf);
+        let decoded = OrderCancel::decode_from_le(&buf);
+        assert_eq!({ decoded.trader_id }, 10);
+        assert_eq!({ decoded.target_order_id }, 999);
+    }
+
+    #[test]
+    fn test_trade_update_le_roundtrip() {
+        let price = Price::from_str_decimal("50.00").unwrap();
+        let trade = TradeUpdate::new(1, 7, price, 10, 2, 3, 99_999);
+        let mut buf = [0u8; 48];
+        trade.encode_to_le(&mut buf);
+        let decoded = TradeUpdate::decode_from_le(&buf);
+        assert_eq!({ decoded.trade_id }, 7);
+        assert_eq!(decoded.price_fixed(), price);
+        assert_eq!({ decoded.timestamp_ns }, 99_999);
+    }
+
+    #[test]
+    fn test_encode_to_decode_from_agree_with_le_path() {
+        // On this (little-endian) test machine, `encode_to`/`decode_from`
+        // take the pointer-cast fast path. Verify it agrees byte-for-byte
+        // with the explicit little-endian path used on big-endian hosts.
+        let price = Price::from_str_decimal("12.34").unwrap();
+        let order = NewOrder::new(9, 1, 2, price, 3, Side::Sell, order_type::LIMIT, tif::IOC);
+
+        let mut fast = [0u8; 48];
+        let mut swapped = [0u8; 48];
+        order.encode_to(&mut fast);
+        order.encode_to_le(&mut swapped);
+        assert_eq!(fast, swapped);
+
+        let decoded = NewOrder::decode_from(&fast).unwrap();
+        assert_eq!({ decoded.trader_id }, { order.trader_id });
+        assert_eq!(decoded.price_fixed(), price);
+    }
+
+    // -------------------------------------------------------------------
+    // serde round-trip tests (feature-gated)
+    // -------------------------------------------------------------------
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_new_order_serde_json_roundtrip() {
+        let price = Price::from_str_decimal("100.05").unwrap();
+        let order = NewOrder::new(1, 42, 12345, price, 50, Side::Buy, order_type::LIMIT, tif::GTC);
+        let json = serde_json::to_string(&order).unwrap();
+        let decoded: NewOrder = serde_json::from_str(&json).unwrap();
+        assert_eq!({ decoded.trader_id }, 42);
+        assert_eq!(decoded.price_fixed(), price);
+        assert_eq!(decoded.side_enum(), Some(Side::Buy));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_new_order_serde_rejects_invalid_side_code() {
+        let json = r#"{"sequence_num":1,"trader_id":1,"client_order_id":1,"price":100,"quantity":1,"side":9,"order_type":1,"time_in_force":1,"stop_price":0,"display_qty":0}"#;
+        let err = serde_json::from_str::<NewOrder>(json).unwrap_err();
+        assert!(err.to_string().contains("Invalid code"));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_order_cancel_serde_json_roundtrip() {
+        let cancel = OrderCancel::new(5, 10, 999);
+        let json = serde_json::to_string(&cancel).unwrap();
+        let decoded: OrderCancel = serde_json::from_str(&json).unwrap();
+        assert_eq!({ decoded.trader_id }, 10);
+        assert_eq!({ decoded.target_order_id }, 999);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_trade_update_serde_json_roundtrip() {
+        let price = Price::from_str_decimal("50.00").unwrap();
+        let trade = TradeUpdate::new(1, 7, price, 10, 2, 3, 99_999);
+        let json = serde_json::to_string(&trade).unwrap();
+        let decoded: TradeUpdate = serde_json::from_str(&json).unwrap();
+        assert_eq!({ decoded.trade_id }, 7);
+        assert_eq!(decoded.price_fixed(), price);
+        assert_eq!({ decoded.timestamp_ns }, 99_999);
+    }
 }
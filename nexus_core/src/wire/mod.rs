@@ -0,0 +1,13 @@
+// nexus_core/src/wire/mod.rs
+//
+// Wire protocol: SBE message layouts plus the decoders that validate bytes
+// coming off the network/WAL before they are trusted as typed structs.
+
+pub mod messages;
+pub mod decode;
+pub mod frame;
+#[cfg(feature = "serde")]
+pub mod serde_support;
+
+pub use decode::{decode, WireError, WireMessage};
+pub use frame::{FrameDecoder, FrameEncoder, FrameError};
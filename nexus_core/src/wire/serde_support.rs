@@ -0,0 +1,50 @@
+// nexus_core/src/wire/serde_support.rs
+//
+// Validated serde (de)serialization for the wire messages.
+//
+// `NewOrder`/`OrderCancel`/`TradeUpdate` are `#[repr(C, packed)]`, so a
+// derived `Serialize`/`Deserialize` impl won't compile — derive-generated
+// code borrows each field (`&self.field`), and a reference into a packed
+// struct's field is unsound the moment the field isn't 1-byte-aligned. So
+// each message implements `Serialize`/`Deserialize` by hand in `messages.rs`,
+// copying fields to locals first (same rule the zero-copy decode path
+// already follows), and routes every byte-coded discriminant through the
+// `try_from_u8`-style validators here. An unmapped code — e.g. a stray `0`
+// from a hand-edited JSON fixture — fails with "Invalid code" instead of
+// round-tripping into a `Side`/`OrderType`/`TimeInForce` that doesn't exist.
+
+use super::messages::{order_type, tif};
+use crate::types::Side;
+
+/// Validate a `side` byte via `Side::try_from_u8`-equivalent logic.
+pub fn validate_side(code: u8) -> Result<(), String> {
+    Side::from_u8(code).map(|_| ()).map_err(|_| format!("Invalid code: {code}"))
+}
+
+/// Validate an `order_type` byte against the known constants.
+pub fn validate_order_type(code: u8) -> Result<(), String> {
+    if code == order_type::LIMIT || code == order_type::MARKET {
+        Ok(())
+    } else {
+        Err(format!("Invalid code: {code}"))
+    }
+}
+
+/// Validate a `time_in_force` byte against the known constants.
+pub fn validate_tif(code: u8) -> Result<(), String> {
+    if code == tif::GTC || code == tif::IOC || code == tif::FOK {
+        Ok(())
+    } else {
+        Err(format!("Invalid code: {code}"))
+    }
+}
+
+/// Validate a `msg_type` byte against the one expected for the message being
+/// (de)serialized — e.g. a `NewOrder` payload must carry `msg_type::NEW_ORDER`.
+pub fn validate_msg_type(expected: u8, code: u8) -> Result<(), String> {
+    if code == expected {
+        Ok(())
+    } else {
+        Err(format!("Invalid code: {code}"))
+    }
+}